@@ -4,7 +4,9 @@ use crate::term_application_result::*;
 use crate::term_pointer::*;
 use crate::context::*;
 use crate::term_reference::*;
+use crate::fetish_error::*;
 use std::collections::HashMap;
+use std::hash::Hash;
 use multimap::MultiMap;
 
 use serde::{Serialize, Deserialize};
@@ -23,19 +25,17 @@ pub struct ApplicationTable {
 
 impl ApplicationTable {
     ///Constructs an initially-empty [`ApplicationTable`] for the given
-    ///function [`TypeId`] in the given [`Context`].
-    pub fn new(func_type_id : TypeId, ctxt : &Context) -> ApplicationTable {
-        if (!ctxt.is_vector_type(func_type_id)) {
-            ApplicationTable {
-                func_type_id,
-                table : MultiMap::new(),
-                result_to_application_map : MultiMap::new(),
-                arg_to_application_map : MultiMap::new(),
-                func_to_application_map : MultiMap::new(),
-            }
-        } else {
-            panic!();
-        }
+    ///function [`TypeId`] in the given [`Context`], or an error if the given
+    ///[`TypeId`] isn't a registered function type.
+    pub fn new(func_type_id : TypeId, ctxt : &Context) -> Result<ApplicationTable, FetishError> {
+        ctxt.type_info_directory.try_get_arg_and_ret_type_ids(func_type_id)?;
+        Result::Ok(ApplicationTable {
+            func_type_id,
+            table : MultiMap::new(),
+            result_to_application_map : MultiMap::new(),
+            arg_to_application_map : MultiMap::new(),
+            func_to_application_map : MultiMap::new(),
+        })
     }
 
     ///Yields all [`TermReference`] results recorded for the given [`TermApplication`].
@@ -82,4 +82,32 @@ impl ApplicationTable {
         self.func_to_application_map.insert(term_app.func_ptr.clone(), result.clone());
         self.table.insert(term_app, result_ref);
     }
+
+    ///Undoes the most recent [`Self::link`] of `term_app` to `result_ref`, if there was one.
+    pub fn unlink(&mut self, term_app : &TermApplication, result_ref : &TermReference) {
+        let result = TermApplicationResult {
+            term_app : term_app.clone(),
+            result_ref : result_ref.clone()
+        };
+
+        remove_last(&mut self.result_to_application_map, result_ref, &result);
+        remove_last(&mut self.arg_to_application_map, &term_app.arg_ref, &result);
+        remove_last(&mut self.func_to_application_map, &term_app.func_ptr, &result);
+        remove_last(&mut self.table, term_app, result_ref);
+    }
+}
+
+fn remove_last<K : Eq + Hash, V : PartialEq>(map : &mut MultiMap<K, V>, key : &K, value : &V) {
+    let now_empty = match (map.get_vec_mut(key)) {
+        Option::Some(values) => {
+            if let Option::Some(position) = values.iter().rposition(|other| other == value) {
+                values.remove(position);
+            }
+            values.is_empty()
+        },
+        Option::None => false
+    };
+    if (now_empty) {
+        map.remove(key);
+    }
 }
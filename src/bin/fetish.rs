@@ -0,0 +1,407 @@
+//! Interactive REPL for exploring an [`InterpreterAndEmbedderState`] over a small
+//! demonstration [`Context`] with scalar and vector types. Run `fetish` and type
+//! `:help` for a list of commands, or `fetish <saved-session>` to resume
+//! a session saved with `:save`.
+
+#![allow(unused_parens)]
+
+use std::fs;
+use std::io::{self, BufRead, Write};
+use fetish_lib::everything::*;
+use rand::prelude::*;
+use serde::{Serialize, Deserialize};
+
+const DEMO_SEED : u64 = 1337;
+const DEMO_VECTOR_DIMENSIONS : usize = 2;
+const DEMO_SKETCHED_DIMENSIONS : usize = 8;
+
+const HELP_TEXT : &str = "\
+<expr>              evaluate a term expression, such as `compose rotate rotate [1, 2]`
+                    or `(\\(x : Vec2). + x (rotate x)) [1, 2]`
+:type <expr>        show the type of a term expression
+:terms <type>       list the known terms of a function type, such as `Vec2 -> Vec2`
+:apps <expr>        list the recorded applications of the function which <expr> evaluates to
+:update             run a bayesian update step for all newly-evaluated terms
+:model <expr>       show the mean and uncertainty of the embedding of a function
+:closest <expr>     draw an embedding sample, and find the closest term to the function's schmeared hole
+:save <path>        save the context and the interpreter and embedder state
+:load <path>        load a context and state saved with :save
+:help               show this message
+:quit               exit";
+
+struct DemoPriorSpecification {}
+
+impl PriorSpecification for DemoPriorSpecification {
+    fn get_in_precision_multiplier(&self, _feat_dims : usize) -> f32 {
+        1.0f32
+    }
+    fn get_out_covariance_multiplier(&self, _out_dims : usize) -> f32 {
+        1.0f32
+    }
+    fn get_out_pseudo_observations(&self, out_dims : usize) -> f32 {
+        (out_dims as f32) + 4.0f32
+    }
+}
+
+///Everything needed to restore a REPL session.
+#[derive(Serialize, Deserialize)]
+struct SavedSession {
+    context : SerializedContext,
+    state : SerializedInterpreterAndEmbedderState
+}
+
+///Constructs the primitives of the demonstration context for every matching type
+///in the given [`TypeInfoDirectory`], namely the standard elementwise unary operators
+///for every vector type with a unary function type, elementwise arithmetic for every
+///vector type with a binary function type, along with instances of the
+///compose, fill, map, reduce and rotate templates and of the combinators
+///which lambda abstractions compile to.
+fn demo_primitives(type_info_directory : &TypeInfoDirectory) -> Result<Vec<Box<dyn FuncImpl>>, FetishError> {
+    let mut result : Vec<Box<dyn FuncImpl>> = Vec::new();
+    for type_id in 0..type_info_directory.get_total_num_types() {
+        if (type_info_directory.is_vector_type(type_id) && type_info_directory.has_func_type(type_id, type_id)) {
+            for operator in standard_unary_operators() {
+                result.push(Box::new(UnaryFuncImpl {
+                    elem_type : type_id,
+                    f : operator
+                }));
+            }
+            let unary_type_id = type_info_directory.get_func_type_id(type_id, type_id);
+            if (type_info_directory.has_func_type(type_id, unary_type_id)) {
+                let operators : Vec<Box<dyn BinaryArrayOperator>> = vec![Box::new(AddOperator {}),
+                                                                         Box::new(SubOperator {}),
+                                                                         Box::new(MulOperator {})];
+                for operator in operators {
+                    result.push(Box::new(BinaryFuncImpl {
+                        elem_type : type_id,
+                        f : operator
+                    }));
+                }
+            }
+        }
+    }
+    let mut templates : Vec<Box<dyn PrimitiveTemplate>> = vec![Box::new(ComposeTemplate {}), Box::new(FillTemplate {}),
+                                                                Box::new(MapTemplate {}), Box::new(ReduceTemplate {}),
+                                                                Box::new(RotateTemplate {})];
+    templates.append(&mut combinator_templates());
+    for template in templates.iter() {
+        result.append(&mut instantiate_template(template.as_ref(), type_info_directory)?);
+    }
+    Result::Ok(result)
+}
+
+///Builds the demonstration [`Context`], with a `Scalar` type and a `Vec2` type along with
+///their standard constants, and the function types needed by [`demo_primitives`].
+fn demo_context() -> Result<Context, Box<dyn std::error::Error>> {
+    let mut builder = ContextBuilder::new(DEMO_SEED);
+    let scalar_t = builder.add_vector_type(1);
+    let vector_t = builder.add_vector_type(DEMO_VECTOR_DIMENSIONS);
+    builder.add_type_alias("Scalar", scalar_t)?;
+    builder.add_type_alias("Vec2", vector_t)?;
+    builder.add_standard_constants(scalar_t)?;
+    builder.add_standard_constants(vector_t)?;
+
+    for type_text in ["Scalar -> Scalar -> Scalar", "Vec2 -> Vec2 -> Vec2", "Scalar -> Vec2",
+                      "(Scalar -> Scalar) -> Vec2 -> Vec2", "(Scalar -> Scalar -> Scalar) -> Scalar -> Vec2 -> Scalar",
+                      "(Vec2 -> Vec2) -> (Vec2 -> Vec2) -> Vec2 -> Vec2",
+                      "(Vec2 -> Vec2 -> Vec2) -> (Vec2 -> Vec2) -> Vec2 -> Vec2"].iter() {
+        builder.parse_type(type_text)?;
+    }
+
+    //Tracks the (feature, sketched) dimensions of every type, since function types
+    //can only be sketched down to fewer dimensions than their base space has.
+    let mut dimensions : Vec<(usize, usize)> = Vec::new();
+    for type_id in 0..builder.get_type_info_directory().get_total_num_types() {
+        match (builder.get_type_info_directory().get_type(type_id)) {
+            Type::FuncType(arg_type_id, ret_type_id) => {
+                let base_dimensions = dimensions[arg_type_id].0 * dimensions[ret_type_id].1;
+                let sketched_dimensions = std::cmp::min(DEMO_SKETCHED_DIMENSIONS, (base_dimensions / 4) * 2);
+                builder.add_fourier_features(type_id, sketched_dimensions, 1.0f32)?;
+                builder.add_sketched_linear_features(type_id, sketched_dimensions, 1.0f32)?;
+                builder.set_random_sketcher(type_id, sketched_dimensions, 1.0f32)?;
+                builder.set_prior_specifications(type_id, Box::new(DemoPriorSpecification {}),
+                                                          Box::new(DemoPriorSpecification {}))?;
+                dimensions.push((sketched_dimensions * 2, sketched_dimensions));
+            },
+            Type::VecType(vector_dimensions) => {
+                builder.add_fourier_features(type_id, vector_dimensions * 2, 1.0f32)?;
+                builder.add_sketched_linear_features(type_id, vector_dimensions * 2, 1.0f32)?;
+                dimensions.push((vector_dimensions * 4, vector_dimensions));
+            },
+            Type::ProductType(_) => {
+                return Result::Err(format!("product type {} has no demo feature space",
+                                           builder.get_type_info_directory().display_type(type_id)).into());
+            }
+        }
+    }
+
+    for primitive in demo_primitives(builder.get_type_info_directory())? {
+        builder.add_primitive(primitive);
+    }
+    builder.build().map_err(Box::from)
+}
+
+fn load_session(path : &str) -> Result<(Context, SerializedInterpreterAndEmbedderState), String> {
+    let bytes = fs::read(path).map_err(|err| err.to_string())?;
+    let saved : SavedSession = bincode::deserialize(&bytes).map_err(|err| err.to_string())?;
+    let type_info_directory = saved.context.type_info_directory.clone().deserialize();
+    let primitives = demo_primitives(&type_info_directory).map_err(|err| err.to_string())?;
+    let ctxt = saved.context.deserialize(primitives).map_err(|err| err.to_string())?;
+    Result::Ok((ctxt, saved.state))
+}
+
+///What the REPL should do after executing a command.
+enum Outcome {
+    Output(String),
+    Load(Box<Context>, SerializedInterpreterAndEmbedderState),
+    Quit
+}
+
+struct Repl<'a> {
+    ctxt : &'a Context,
+    state : InterpreterAndEmbedderState<'a>,
+    rng : StdRng
+}
+
+impl<'a> Repl<'a> {
+    fn new(ctxt : &'a Context, state : InterpreterAndEmbedderState<'a>) -> Repl<'a> {
+        Repl {
+            ctxt,
+            state,
+            rng : StdRng::seed_from_u64(DEMO_SEED)
+        }
+    }
+
+    fn display_term_ref(&self, term_ref : &TermReference) -> String {
+        let displayed = term_ref.display(&self.state.interpreter_state);
+        match (term_ref) {
+            //Vectors of named types already display their type
+            TermReference::VecRef(type_id, _) if self.ctxt.type_info_directory.get_vector_metadata(*type_id).is_some() => displayed,
+            _ => format!("{} : {}", displayed, self.ctxt.display_type(term_ref.get_type()))
+        }
+    }
+
+    fn evaluate_to_func(&mut self, text : &str) -> Result<TermPointer, String> {
+        match (self.state.evaluate_expression(text).map_err(|err| err.to_string())?) {
+            TermReference::FuncRef(func_ptr) => Result::Ok(func_ptr),
+            _ => Result::Err(format!("\"{}\" is not a function", text))
+        }
+    }
+
+    fn get_model(&mut self, text : &str) -> Result<&TermModel<'_>, String> {
+        let func_ptr = self.evaluate_to_func(text)?;
+        if (!self.state.embedder_state.has_embedding(func_ptr)) {
+            return Result::Err(format!("\"{}\" has no embedding yet, run :update first", text));
+        }
+        Result::Ok(self.state.embedder_state.get_embedding(func_ptr))
+    }
+
+    fn list_terms(&self, text : &str) -> Result<String, String> {
+        let type_id = self.ctxt.parse_type(text).map_err(|err| err.to_string())?;
+        if (!self.ctxt.is_func_type(type_id)) {
+            return Result::Err(String::from("only terms of function types are stored"));
+        }
+        let mut lines = Vec::new();
+        let primitive_type_space = self.ctxt.primitive_directory.primitive_type_spaces.get(&type_id).unwrap();
+        for index in 0..primitive_type_space.terms.len() {
+            let term_ptr = TermPointer {
+                type_id,
+                index : TermIndex::Primitive(index)
+            };
+            lines.push(format!("primitive {}: {}", index, term_ptr.display(&self.state.interpreter_state)));
+        }
+        let type_space = self.state.interpreter_state.type_spaces.get(&type_id).unwrap();
+        for index in 0..type_space.get_num_terms() {
+            lines.push(format!("term {}: {}", index, type_space.get(index).display(&self.state.interpreter_state)));
+        }
+        Result::Ok(lines.join("\n"))
+    }
+
+    fn list_applications(&mut self, text : &str) -> Result<String, String> {
+        let func_ptr = self.evaluate_to_func(text)?;
+        let lines : Vec<String> = self.state.interpreter_state.get_app_results_with_func(func_ptr).iter()
+            .map(|app_result| format!("{} => {}", app_result.get_arg_ref().display(&self.state.interpreter_state),
+                                                  app_result.get_ret_ref().display(&self.state.interpreter_state)))
+            .collect();
+        Result::Ok(lines.join("\n"))
+    }
+
+    fn show_model(&mut self, text : &str) -> Result<String, String> {
+        let model = self.get_model(text)?;
+        let mean = model.get_mean_as_vec().to_owned();
+        let uncertainty = model.get_schmear().flatten().covariance.diag().sum();
+        Result::Ok(format!("mean: {}\nuncertainty (covariance trace): {}", mean, uncertainty))
+    }
+
+    fn find_closest(&mut self, text : &str) -> Result<String, String> {
+        let hole = self.get_model(text)?.get_schmeared_hole();
+        let sampled_state = self.state.embedder_state.sample(&mut self.rng);
+        match (hole.get_closest_term(&sampled_state)) {
+            Option::Some((term_ref, dist)) => Result::Ok(format!("{} (squared distance {})",
+                                                                 self.display_term_ref(&term_ref), dist)),
+            Option::None => Result::Err(String::from("no terms of that type have embeddings"))
+        }
+    }
+
+    fn save(&mut self, path : &str) -> Result<String, String> {
+        let placeholder = InterpreterAndEmbedderState::new(self.ctxt);
+        let state = std::mem::replace(&mut self.state, placeholder).serialize();
+        let saved = SavedSession {
+            context : self.ctxt.serialize(),
+            state
+        };
+        let bytes = bincode::serialize(&saved);
+        self.state = saved.state.deserialize(self.ctxt);
+        fs::write(path, bytes.map_err(|err| err.to_string())?).map_err(|err| err.to_string())?;
+        Result::Ok(format!("saved to {}", path))
+    }
+
+    fn execute(&mut self, line : &str) -> Result<Outcome, String> {
+        let line = line.trim();
+        let (command, rest) = match (line.find(char::is_whitespace)) {
+            Option::Some(split) => (&line[..split], line[split..].trim()),
+            Option::None => (line, "")
+        };
+        let output = match (command) {
+            "" => String::new(),
+            ":quit" | ":q" => return Result::Ok(Outcome::Quit),
+            ":help" => String::from(HELP_TEXT),
+            ":type" => {
+                let expr = TermExpression::parse(rest).map_err(|err| err.to_string())?;
+                let possible_types = expr.get_possible_types(self.ctxt).map_err(|err| err.to_string())?;
+                let displayed : Vec<String> = possible_types.iter().map(|type_id| self.ctxt.display_type(*type_id)).collect();
+                displayed.join(" | ")
+            },
+            ":terms" => self.list_terms(rest)?,
+            ":apps" => self.list_applications(rest)?,
+            ":update" => {
+                let num_terms = self.state.newly_evaluated_terms.terms.len();
+                self.state.bayesian_update_step();
+                self.state.clear_newly_received();
+                format!("updated embeddings with {} new terms", num_terms)
+            },
+            ":model" => self.show_model(rest)?,
+            ":closest" => self.find_closest(rest)?,
+            ":save" => self.save(rest)?,
+            ":load" => {
+                let (ctxt, state) = load_session(rest)?;
+                return Result::Ok(Outcome::Load(Box::new(ctxt), state));
+            },
+            _ if command.starts_with(':') => return Result::Err(format!("unknown command {}, try :help", command)),
+            _ => {
+                let result_ref = self.state.evaluate_expression(line).map_err(|err| err.to_string())?;
+                self.display_term_ref(&result_ref)
+            }
+        };
+        Result::Ok(Outcome::Output(output))
+    }
+}
+
+///Runs the REPL over the given [`Context`] until the user quits or loads another session,
+///in which case the loaded session is returned.
+fn run(ctxt : &Context, saved_state : Option<SerializedInterpreterAndEmbedderState>)
+      -> Option<(Context, SerializedInterpreterAndEmbedderState)> {
+    let state = match (saved_state) {
+        Option::Some(saved_state) => saved_state.deserialize(ctxt),
+        Option::None => InterpreterAndEmbedderState::new(ctxt)
+    };
+    let mut repl = Repl::new(ctxt, state);
+    let stdin = io::stdin();
+    loop {
+        print!("fetish> ");
+        io::stdout().flush().unwrap();
+        let mut line = String::new();
+        if (stdin.lock().read_line(&mut line).unwrap_or(0) == 0) {
+            return Option::None;
+        }
+        match (repl.execute(&line)) {
+            Result::Ok(Outcome::Output(output)) => {
+                if (!output.is_empty()) {
+                    println!("{}", output);
+                }
+            },
+            Result::Ok(Outcome::Load(ctxt, state)) => return Option::Some((*ctxt, state)),
+            Result::Ok(Outcome::Quit) => return Option::None,
+            Result::Err(message) => println!("error: {}", message)
+        }
+    }
+}
+
+fn main() {
+    let args : Vec<String> = std::env::args().collect();
+    let mut session = match (args.get(1)) {
+        Option::Some(path) => load_session(path).map(|(ctxt, state)| (ctxt, Option::Some(state))),
+        Option::None => demo_context().map(|ctxt| (ctxt, Option::None)).map_err(|err| err.to_string())
+    };
+    loop {
+        match (session) {
+            Result::Ok((ctxt, saved_state)) => {
+                match (run(&ctxt, saved_state)) {
+                    Option::Some((ctxt, state)) => session = Result::Ok((ctxt, Option::Some(state))),
+                    Option::None => return
+                }
+            },
+            Result::Err(message) => {
+                eprintln!("error: {}", message);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(repl : &mut Repl, line : &str) -> String {
+        match (repl.execute(line)) {
+            Result::Ok(Outcome::Output(output)) => output,
+            Result::Ok(_) => panic!("unexpected outcome for {}", line),
+            Result::Err(message) => panic!("{}", message)
+        }
+    }
+
+    #[test]
+    fn session_evaluates_updates_and_saves() {
+        let ctxt = demo_context().unwrap();
+        let mut repl = Repl::new(&ctxt, InterpreterAndEmbedderState::new(&ctxt));
+        assert_eq!(output(&mut repl, "compose rotate rotate [1, 2]"), "[1, 2] : Vec2");
+        assert_eq!(output(&mut repl, ":type + [1, 2]"), "(Vec2 -> Vec2)");
+        assert_eq!(output(&mut repl, ":apps (rotate : Vec2 -> Vec2)"), "[1, 2] => [2, 1]\n[2, 1] => [1, 2]");
+        assert!(output(&mut repl, ":terms Vec2 -> Vec2").contains("rotate"));
+        assert_eq!(output(&mut repl, "(\\(x : Vec2). + x (rotate x)) [1, 2]"), "[3, 3] : Vec2");
+        assert_eq!(output(&mut repl, "map negate [1, -2]"), "[-1, 2] : Vec2");
+        assert_eq!(output(&mut repl, "+ one e1"), "[1, 2] : Vec2");
+        assert!(repl.execute(":model compose rotate").is_err());
+
+        output(&mut repl, ":update");
+        assert!(output(&mut repl, ":model compose rotate").starts_with("mean: "));
+        assert!(output(&mut repl, ":closest compose rotate").contains("(Vec2 -> Vec2)"));
+
+        let file_name = format!("fetish_{}_session_evaluates_updates_and_saves.bin", std::process::id());
+        let path = std::env::temp_dir().join(file_name);
+        let path = path.to_str().unwrap();
+        output(&mut repl, &format!(":save {}", path));
+        match (repl.execute(&format!(":load {}", path))) {
+            Result::Ok(Outcome::Load(loaded_ctxt, state)) => {
+                let state = state.deserialize(&loaded_ctxt);
+                let mut loaded_repl = Repl::new(&loaded_ctxt, state);
+                assert!(output(&mut loaded_repl, ":apps (rotate : Vec2 -> Vec2)").contains("[2, 1] => [1, 2]"));
+            },
+            _ => panic!()
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn named_vectors_display_their_type_once() {
+        let mut builder = ContextBuilder::new(DEMO_SEED);
+        let signal_t = builder.add_named_vector_type(4, VectorTypeMetadata::new("Signal")).unwrap();
+        builder.add_fourier_features(signal_t, 8, 1.0f32).unwrap();
+        builder.add_sketched_linear_features(signal_t, 8, 1.0f32).unwrap();
+        let ctxt = builder.build().unwrap();
+        let repl = Repl::new(&ctxt, InterpreterAndEmbedderState::new(&ctxt));
+        let vec_ref = TermReference::VecRef(signal_t, to_noisy(ndarray::array![1.0f32, 2.0f32, 3.0f32, 4.0f32].view()));
+        assert_eq!(repl.display_term_ref(&vec_ref), "[1, 2, 3, 4] : Signal");
+    }
+}
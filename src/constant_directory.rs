@@ -0,0 +1,134 @@
+use crate::type_id::*;
+use crate::term_reference::*;
+use crate::array_utils::*;
+use crate::fetish_error::*;
+use ndarray::*;
+
+use serde::{Serialize, Deserialize};
+
+///A literal vector term which may be referred to by name, such as `zero` or `pi`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct NamedConstant {
+    pub name : String,
+    ///The [`TermReference::VecRef`] which the name stands for.
+    pub value : TermReference
+}
+
+///A directory of [`NamedConstant`]s, which are the nullary counterparts of the
+///primitives in a [`crate::primitive_directory::PrimitiveDirectory`]. As with primitives,
+///the same name may be used for constants of different types, such as `zero` for
+///every vector type, but not twice for the same type.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct ConstantDirectory {
+    pub constants : Vec<NamedConstant>
+}
+
+impl ConstantDirectory {
+    ///Constructs an empty [`ConstantDirectory`].
+    pub fn new() -> ConstantDirectory {
+        ConstantDirectory {
+            constants : Vec::new()
+        }
+    }
+
+    ///Adds a constant with the given name for the vector of the given elements, of the given
+    ///vector type within the given [`TypeInfoDirectory`]. Yields an error if the type isn't a vector
+    ///type of the right dimension, if any element isn't finite, or if the type already has
+    ///a constant of the same name.
+    pub fn add(&mut self, name : &str, type_id : TypeId, elements : &[f32],
+               type_info_directory : &TypeInfoDirectory) -> Result<(), FetishError> {
+        let dimension = type_info_directory.try_get_dimension(type_id)?;
+        if (elements.len() != dimension) {
+            return Result::Err(FetishError::DimensionMismatch {
+                type_id,
+                expected : dimension,
+                actual : elements.len()
+            });
+        }
+        if (elements.iter().any(|element| !element.is_finite())) {
+            return Result::Err(FetishError::NonFiniteValue { name : String::from(name) });
+        }
+        if (self.find_constants(name).iter().any(|value| value.get_type() == type_id)) {
+            return Result::Err(FetishError::DuplicateConstant {
+                name : String::from(name),
+                type_id
+            });
+        }
+        let vec = Array::from(elements.to_vec());
+        self.constants.push(NamedConstant {
+            name : String::from(name),
+            value : TermReference::VecRef(type_id, to_noisy(vec.view()))
+        });
+        Result::Ok(())
+    }
+
+    ///Adds the standard constants for the given vector type, namely `zero`, `one` and `pi`
+    ///for the vectors filled with the respective value, and, if the type has more than one
+    ///dimension, the unit basis vectors `e0`, `e1`, and so on.
+    pub fn add_standard_constants(&mut self, type_id : TypeId,
+                                  type_info_directory : &TypeInfoDirectory) -> Result<(), FetishError> {
+        let dimension = type_info_directory.try_get_dimension(type_id)?;
+        self.add("zero", type_id, &vec![0.0f32; dimension], type_info_directory)?;
+        self.add("one", type_id, &vec![1.0f32; dimension], type_info_directory)?;
+        self.add("pi", type_id, &vec![std::f32::consts::PI; dimension], type_info_directory)?;
+        if (dimension > 1) {
+            for i in 0..dimension {
+                let mut basis_vector = vec![0.0f32; dimension];
+                basis_vector[i] = 1.0f32;
+                self.add(&format!("e{}", i), type_id, &basis_vector, type_info_directory)?;
+            }
+        }
+        Result::Ok(())
+    }
+
+    ///Yields the values of every constant with the given name, across all types.
+    pub fn find_constants(&self, name : &str) -> Vec<&TermReference> {
+        self.constants.iter()
+                      .filter(|constant| constant.name == name)
+                      .map(|constant| &constant.value)
+                      .collect()
+    }
+
+    ///Yields the values of every constant of the given [`TypeId`], in the order they were added.
+    pub fn get_constants_of_type(&self, type_id : TypeId) -> Vec<TermReference> {
+        self.constants.iter()
+                      .filter(|constant| constant.value.get_type() == type_id)
+                      .map(|constant| constant.value.clone())
+                      .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn standard_constants_are_named_per_type() {
+        let builder = get_test_vector_only_context_builder();
+        let type_info_directory = builder.get_type_info_directory();
+        let mut constants = ConstantDirectory::new();
+        constants.add_standard_constants(TEST_SCALAR_T, type_info_directory).unwrap();
+        constants.add_standard_constants(TEST_VECTOR_T, type_info_directory).unwrap();
+
+        assert_eq!(constants.get_constants_of_type(TEST_SCALAR_T).len(), 3);
+        assert_eq!(constants.get_constants_of_type(TEST_VECTOR_T).len(), 5);
+        assert_eq!(constants.find_constants("zero").len(), 2);
+        assert_equal_vector_term(constants.find_constants("e1")[0].clone(), array![0.0f32, 1.0f32].view());
+    }
+
+    #[test]
+    fn malformed_constants_are_rejected() {
+        let builder = get_test_vector_only_context_builder();
+        let type_info_directory = builder.get_type_info_directory();
+        let mut constants = ConstantDirectory::new();
+        constants.add("half", TEST_SCALAR_T, &[0.5f32], type_info_directory).unwrap();
+        assert_eq!(constants.add("half", TEST_SCALAR_T, &[0.25f32], type_info_directory),
+                   Result::Err(FetishError::DuplicateConstant { name : String::from("half"), type_id : TEST_SCALAR_T }));
+        assert_eq!(constants.add("half", TEST_VECTOR_T, &[0.5f32], type_info_directory),
+                   Result::Err(FetishError::DimensionMismatch { type_id : TEST_VECTOR_T, expected : 2, actual : 1 }));
+        assert_eq!(constants.add("inf", TEST_SCALAR_T, &[f32::INFINITY], type_info_directory),
+                   Result::Err(FetishError::NonFiniteValue { name : String::from("inf") }));
+        assert_eq!(constants.constants.len(), 1);
+    }
+}
@@ -4,23 +4,80 @@ use crate::func_impl::*;
 use crate::function_space_info::*;
 use crate::feature_space_info::*;
 use crate::primitive_directory::*;
+use crate::constant_directory::*;
 use crate::primitive_term_pointer::*;
 use crate::prior_directory::*;
 use crate::prior_specification::*;
+use crate::context_builder::*;
+use crate::fetish_error::*;
+use crate::type_parser::*;
+
+use serde::{Serialize, Deserialize};
 
 ///Stores interpreter-global context information, such as the
 ///collection of all types in the language, the collection of all
-///primitives, and the definitions of their associated featurization
-///maps. See [`TypeInfoDirectory`], [`SpaceInfoDirectory`], [`PrimitiveDirectory`]
-///for these individual components.
+///primitives and named constants, and the definitions of their associated featurization
+///maps. See [`TypeInfoDirectory`], [`SpaceInfoDirectory`], [`PrimitiveDirectory`],
+///[`ConstantDirectory`] for these individual components. A [`crate::context_builder::ContextBuilder`]
+///may be used to assemble these in a way that's guaranteed to be consistent.
 pub struct Context {
     pub type_info_directory : TypeInfoDirectory,
     pub space_info_directory : SpaceInfoDirectory,
     pub primitive_directory : PrimitiveDirectory,
+    pub constant_directory : ConstantDirectory,
     pub prior_directory : PriorDirectory
 }
 
+///Serialized form of a [`Context`]. Everything is stored exactly, including
+///randomly-drawn sketchers and feature mappings, so that [`crate::term_model::TermModel`]s
+///learned against the original [`Context`] remain meaningful against the restored one.
+///The exception are the primitives, which are stored by [`PrimitiveSignature`] and
+///must be re-supplied to [`SerializedContext::deserialize`].
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SerializedContext {
+    pub type_info_directory : SerializedTypeInfoDirectory,
+    pub space_info_directory : SerializedSpaceInfoDirectory,
+    pub primitive_directory : SerializedPrimitiveDirectory,
+    #[serde(default)]
+    pub constant_directory : ConstantDirectory,
+    pub prior_directory : SerializedPriorDirectory
+}
+
+impl SerializedContext {
+    ///Restores the serialized [`Context`], binding the given [`FuncImpl`]s to the
+    ///positions of the primitives with matching [`PrimitiveSignature`]s. Yields an error
+    ///if any type lacks a feature space, or if the restored priors don't cover the
+    ///dimensions of the restored spaces, as well as for any mismatch between the primitives.
+    pub fn deserialize(self, primitives : Vec<Box<dyn FuncImpl>>) -> Result<Context, FetishError> {
+        let type_info_directory = self.type_info_directory.deserialize();
+        let space_info_directory = self.space_info_directory.deserialize();
+        let num_feature_spaces = space_info_directory.feature_spaces.len();
+        if (num_feature_spaces < type_info_directory.get_total_num_types()) {
+            return Result::Err(ContextBuildError::MissingFeatureSpace(num_feature_spaces).into());
+        }
+        let primitive_directory = self.primitive_directory.deserialize(primitives, &type_info_directory)?;
+        let prior_directory = self.prior_directory.deserialize(&type_info_directory, &space_info_directory)?;
+        Result::Ok(Context {
+            type_info_directory,
+            space_info_directory,
+            primitive_directory,
+            constant_directory : self.constant_directory,
+            prior_directory
+        })
+    }
+}
+
 impl Context {
+    pub fn serialize(&self) -> SerializedContext {
+        SerializedContext {
+            type_info_directory : self.type_info_directory.serialize(),
+            space_info_directory : self.space_info_directory.serialize(),
+            primitive_directory : self.primitive_directory.serialize(),
+            constant_directory : self.constant_directory.clone(),
+            prior_directory : self.prior_directory.serialize(self)
+        }
+    }
+
     //Prior information
     ///Gets the [`PriorSpecification`] for the [`Elaborator`] of the given type
     pub fn get_elaborator_prior_specification(&self, type_id : TypeId) -> &dyn PriorSpecification {
@@ -71,7 +128,7 @@ impl Context {
                     out_feat_info : ret_feat_info
                 }
             },
-            Type::VecType(_) => {
+            _ => {
                 panic!();
             }
         }
@@ -83,10 +140,29 @@ impl Context {
     pub fn get_func_type_id(&self, arg_type_id : TypeId, ret_type_id : TypeId) -> TypeId {
         self.type_info_directory.get_func_type_id(arg_type_id, ret_type_id)
     }
+    ///Like [`Self::get_func_type_id`], but yields an error if the function type doesn't exist.
+    pub fn try_get_func_type_id(&self, arg_type_id : TypeId, ret_type_id : TypeId) -> Result<TypeId, FetishError> {
+        self.type_info_directory.try_get_func_type_id(arg_type_id, ret_type_id)
+    }
     ///Given a [`TypeId`], yields the [`Type`] struct describing the type.
     pub fn get_type(&self, id : TypeId) -> Type {
         self.type_info_directory.get_type(id)
     }
+    ///Parses a textual type expression such as `(2 -> (2 -> Scalar))` (see
+    ///[`crate::type_parser::TypeExpression`]), and yields its [`TypeId`]. Unlike
+    ///[`TypeInfoDirectory::parse_type`], every mentioned type must already exist.
+    pub fn parse_type(&self, text : &str) -> Result<TypeId, FetishError> {
+        TypeExpression::parse(text)?.lookup(&self.type_info_directory)
+    }
+    ///Renders the type with the given [`TypeId`], using the names of named vector types
+    ///where available. See [`TypeInfoDirectory::display_type`].
+    pub fn display_type(&self, type_id : TypeId) -> String {
+        self.type_info_directory.display_type(type_id)
+    }
+    ///Like [`Self::get_type`], but yields an error if the [`TypeId`] isn't registered.
+    pub fn try_get_type(&self, id : TypeId) -> Result<Type, FetishError> {
+        self.type_info_directory.try_get_type(id)
+    }
     ///Given the argument and result types for a function type, returns true iff
     ///the function type actually exists in the [`TypeInfoDirectory`].
     pub fn has_func_type(&self, arg_type_id : TypeId, ret_type_id : TypeId) -> bool {
@@ -106,19 +182,145 @@ impl Context {
     pub fn get_dimension(&self, vec_type_id : TypeId) -> usize {
         self.type_info_directory.get_dimension(vec_type_id)
     }
+    ///Like [`Self::get_dimension`], but yields an error if the [`TypeId`] isn't a vector type.
+    pub fn try_get_dimension(&self, vec_type_id : TypeId) -> Result<usize, FetishError> {
+        self.type_info_directory.try_get_dimension(vec_type_id)
+    }
     ///Given the [`TypeId`] of a function type, yields the [`TypeId`] of the
     ///function's argument type.
     pub fn get_arg_type_id(&self, func_type_id : TypeId) -> TypeId {
         self.type_info_directory.get_arg_type_id(func_type_id)
     }
+    ///Like [`Self::get_arg_type_id`], but yields an error if the [`TypeId`] isn't a function type.
+    pub fn try_get_arg_type_id(&self, func_type_id : TypeId) -> Result<TypeId, FetishError> {
+        self.type_info_directory.try_get_arg_type_id(func_type_id)
+    }
     ///Given the [`TypeId`] of a function type, yields the [`TypeId`] of the
     ///function's return type.
     pub fn get_ret_type_id(&self, func_type_id : TypeId) -> TypeId {
         self.type_info_directory.get_ret_type_id(func_type_id)
     }
-    ///Given a [`TypeId`], returns `true` if the underlying [`Type`] is
-    ///a vector type, and `false` if it's a function type instead.
+    ///Like [`Self::get_ret_type_id`], but yields an error if the [`TypeId`] isn't a function type.
+    pub fn try_get_ret_type_id(&self, func_type_id : TypeId) -> Result<TypeId, FetishError> {
+        self.type_info_directory.try_get_ret_type_id(func_type_id)
+    }
+    ///Given a [`TypeId`], returns `true` if the underlying [`Type`] is a vector type.
     pub fn is_vector_type(&self, id : TypeId) -> bool {
         self.type_info_directory.is_vector_type(id)
     }
+    ///Given a [`TypeId`], returns `true` if the underlying [`Type`] is a function type.
+    pub fn is_func_type(&self, id : TypeId) -> bool {
+        self.type_info_directory.is_func_type(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+    use crate::interpreter_and_embedder_state::*;
+    use crate::term_pointer::*;
+    use crate::term_index::*;
+    use crate::term_reference::*;
+    use crate::term_application::*;
+    use crate::array_utils::*;
+    use ndarray::*;
+
+    fn round_trip(ctxt : &Context) -> Context {
+        let serialized = serde_json::to_string(&ctxt.serialize()).unwrap();
+        let deserialized : SerializedContext = serde_json::from_str(&serialized).unwrap();
+        deserialized.deserialize(get_test_function_primitives()).unwrap()
+    }
+
+    #[test]
+    fn round_trip_preserves_feature_maps() {
+        let ctxt = get_test_function_context();
+        let restored = round_trip(&ctxt);
+        assert_eq!(restored.get_total_num_types(), ctxt.get_total_num_types());
+        for type_id in 0..ctxt.get_total_num_types() {
+            assert_eq!(restored.get_type(type_id), ctxt.get_type(type_id));
+
+            let feat_info = ctxt.get_feature_space_info(type_id);
+            let restored_feat_info = restored.get_feature_space_info(type_id);
+            assert_equal_matrices(restored_feat_info.get_projection_matrix().view(),
+                                  feat_info.get_projection_matrix().view());
+
+            let in_vec = random_vector(feat_info.base_dimensions);
+            let expected = feat_info.get_features_from_base(in_vec.view());
+            let actual = restored_feat_info.get_features_from_base(in_vec.view());
+            assert_equal_vectors(actual.view(), expected.view());
+        }
+    }
+
+    #[test]
+    fn round_trip_rejects_missing_primitives() {
+        let ctxt = get_test_function_context();
+        let serialized = ctxt.serialize();
+        let result = serialized.deserialize(Vec::new());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn round_trip_rejects_duplicate_signatures() {
+        let ctxt = get_test_function_context();
+        let mut serialized = ctxt.serialize();
+        let signatures = serialized.primitive_directory.signatures.get_mut(&TEST_VECTOR_FUNC_T).unwrap();
+        signatures.push(signatures[0].clone());
+        let mut primitives = get_test_function_primitives();
+        primitives.extend(get_test_function_primitives());
+        assert_eq!(serialized.deserialize(primitives).err(), Option::Some(FetishError::ContextBuild(
+            ContextBuildError::DuplicatePrimitive {
+                name : String::from("rotate"),
+                func_type_id : TEST_VECTOR_FUNC_T
+            })));
+    }
+
+    #[test]
+    fn round_trip_rejects_untabulated_priors() {
+        let ctxt = get_test_function_context();
+        let mut serialized = ctxt.serialize();
+        let feat_dims = ctxt.get_function_space_info(TEST_VECTOR_FUNC_T).get_feature_dimensions();
+        let prior_info = serialized.prior_directory.priors.get_mut(&TEST_VECTOR_FUNC_T).unwrap();
+        prior_info.model_prior_specification = TabulatedPriorSpecification::tabulate(&TestPriorSpecification {},
+                                                                                     feat_dims + 1, 1);
+        assert!(matches!(serialized.deserialize(get_test_function_primitives()).err(),
+                         Option::Some(FetishError::ContextBuild(ContextBuildError::UntabulatedPrior {
+                             type_id : TEST_VECTOR_FUNC_T, ..
+                         }))));
+    }
+
+    #[test]
+    fn round_trip_rejects_missing_feature_spaces() {
+        let ctxt = get_test_function_context();
+        let mut serialized = ctxt.serialize();
+        serialized.space_info_directory.feature_spaces.pop();
+        let missing_type_id = serialized.space_info_directory.feature_spaces.len();
+        assert_eq!(serialized.deserialize(get_test_function_primitives()).err(),
+                   Option::Some(FetishError::ContextBuild(ContextBuildError::MissingFeatureSpace(missing_type_id))));
+    }
+
+    #[test]
+    fn saved_state_restores_against_restored_context() {
+        let ctxt = get_test_function_context();
+        let mut state = InterpreterAndEmbedderState::new(&ctxt);
+        let term_app = TermApplication {
+            func_ptr : TermPointer {
+                type_id : TEST_BINARY_VECTOR_FUNC_T,
+                index : TermIndex::Primitive(0)
+            },
+            arg_ref : TermReference::VecRef(TEST_VECTOR_T, to_noisy(array![1.0f32, 2.0f32].view()))
+        };
+        let result_ref = state.evaluate(&term_app).unwrap();
+        state.bayesian_update_step();
+        let result_ptr = match (result_ref) {
+            TermReference::FuncRef(result_ptr) => result_ptr,
+            _ => panic!()
+        };
+        let expected_mean = state.embedder_state.get_embedding(result_ptr).get_mean_as_vec().to_owned();
+
+        let restored_ctxt = round_trip(&ctxt);
+        let restored_state = state.serialize().deserialize(&restored_ctxt);
+        let actual_mean = restored_state.embedder_state.get_embedding(result_ptr).get_mean_as_vec();
+        assert_equal_vectors(actual_mean, expected_mean.view());
+    }
 }
@@ -0,0 +1,693 @@
+use std::collections::HashMap;
+use std::fmt;
+use crate::context::*;
+use crate::type_id::*;
+use crate::space_info::*;
+use crate::feature_space_info::*;
+use crate::feature_collection::*;
+use crate::fourier_feature_collection::*;
+use crate::quadratic_feature_collection::*;
+use crate::sketched_linear_feature_collection::*;
+use crate::linear_sketch::*;
+use crate::func_impl::*;
+use crate::primitive_directory::*;
+use crate::constant_directory::*;
+use crate::primitive_template::*;
+use crate::type_parser::*;
+use crate::prior_directory::*;
+use crate::prior_info::*;
+use crate::prior_specification::*;
+use crate::rand_utils::*;
+use crate::fetish_error::*;
+use crate::vector_type_metadata::*;
+use rand::prelude::*;
+
+///Description of a [`FeatureCollection`] to be attached to a type by a [`ContextBuilder`].
+///Everything but `Custom` is constructed lazily during [`ContextBuilder::build`], once the
+///dimension of the compressed space for the type is known.
+pub enum FeatureCollectionSpec {
+    ///A [`FourierFeatureCollection`] with the given number of features and scaling factor,
+    ///whose coefficient vectors are drawn uniformly from the unit sphere.
+    Fourier { num_features : usize, alpha : f32 },
+    ///A [`QuadraticFeatureCollection`] with the given number of features and scaling factor.
+    Quadratic { num_features : usize, alpha : f32 },
+    ///A [`SketchedLinearFeatureCollection`] with the given number of features and scaling factor.
+    SketchedLinear { num_features : usize, alpha : f32 },
+    ///An already-constructed [`FeatureCollection`], whose input dimension must agree
+    ///with the compressed dimension of the type it's attached to.
+    Custom(Box<dyn FeatureCollection>)
+}
+
+///Description of the [`LinearSketch`] to be attached to a type by a [`ContextBuilder`].
+pub enum SketcherSpec {
+    ///A random [`LinearSketch`] down to the given number of dimensions, with the given scaling factor.
+    Random { out_dimensions : usize, alpha : f32 },
+    ///An already-constructed [`LinearSketch`], whose input dimension must agree with
+    ///the base dimension of the type it's attached to.
+    Custom(LinearSketch)
+}
+
+///Structured description of what went wrong when a [`ContextBuilder`] was asked
+///to build an inconsistent [`Context`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ContextBuildError {
+    ///The referenced [`TypeId`] was never registered with the builder.
+    UnknownType(TypeId),
+    ///No [`FeatureCollection`]s were specified for the given type.
+    MissingFeatureSpace(TypeId),
+    ///A custom [`FeatureCollection`] for the given type expected a different input dimension
+    ///than the compressed dimension of the type.
+    FeatureDimensionMismatch { type_id : TypeId, expected : usize, actual : usize },
+    ///A custom [`LinearSketch`] for the given type expected a different input dimension
+    ///than the base dimension of the type.
+    SketcherDimensionMismatch { type_id : TypeId, expected : usize, actual : usize },
+    ///The given function type has no [`PriorInfo`].
+    MissingPrior(TypeId),
+    ///The given function type has no sketcher, but its [`crate::elaborator::Elaborator`] needs one.
+    MissingSketcher(TypeId),
+    ///The sketcher for the given function type has a trivial kernel, but its
+    ///[`crate::elaborator::Elaborator`] needs a nontrivial one.
+    MissingKernel(TypeId),
+    ///A primitive with the given name references a function type `arg -> ret`
+    ///which was never registered.
+    MissingFuncType { name : String, arg_type_id : TypeId, ret_type_id : TypeId },
+    ///A serialized [`Context`] referenced a primitive with the given name and
+    ///function type, but no matching [`FuncImpl`] was supplied to re-bind it to.
+    MissingPrimitive { name : String, func_type_id : TypeId },
+    ///A [`FuncImpl`] with the given name and function type was supplied while restoring
+    ///a serialized [`Context`], but the serialized [`Context`] had no such primitive.
+    UnexpectedPrimitive { name : String, func_type_id : TypeId },
+    ///More than one primitive with the given name and function type has the same
+    ///[`PrimitiveSignature`], so they couldn't be told apart after serialization.
+    DuplicatePrimitive { name : String, func_type_id : TypeId },
+    ///The [`PrimitiveTemplate`] with the given name couldn't be instantiated, for the given reason.
+    InvalidTemplate { name : String, message : String },
+    ///A serialized [`Context`] has no tabulated prior for the given function type at the
+    ///feature and output dimensions that the type's spaces have.
+    UntabulatedPrior { type_id : TypeId, feat_dims : usize, out_dims : usize }
+}
+
+impl fmt::Display for ContextBuildError {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self) {
+            ContextBuildError::UnknownType(type_id) =>
+                write!(f, "type {} was never registered", type_id),
+            ContextBuildError::MissingFeatureSpace(type_id) =>
+                write!(f, "type {} has no feature collections", type_id),
+            ContextBuildError::FeatureDimensionMismatch { type_id, expected, actual } =>
+                write!(f, "feature collection for type {} takes {} dimensions, but the type's compressed space has {}",
+                       type_id, actual, expected),
+            ContextBuildError::SketcherDimensionMismatch { type_id, expected, actual } =>
+                write!(f, "sketcher for type {} takes {} dimensions, but the type's base space has {}",
+                       type_id, actual, expected),
+            ContextBuildError::MissingPrior(type_id) =>
+                write!(f, "function type {} has no prior information", type_id),
+            ContextBuildError::MissingSketcher(type_id) =>
+                write!(f, "function type {} has no sketcher", type_id),
+            ContextBuildError::MissingKernel(type_id) =>
+                write!(f, "the sketcher for function type {} has a trivial kernel", type_id),
+            ContextBuildError::MissingFuncType { name, arg_type_id, ret_type_id } =>
+                write!(f, "primitive \"{}\" needs the function type ({} -> {}), which was never registered",
+                       name, arg_type_id, ret_type_id),
+            ContextBuildError::MissingPrimitive { name, func_type_id } =>
+                write!(f, "no implementation was supplied for primitive \"{}\" of type {}", name, func_type_id),
+            ContextBuildError::UnexpectedPrimitive { name, func_type_id } =>
+                write!(f, "primitive \"{}\" of type {} is not part of the serialized context", name, func_type_id),
+            ContextBuildError::DuplicatePrimitive { name, func_type_id } =>
+                write!(f, "primitive \"{}\" of type {} was added more than once", name, func_type_id),
+            ContextBuildError::InvalidTemplate { name, message } =>
+                write!(f, "primitive template \"{}\" could not be instantiated: {}", name, message),
+            ContextBuildError::UntabulatedPrior { type_id, feat_dims, out_dims } =>
+                write!(f, "function type {} has no prior tabulated for {} feature and {} output dimensions",
+                       type_id, feat_dims, out_dims)
+        }
+    }
+}
+
+impl std::error::Error for ContextBuildError {}
+
+///Incrementally assembles a [`Context`], keeping its [`TypeInfoDirectory`], [`SpaceInfoDirectory`],
+///[`PrimitiveDirectory`] and [`PriorDirectory`] consistent with one another. Types are registered
+///first, then feature collections, sketchers, priors and primitives may be attached to them in
+///any order, and [`Self::build`] checks that the result is complete before yielding the [`Context`].
+///All randomness used to construct sketchers and feature maps is drawn from a [`StdRng`]
+///seeded with the builder's seed, so the same seed always yields the same [`Context`].
+///Every fallible method yields a [`FetishError`], with inconsistencies in the described
+///[`Context`] reported as [`FetishError::ContextBuild`].
+pub struct ContextBuilder {
+    seed : u64,
+    type_info_directory : TypeInfoDirectory,
+    feature_collections : HashMap<TypeId, Vec<FeatureCollectionSpec>>,
+    sketchers : HashMap<TypeId, SketcherSpec>,
+    priors : HashMap<TypeId, PriorInfo>,
+    primitives : Vec<Box<dyn FuncImpl>>,
+    templates : Vec<Box<dyn PrimitiveTemplate>>,
+    constant_directory : ConstantDirectory
+}
+
+impl ContextBuilder {
+    ///Constructs a [`ContextBuilder`] with no types registered, whose randomly-generated
+    ///sketchers and feature maps will be derived from the given seed.
+    pub fn new(seed : u64) -> Self {
+        ContextBuilder {
+            seed,
+            type_info_directory : TypeInfoDirectory::new(),
+            feature_collections : HashMap::new(),
+            sketchers : HashMap::new(),
+            priors : HashMap::new(),
+            primitives : Vec::new(),
+            templates : Vec::new(),
+            constant_directory : ConstantDirectory::new()
+        }
+    }
+
+    ///Gets the [`TypeInfoDirectory`] built up so far, which is useful for constructing
+    ///primitives such as [`ComposeImpl`] which need to look up function types.
+    pub fn get_type_info_directory(&self) -> &TypeInfoDirectory {
+        &self.type_info_directory
+    }
+
+    ///Registers a new vector type with the given number of dimensions, returning its [`TypeId`].
+    pub fn add_vector_type(&mut self, dimensions : usize) -> TypeId {
+        self.type_info_directory.add(Type::VecType(dimensions))
+    }
+
+    ///Registers a new vector type with the given number of dimensions, described by
+    ///the given [`VectorTypeMetadata`], returning its [`TypeId`].
+    ///See [`TypeInfoDirectory::add_named_vector_type`].
+    pub fn add_named_vector_type(&mut self, dimensions : usize, metadata : VectorTypeMetadata)
+                                -> Result<TypeId, FetishError> {
+        self.type_info_directory.add_named_vector_type(dimensions, metadata)
+    }
+
+    ///Registers the function type `arg_type_id -> ret_type_id`, returning its [`TypeId`].
+    ///If the function type was already registered, yields the existing [`TypeId`].
+    pub fn add_func_type(&mut self, arg_type_id : TypeId, ret_type_id : TypeId) -> Result<TypeId, FetishError> {
+        self.check_type_exists(arg_type_id)?;
+        self.check_type_exists(ret_type_id)?;
+        Result::Ok(self.type_info_directory.add(Type::FuncType(arg_type_id, ret_type_id)))
+    }
+
+    ///Registers the product type with the given components, returning its [`TypeId`].
+    ///If the product type was already registered, yields the existing [`TypeId`].
+    ///The base space of the product type is the concatenation of the compressed spaces
+    ///of its components, and so feature collections and sketchers for it should be sized accordingly.
+    pub fn add_product_type(&mut self, components : &[TypeId]) -> Result<TypeId, FetishError> {
+        for component_type_id in components.iter() {
+            self.check_type_exists(*component_type_id)?;
+        }
+        Result::Ok(self.type_info_directory.add(Type::ProductType(components.to_vec())))
+    }
+
+    ///Registers every function type built from the given base types with at most
+    ///`max_depth` levels of nested arrows, and yields the [`TypeId`]s of those which
+    ///weren't already registered. Each of these will need a feature space and priors
+    ///before [`Self::build`] succeeds. See [`TypeInfoDirectory::add_func_type_closure`].
+    pub fn add_func_type_closure(&mut self, base_type_ids : &[TypeId], max_depth : usize)
+                                -> Result<Vec<TypeId>, FetishError> {
+        self.type_info_directory.add_func_type_closure(base_type_ids, max_depth)
+    }
+
+    ///Registers `name` as an alias for the given type, for use in [`Self::parse_type`].
+    ///See [`TypeInfoDirectory::add_type_alias`].
+    pub fn add_type_alias(&mut self, name : &str, type_id : TypeId) -> Result<(), FetishError> {
+        self.type_info_directory.add_type_alias(name, type_id)
+    }
+
+    ///Parses a textual type expression such as `(Vec2 -> Scalar)`, registering
+    ///any function types which weren't already present, and returns its [`TypeId`].
+    ///See [`TypeInfoDirectory::parse_type`].
+    pub fn parse_type(&mut self, text : &str) -> Result<TypeId, FetishError> {
+        self.type_info_directory.parse_type(text)
+    }
+
+    ///Attaches a feature collection described by the given [`FeatureCollectionSpec`]
+    ///to the given type. Feature collections are concatenated in the order they're added.
+    pub fn add_feature_collection(&mut self, type_id : TypeId, spec : FeatureCollectionSpec) -> Result<(), FetishError> {
+        self.check_type_exists(type_id)?;
+        self.feature_collections.entry(type_id).or_default().push(spec);
+        Result::Ok(())
+    }
+
+    ///Convenience wrapper around [`Self::add_feature_collection`] for [`FeatureCollectionSpec::Fourier`].
+    pub fn add_fourier_features(&mut self, type_id : TypeId, num_features : usize, alpha : f32) -> Result<(), FetishError> {
+        self.add_feature_collection(type_id, FeatureCollectionSpec::Fourier { num_features, alpha })
+    }
+
+    ///Convenience wrapper around [`Self::add_feature_collection`] for [`FeatureCollectionSpec::Quadratic`].
+    pub fn add_quadratic_features(&mut self, type_id : TypeId, num_features : usize, alpha : f32) -> Result<(), FetishError> {
+        self.add_feature_collection(type_id, FeatureCollectionSpec::Quadratic { num_features, alpha })
+    }
+
+    ///Convenience wrapper around [`Self::add_feature_collection`] for [`FeatureCollectionSpec::SketchedLinear`].
+    pub fn add_sketched_linear_features(&mut self, type_id : TypeId, num_features : usize, alpha : f32) -> Result<(), FetishError> {
+        self.add_feature_collection(type_id, FeatureCollectionSpec::SketchedLinear { num_features, alpha })
+    }
+
+    ///Sets the sketcher for the given type, replacing any previously-set one.
+    pub fn set_sketcher(&mut self, type_id : TypeId, spec : SketcherSpec) -> Result<(), FetishError> {
+        self.check_type_exists(type_id)?;
+        self.sketchers.insert(type_id, spec);
+        Result::Ok(())
+    }
+
+    ///Convenience wrapper around [`Self::set_sketcher`] for [`SketcherSpec::Random`].
+    pub fn set_random_sketcher(&mut self, type_id : TypeId, out_dimensions : usize, alpha : f32) -> Result<(), FetishError> {
+        self.set_sketcher(type_id, SketcherSpec::Random { out_dimensions, alpha })
+    }
+
+    ///Sets the [`PriorSpecification`]s for [`crate::term_model::TermModel`]s and for the
+    ///[`crate::elaborator::Elaborator`] of the given function type.
+    pub fn set_prior_specifications(&mut self, type_id : TypeId,
+                                    model_prior_specification : Box<dyn PriorSpecification>,
+                                    elaborator_prior_specification : Box<dyn PriorSpecification>)
+                                   -> Result<(), FetishError> {
+        self.check_type_exists(type_id)?;
+        let prior_info = PriorInfo {
+            model_prior_specification,
+            elaborator_prior_specification
+        };
+        self.priors.insert(type_id, prior_info);
+        Result::Ok(())
+    }
+
+    ///Adds the given [`FuncImpl`] as a primitive. Its function type is resolved during [`Self::build`],
+    ///which fails if another primitive of the same type has the same [`PrimitiveSignature`].
+    pub fn add_primitive(&mut self, func_impl : Box<dyn FuncImpl>) {
+        self.primitives.push(func_impl);
+    }
+
+    ///Adds the given [`PrimitiveTemplate`], which is instantiated during [`Self::build`] for every
+    ///matching function type registered by then (see [`PrimitiveDirectory::add_template`]).
+    ///Instances which duplicate primitives added via [`Self::add_primitive`] are skipped.
+    ///Yields an error immediately if the template's signature doesn't parse.
+    pub fn add_primitive_template(&mut self, template : Box<dyn PrimitiveTemplate>) -> Result<(), FetishError> {
+        TypeExpression::parse(&template.get_signature())?;
+        self.templates.push(template);
+        Result::Ok(())
+    }
+
+    ///Adds a constant with the given name for the vector of the given elements, of the given
+    ///vector type. Yields an error immediately if the constant doesn't fit the type, or if
+    ///the type already has a constant of the same name. See [`ConstantDirectory::add`].
+    pub fn add_constant(&mut self, name : &str, type_id : TypeId, elements : &[f32]) -> Result<(), FetishError> {
+        self.constant_directory.add(name, type_id, elements, &self.type_info_directory)
+    }
+
+    ///Adds the standard constants `zero`, `one`, `pi` and the unit basis vectors for the given
+    ///vector type. See [`ConstantDirectory::add_standard_constants`].
+    pub fn add_standard_constants(&mut self, type_id : TypeId) -> Result<(), FetishError> {
+        self.constant_directory.add_standard_constants(type_id, &self.type_info_directory)
+    }
+
+    ///Convenient wrapper around [`Self::add_primitive`] which adds a [`BinaryArrayOperator`]
+    ///whose element type is the given [`TypeId`].
+    pub fn add_binary_func(&mut self, type_id : TypeId, binary_func : Box<dyn BinaryArrayOperator>) {
+        let binary_func_impl = BinaryFuncImpl {
+            elem_type : type_id,
+            f : binary_func
+        };
+        self.add_primitive(Box::new(binary_func_impl));
+    }
+
+    ///Convenient wrapper around [`Self::add_primitive`] which adds a [`UnaryArrayOperator`]
+    ///whose element type is the given [`TypeId`].
+    pub fn add_unary_func(&mut self, type_id : TypeId, unary_func : Box<dyn UnaryArrayOperator>) {
+        let unary_func_impl = UnaryFuncImpl {
+            elem_type : type_id,
+            f : unary_func
+        };
+        self.add_primitive(Box::new(unary_func_impl));
+    }
+
+    ///Validates everything registered with this [`ContextBuilder`], and if it's consistent,
+    ///constructs the described [`Context`]. Feature spaces are built in [`TypeId`] order, which
+    ///guarantees that the spaces for the argument and return types of a function type are
+    ///available by the time that the function type's own space is built.
+    pub fn build(mut self) -> Result<Context, FetishError> {
+        let total_num_types = self.type_info_directory.get_total_num_types();
+        let mut rng = StdRng::seed_from_u64(self.seed);
+
+        let mut feature_spaces : Vec<FeatureSpaceInfo> = Vec::with_capacity(total_num_types);
+        for type_id in 0..total_num_types {
+            let base_dimensions = match (self.type_info_directory.get_type(type_id)) {
+                Type::VecType(dim) => dim,
+                Type::FuncType(arg_type_id, ret_type_id) => {
+                    let arg_feat_info = &feature_spaces[arg_type_id];
+                    let ret_feat_info = &feature_spaces[ret_type_id];
+                    arg_feat_info.feature_dimensions * ret_feat_info.get_sketched_dimensions()
+                },
+                Type::ProductType(components) => {
+                    components.iter()
+                              .map(|component_type_id| feature_spaces[*component_type_id].get_sketched_dimensions())
+                              .sum()
+                }
+            };
+            let feature_space_info = self.build_feature_space_info(type_id, base_dimensions, &mut rng)?;
+            feature_spaces.push(feature_space_info);
+        }
+
+        let mut priors = HashMap::new();
+        for (type_id, feature_space_info) in feature_spaces.iter().enumerate() {
+            if (self.type_info_directory.is_func_type(type_id)) {
+                let prior_info = self.priors.remove(&type_id).ok_or(ContextBuildError::MissingPrior(type_id))?;
+                priors.insert(type_id, prior_info);
+
+                //Every function type gets an elaborator, which needs a sketcher with a kernel
+                match (&feature_space_info.sketcher) {
+                    Option::None => return Result::Err(ContextBuildError::MissingSketcher(type_id).into()),
+                    Option::Some(sketcher) => {
+                        if (sketcher.get_kernel_matrix().is_none()) {
+                            return Result::Err(ContextBuildError::MissingKernel(type_id).into());
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut primitive_directory = PrimitiveDirectory::new(&self.type_info_directory);
+        let mut primitives = std::mem::take(&mut self.primitives);
+        for primitive in primitives.drain(..) {
+            self.check_primitive_types(primitive.as_ref())?;
+            let func_type_id = primitive.func_type(&self.type_info_directory);
+            if (primitive_directory.has_signature(func_type_id, &PrimitiveSignature::of(primitive.as_ref()))) {
+                return Result::Err(ContextBuildError::DuplicatePrimitive {
+                    name : primitive.get_name(),
+                    func_type_id
+                }.into());
+            }
+            primitive_directory.add(primitive, &self.type_info_directory);
+        }
+        for template in self.templates.iter() {
+            primitive_directory.add_template(template.as_ref(), &self.type_info_directory)
+                               .map_err(|err| ContextBuildError::InvalidTemplate {
+                                   name : template.get_name(),
+                                   message : err.to_string()
+                               })?;
+        }
+
+        Result::Ok(Context {
+            type_info_directory : self.type_info_directory,
+            space_info_directory : SpaceInfoDirectory {
+                feature_spaces
+            },
+            primitive_directory,
+            constant_directory : self.constant_directory,
+            prior_directory : PriorDirectory {
+                priors
+            }
+        })
+    }
+
+    fn check_type_exists(&self, type_id : TypeId) -> Result<(), ContextBuildError> {
+        if (type_id < self.type_info_directory.get_total_num_types()) {
+            Result::Ok(())
+        } else {
+            Result::Err(ContextBuildError::UnknownType(type_id))
+        }
+    }
+
+    fn check_primitive_types(&self, primitive : &dyn FuncImpl) -> Result<(), ContextBuildError> {
+        let mut arg_types = primitive.required_arg_types();
+        arg_types.reverse();
+
+        let mut result = primitive.ret_type();
+        self.check_type_exists(result)?;
+        for arg_type_id in arg_types.drain(..) {
+            self.check_type_exists(arg_type_id)?;
+            if (!self.type_info_directory.has_func_type(arg_type_id, result)) {
+                return Result::Err(ContextBuildError::MissingFuncType {
+                    name : primitive.get_name(),
+                    arg_type_id,
+                    ret_type_id : result
+                });
+            }
+            result = self.type_info_directory.get_func_type_id(arg_type_id, result);
+        }
+        Result::Ok(())
+    }
+
+    fn build_feature_space_info<R : Rng + ?Sized>(&mut self, type_id : TypeId, base_dimensions : usize,
+                                                  rng : &mut R) -> Result<FeatureSpaceInfo, ContextBuildError> {
+        let sketcher = match (self.sketchers.remove(&type_id)) {
+            Option::None => Option::None,
+            Option::Some(SketcherSpec::Random { out_dimensions, alpha }) => {
+                Option::Some(LinearSketch::new(base_dimensions, out_dimensions, alpha, rng))
+            },
+            Option::Some(SketcherSpec::Custom(sketcher)) => {
+                if (sketcher.get_input_dimension() != base_dimensions) {
+                    return Result::Err(ContextBuildError::SketcherDimensionMismatch {
+                        type_id,
+                        expected : base_dimensions,
+                        actual : sketcher.get_input_dimension()
+                    });
+                }
+                Option::Some(sketcher)
+            }
+        };
+        let sketched_dimensions = match (&sketcher) {
+            Option::None => base_dimensions,
+            Option::Some(sketcher) => sketcher.get_output_dimension()
+        };
+
+        let mut specs = self.feature_collections.remove(&type_id)
+                            .ok_or(ContextBuildError::MissingFeatureSpace(type_id))?;
+        if (specs.is_empty()) {
+            return Result::Err(ContextBuildError::MissingFeatureSpace(type_id));
+        }
+
+        let mut feature_collections = Vec::<Box<dyn FeatureCollection>>::new();
+        for spec in specs.drain(..) {
+            let feature_collection : Box<dyn FeatureCollection> = match (spec) {
+                FeatureCollectionSpec::Fourier { num_features, alpha } => {
+                    Box::new(FourierFeatureCollection::new(sketched_dimensions, num_features, alpha,
+                                                           gen_nsphere_random, rng))
+                },
+                FeatureCollectionSpec::Quadratic { num_features, alpha } => {
+                    Box::new(QuadraticFeatureCollection::new(sketched_dimensions, num_features, alpha, rng))
+                },
+                FeatureCollectionSpec::SketchedLinear { num_features, alpha } => {
+                    Box::new(SketchedLinearFeatureCollection::new(sketched_dimensions, num_features, alpha, rng))
+                },
+                FeatureCollectionSpec::Custom(feature_collection) => {
+                    if (feature_collection.get_in_dimensions() != sketched_dimensions) {
+                        return Result::Err(ContextBuildError::FeatureDimensionMismatch {
+                            type_id,
+                            expected : sketched_dimensions,
+                            actual : feature_collection.get_in_dimensions()
+                        });
+                    }
+                    feature_collection
+                }
+            };
+            feature_collections.push(feature_collection);
+        }
+
+        let feature_dimensions = get_total_feat_dims(&feature_collections);
+
+        Result::Ok(FeatureSpaceInfo {
+            base_dimensions,
+            feature_dimensions,
+            feature_collections,
+            sketcher
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+    use crate::interpreter_and_embedder_state::*;
+    use crate::term_pointer::*;
+    use crate::term_index::*;
+    use crate::term_reference::*;
+    use crate::term_application::*;
+    use crate::array_utils::*;
+    use ndarray::*;
+
+    fn scalar_builder() -> (ContextBuilder, TypeId, TypeId) {
+        let mut builder = ContextBuilder::new(TEST_SEED);
+        let scalar_t = builder.add_vector_type(1);
+        let unary_t = builder.add_func_type(scalar_t, scalar_t).unwrap();
+        builder.add_fourier_features(scalar_t, 4, 1.0f32).unwrap();
+        builder.add_sketched_linear_features(scalar_t, 2, 1.0f32).unwrap();
+        builder.add_sketched_linear_features(unary_t, 4, 1.0f32).unwrap();
+        (builder, scalar_t, unary_t)
+    }
+
+    #[test]
+    fn missing_prior_is_reported() {
+        let (mut builder, _, unary_t) = scalar_builder();
+        builder.set_random_sketcher(unary_t, 4, 1.0f32).unwrap();
+        let result = builder.build();
+        assert_eq!(result.err(), Option::Some(FetishError::ContextBuild(ContextBuildError::MissingPrior(unary_t))));
+    }
+
+    #[test]
+    fn missing_sketcher_is_reported() {
+        let (mut builder, _, unary_t) = scalar_builder();
+        builder.set_prior_specifications(unary_t, Box::new(TestPriorSpecification {}),
+                                                  Box::new(TestPriorSpecification {})).unwrap();
+        let result = builder.build();
+        assert_eq!(result.err(), Option::Some(FetishError::ContextBuild(ContextBuildError::MissingSketcher(unary_t))));
+    }
+
+    #[test]
+    fn missing_kernel_is_reported() {
+        let (mut builder, _, unary_t) = scalar_builder();
+        //Trivial sketches have no kernel, but otherwise fit the 11 base dimensions of the unary function type
+        builder.set_sketcher(unary_t, SketcherSpec::Custom(LinearSketch::trivial_sketch(11))).unwrap();
+        builder.set_prior_specifications(unary_t, Box::new(TestPriorSpecification {}),
+                                                  Box::new(TestPriorSpecification {})).unwrap();
+        let result = builder.build();
+        assert_eq!(result.err(), Option::Some(FetishError::ContextBuild(ContextBuildError::MissingKernel(unary_t))));
+    }
+
+    #[test]
+    fn duplicate_primitives_are_reported() {
+        let mut builder = get_test_vector_only_context_builder();
+        builder.add_func_type(TEST_VECTOR_T, TEST_VECTOR_T).unwrap();
+        add_test_function_space(&mut builder, TEST_VECTOR_FUNC_T, 8);
+        builder.add_unary_func(TEST_VECTOR_T, Box::new(NegateOperator {}));
+        builder.add_unary_func(TEST_VECTOR_T, Box::new(NegateOperator {}));
+        assert_eq!(builder.build().err(), Option::Some(FetishError::ContextBuild(ContextBuildError::DuplicatePrimitive {
+            name : String::from("negate"),
+            func_type_id : TEST_VECTOR_FUNC_T
+        })));
+    }
+
+    #[test]
+    fn missing_func_type_for_primitive_is_reported() {
+        let (mut builder, scalar_t, unary_t) = scalar_builder();
+        builder.set_random_sketcher(unary_t, 4, 1.0f32).unwrap();
+        builder.set_prior_specifications(unary_t, Box::new(TestPriorSpecification {}),
+                                                  Box::new(TestPriorSpecification {})).unwrap();
+        builder.add_binary_func(scalar_t, Box::new(AddOperator {}));
+        let result = builder.build();
+        assert_eq!(result.err(), Option::Some(FetishError::ContextBuild(ContextBuildError::MissingFuncType {
+            name : String::from("+"),
+            arg_type_id : scalar_t,
+            ret_type_id : unary_t
+        })));
+    }
+
+    #[test]
+    fn built_context_supports_embedding_updates() {
+        let ctxt = get_test_function_context();
+        let mut state = InterpreterAndEmbedderState::new(&ctxt);
+        let add_ptr = TermPointer {
+            type_id : TEST_BINARY_VECTOR_FUNC_T,
+            index : TermIndex::Primitive(0)
+        };
+        let arg = TermReference::VecRef(TEST_VECTOR_T, to_noisy(array![1.0f32, 2.0f32].view()));
+        let term_app = TermApplication {
+            func_ptr : add_ptr,
+            arg_ref : arg
+        };
+        state.evaluate(&term_app).unwrap();
+        state.bayesian_update_step();
+        assert_eq!(state.newly_evaluated_terms.terms.len(), 1);
+    }
+
+    fn sample_after_update(ctxt : &Context, sample_seed : u64) -> Array1<f32> {
+        let mut state = InterpreterAndEmbedderState::new(ctxt);
+        let add_ptr = TermPointer {
+            type_id : TEST_BINARY_VECTOR_FUNC_T,
+            index : TermIndex::Primitive(0)
+        };
+        let arg = TermReference::VecRef(TEST_VECTOR_T, to_noisy(array![1.0f32, 2.0f32].view()));
+        let term_app = TermApplication {
+            func_ptr : add_ptr,
+            arg_ref : arg
+        };
+        state.evaluate(&term_app).unwrap();
+        state.bayesian_update_step();
+
+        let mut rng = StdRng::seed_from_u64(sample_seed);
+        let sampled_state = state.embedder_state.sample(&mut rng);
+        sampled_state.get_model_embedding(add_ptr).sampled_vec.clone()
+    }
+
+    #[test]
+    fn same_seed_yields_identical_contexts_and_samples() {
+        let ctxt_one = get_test_function_context();
+        let ctxt_two = get_test_function_context();
+
+        for type_id in 0..ctxt_one.get_total_num_types() {
+            let feat_one = ctxt_one.get_feature_space_info(type_id);
+            let feat_two = ctxt_two.get_feature_space_info(type_id);
+            assert_eq!(feat_one.get_projection_matrix(), feat_two.get_projection_matrix());
+
+            let in_vec = Array::ones((feat_one.get_sketched_dimensions(),));
+            assert_eq!(feat_one.get_features(in_vec.view()), feat_two.get_features(in_vec.view()));
+        }
+
+        assert_eq!(sample_after_update(&ctxt_one, 7), sample_after_update(&ctxt_two, 7));
+    }
+
+    //Embeds several applications of `+`, including the partial applications they create,
+    //and yields the means and samples of every model involved, in a fixed order
+    fn embeddings_after_updates(ctxt : &Context, sample_seed : u64) -> Vec<Array1<f32>> {
+        let mut state = InterpreterAndEmbedderState::new(ctxt);
+        for text in ["+ [1, 2] [3, 4]", "+ [3, 4] [1, 2]", "+ [1, 2] [5, 6]", "+ (rotate [1, 2]) [2, 2]"].iter() {
+            state.evaluate_expression(text).unwrap();
+        }
+        state.bayesian_update_step();
+        state.evaluate_expression("+ [3, 4] [3, 4]").unwrap();
+        state.bayesian_update_step();
+
+        let add_ptr = TermPointer::from(ctxt.primitive_directory.find_primitives("+")[0]);
+        let rotate_ptr = TermPointer::from(ctxt.primitive_directory.find_primitives("rotate")[0]);
+        let mut term_ptrs = vec![add_ptr, rotate_ptr];
+        for app_result in state.interpreter_state.get_app_results_with_func(add_ptr) {
+            if let TermReference::FuncRef(curried_ptr) = app_result.get_ret_ref() {
+                if (!term_ptrs.contains(&curried_ptr)) {
+                    term_ptrs.push(curried_ptr);
+                }
+            }
+        }
+        term_ptrs.sort_by_key(|term_ptr| (term_ptr.type_id, term_ptr.index));
+
+        let mut rng = StdRng::seed_from_u64(sample_seed);
+        let sampled_state = state.embedder_state.sample(&mut rng);
+        let mut result = Vec::new();
+        for term_ptr in term_ptrs {
+            result.push(state.embedder_state.get_embedding(term_ptr).get_mean_as_vec().to_owned());
+            result.push(sampled_state.get_model_embedding(term_ptr).sampled_vec.clone());
+        }
+        result
+    }
+
+    #[test]
+    fn embeddings_of_many_terms_are_reproducible() {
+        let ctxt = get_test_function_context();
+        let expected = embeddings_after_updates(&ctxt, 7);
+        assert_eq!(expected.len(), 2 * 5);
+        for _ in 0..4 {
+            assert_eq!(embeddings_after_updates(&get_test_function_context(), 7), expected);
+        }
+    }
+
+    #[test]
+    fn func_type_closure_registers_nested_function_types() {
+        let mut builder = ContextBuilder::new(TEST_SEED);
+        let scalar_t = builder.add_vector_type(1);
+        let unary_t = builder.add_func_type(scalar_t, scalar_t).unwrap();
+
+        let generated = builder.add_func_type_closure(&[scalar_t], 2).unwrap();
+        assert_eq!(generated.len(), 3);
+        assert!(!generated.contains(&unary_t));
+        for text in ["1 -> (1 -> 1)", "(1 -> 1) -> 1", "(1 -> 1) -> (1 -> 1)"].iter() {
+            assert!(generated.contains(&builder.parse_type(text).unwrap()));
+        }
+        assert_eq!(builder.get_type_info_directory().get_total_num_types(), 5);
+
+        //Compose over scalars can now find all of its types
+        let compose_impl = ComposeImpl::new(builder.get_type_info_directory(), scalar_t, scalar_t, scalar_t);
+        assert_eq!(compose_impl.required_arg_types(), vec![unary_t, unary_t, scalar_t]);
+
+        assert_eq!(builder.add_func_type_closure(&[scalar_t], 2).unwrap(), Vec::<TypeId>::new());
+        assert_eq!(builder.add_func_type_closure(&[17], 1).err(), Option::Some(FetishError::UnknownType(17)));
+    }
+}
@@ -21,12 +21,11 @@ pub struct CountSketch {
 
 impl CountSketch {
     ///Creates a [`CountSketch`] which projects from the specified `in_dims` to
-    ///the specified `out_dims`.
-    pub fn new(in_dims : usize, out_dims : usize) -> CountSketch {
+    ///the specified `out_dims`, drawing random indices and signs from `rng`.
+    pub fn new<R : Rng + ?Sized>(in_dims : usize, out_dims : usize, rng : &mut R) -> CountSketch {
         //Need to initialize both indices and signs here.
         let mut indices = Vec::<usize>::with_capacity(in_dims);
         let mut signs = Vec::<f32>::with_capacity(in_dims);
-        let mut rng = rand::thread_rng();
         for _ in 0..in_dims {
             let r_one : u8 = rng.gen();
             let sign = (((r_one % 2) as i8) * 2 - 1) as f32;
@@ -68,7 +67,7 @@ mod tests {
 
     #[test]
     fn signs_have_abs_value_one() {
-        let count_sketch = CountSketch::new(10, 5);
+        let count_sketch = CountSketch::new(10, 5, &mut rand::thread_rng());
         for i in 0..count_sketch.in_dims {
             let elem = count_sketch.signs[i];
             assert_eps_equals(elem.abs(), 1.0f32);
@@ -76,7 +75,7 @@ mod tests {
     }
     #[test]
     fn signs_differ() {
-        let count_sketch = CountSketch::new(50, 5);
+        let count_sketch = CountSketch::new(50, 5, &mut rand::thread_rng());
         let mut pos_count : usize = 0;
         let mut neg_count : usize = 0;
         for i in 0..count_sketch.in_dims {
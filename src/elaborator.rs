@@ -106,7 +106,7 @@ impl<'a> Elaborator<'a> {
 
     ///Samples a left-inverse to the projection for this elaborator's type from the
     ///distribution defined by this [`Elaborator`].
-    pub fn sample(&self, rng : &mut ThreadRng) -> Array2<f32> {
+    pub fn sample<R : Rng + ?Sized>(&self, rng : &mut R) -> Array2<f32> {
         let feature_space_info = self.ctxt.get_feature_space_info(self.type_id);
         let sketcher = &feature_space_info.sketcher.as_ref().unwrap();
         let kernel_mat = sketcher.get_kernel_matrix().as_ref().unwrap();
@@ -8,6 +8,7 @@ use crate::space_info::*;
 use crate::newly_evaluated_terms::*;
 use ndarray::*;
 use std::collections::HashSet;
+use std::collections::BTreeSet;
 use std::collections::HashMap;
 use crate::input_to_schmeared_output::*;
 use crate::sampled_embedder_state::*;
@@ -26,6 +27,7 @@ use crate::normal_inverse_wishart::*;
 use crate::elaborator::*;
 use topological_sort::TopologicalSort;
 use crate::context::*;
+use crate::fetish_error::*;
 use serde::{Serialize, Deserialize};
 
 ///An [`EmbedderState`] keeps track of the embeddings of function terms ([`TermModel`]s)
@@ -68,12 +70,15 @@ impl<'a> EmbedderState<'a> {
     }
 
     ///Draws a sample from the distribution over [`TermModel`]s represented in this
-    ///[`EmbedderState`], yielding a [`SampledEmbedderState`].
-    pub fn sample(&self, rng : &mut ThreadRng) -> SampledEmbedderState<'a> {
+    ///[`EmbedderState`], yielding a [`SampledEmbedderState`]. Spaces are sampled in
+    ///[`TypeId`] order, so that a seeded `rng` always yields the same sample.
+    pub fn sample<R : Rng + ?Sized>(&self, rng : &mut R) -> SampledEmbedderState<'a> {
         let mut embedding_spaces = HashMap::new();
-        for (type_id, model_space) in self.model_spaces.iter() {
-            let sampled_embedding_space = model_space.sample(rng); 
-            embedding_spaces.insert(*type_id, sampled_embedding_space);
+        let mut type_ids : Vec<TypeId> = self.model_spaces.keys().copied().collect();
+        type_ids.sort_unstable();
+        for type_id in type_ids {
+            let sampled_embedding_space = self.model_spaces[&type_id].sample(rng); 
+            embedding_spaces.insert(type_id, sampled_embedding_space);
         }
         SampledEmbedderState {
             embedding_spaces,
@@ -88,7 +93,7 @@ impl<'a> EmbedderState<'a> {
 
         let mut model_spaces = HashMap::new();
         for func_type_id in 0..ctxt.get_total_num_types() {
-            if (!ctxt.is_vector_type(func_type_id)) {
+            if (ctxt.is_func_type(func_type_id)) {
                 let mut model_space = EmbeddingSpace::new(func_type_id, ctxt);
 
                 //Initialize embeddings for primitive terms
@@ -108,6 +113,22 @@ impl<'a> EmbedderState<'a> {
         }
     }
 
+    ///Makes the terms that the given [`TermPointer`]s point to share one [`TermModel`], namely
+    ///that of `kept`, which absorbs all of the updates which were applied to that of `merged`.
+    ///Yields an error if the terms are of different types.
+    pub fn merge_embeddings(&mut self, kept : TermPointer, merged : TermPointer) -> Result<(), FetishError> {
+        if (kept.type_id != merged.type_id) {
+            return Result::Err(FetishError::TypeMismatch {
+                expected : kept.type_id,
+                actual : merged.type_id
+            });
+        }
+        let model_space = self.model_spaces.get_mut(&kept.type_id)
+                              .ok_or(FetishError::NotAFunctionType(kept.type_id))?;
+        model_space.merge_models(kept.index, merged.index);
+        Result::Ok(())
+    }
+
     ///Initializes default embeddings for the passed collection of terms in a
     ///[`NewlyEvaluatedTerms`].
     pub fn init_embeddings_for_new_terms(&mut self, newly_evaluated_terms : &NewlyEvaluatedTerms) {
@@ -123,17 +144,21 @@ impl<'a> EmbedderState<'a> {
     ///Given an [`InterpreterState`] and a collection of [`NewlyEvaluatedTerms`], performs
     ///a bottom-up (data) update followed by a top-down (prior) update recursively
     ///on all modified terms. This method may be used to keep the [`TermModel`]s in this
-    ///[`EmbedderState`] up-to-date with new information.
+    ///[`EmbedderState`] up-to-date with new information. Terms and applications are
+    ///always updated in the same order, so that the same evaluations yield the same embeddings.
     pub fn bayesian_update_step(&mut self, interpreter_state : &InterpreterState,
                                            newly_evaluated_terms : &NewlyEvaluatedTerms) {
         self.init_embeddings_for_new_terms(newly_evaluated_terms);
 
-        let mut data_updated_terms : HashSet<TermPointer> = HashSet::new();
-        let mut prior_updated_terms : HashSet<TermPointer> = HashSet::new();
+        let mut data_updated_terms : BTreeSet<TermPointer> = BTreeSet::new();
+        let mut prior_updated_terms : BTreeSet<TermPointer> = BTreeSet::new();
 
-        let mut updated_apps : HashSet::<TermApplicationResult> = HashSet::new();
+        let mut seen_apps : HashSet::<&TermApplicationResult> = HashSet::new();
+        let mut updated_apps : Vec::<TermApplicationResult> = Vec::new();
         for term_app_result in newly_evaluated_terms.term_app_results.iter() {
-            updated_apps.insert(term_app_result.clone()); 
+            if (seen_apps.insert(term_app_result)) {
+                updated_apps.push(term_app_result.clone());
+            }
         }
 
         trace!("Propagating data updates for {} applications", updated_apps.len());
@@ -143,13 +168,8 @@ impl<'a> EmbedderState<'a> {
         self.propagate_prior_recursive(interpreter_state, &data_updated_terms, &mut prior_updated_terms,
                                        newly_evaluated_terms);
 
-        let mut all_updated_terms = HashSet::new();
-        for data_updated_term in data_updated_terms.drain() {
-            all_updated_terms.insert(data_updated_term);
-        }
-        for prior_updated_term in prior_updated_terms.drain() {
-            all_updated_terms.insert(prior_updated_term);
-        }
+        let mut all_updated_terms = data_updated_terms;
+        all_updated_terms.append(&mut prior_updated_terms);
         self.update_elaborators(all_updated_terms);
     }
 
@@ -203,32 +223,42 @@ impl<'a> EmbedderState<'a> {
         result
     }
 
-    fn get_compressed_schmear_from_ref(&self, term_ref : &TermReference) -> Schmear {
+    ///Yields the [`Schmear`] for the given [`TermReference`] in the compressed space
+    ///of its type. Vectors have zero covariance.
+    pub fn get_compressed_schmear_from_ref(&self, term_ref : &TermReference) -> Schmear {
         match term_ref {
             TermReference::FuncRef(func_ptr) => self.get_compressed_schmear_from_ptr(*func_ptr),
-            TermReference::VecRef(_, vec) => Schmear::from_vector(vec.view())
+            TermReference::VecRef(_, vec) => Schmear::from_vector(vec.view()),
+            TermReference::ProductRef(type_id, components) => {
+                let component_schmears : Vec<Schmear> = components.iter()
+                                                .map(|component| self.get_compressed_schmear_from_ref(component))
+                                                .collect();
+                let base_schmear = Schmear::concatenate(&component_schmears);
+                self.ctxt.get_feature_space_info(*type_id).compress_schmear(&base_schmear)
+            }
         }
     }
 
-    fn update_elaborators(&mut self, mut updated_terms : HashSet::<TermPointer>) {
-        for term_ptr in updated_terms.drain() {
+    fn update_elaborators(&mut self, updated_terms : BTreeSet::<TermPointer>) {
+        for term_ptr in updated_terms {
             let model_space = self.model_spaces.get_mut(&term_ptr.type_id).unwrap();
+            let model_key = model_space.resolve(term_ptr.index);
             let elaborator = &mut model_space.elaborator;
 
             //Remove existing data for the term
-            if (elaborator.has_data(&term_ptr.index)) {
-                elaborator.downdate_data(&term_ptr.index);
+            if (elaborator.has_data(&model_key)) {
+                elaborator.downdate_data(&model_key);
             }
 
-            let term_model = model_space.models.get(&term_ptr.index).unwrap();
-            elaborator.update_data(term_ptr.index, &term_model.model);
+            let term_model = model_space.models.get(&model_key).unwrap();
+            elaborator.update_data(model_key, &term_model.model);
         }
     }
 
     //Propagates prior updates downwards
     fn propagate_prior_recursive(&mut self, interpreter_state : &InterpreterState,
-                                     to_propagate : &HashSet::<TermPointer>,
-                                     all_modified : &mut HashSet::<TermPointer>,
+                                     to_propagate : &BTreeSet::<TermPointer>,
+                                     all_modified : &mut BTreeSet::<TermPointer>,
                                      newly_evaluated : &NewlyEvaluatedTerms) {
         let new_count_map = newly_evaluated.get_count_map();
 
@@ -247,9 +277,12 @@ impl<'a> EmbedderState<'a> {
             }
         }
 
-        let mut ret_type_set = HashSet::new();
+        let mut ret_type_set = BTreeSet::new();
+        let mut first_seen = HashMap::new();
         while (stack.len() > 0) {
             let elem = stack.pop().unwrap();
+            let num_seen = first_seen.len();
+            first_seen.entry(elem.clone()).or_insert(num_seen);
             let ret_ref = elem.get_ret_ref();
 
             ret_type_set.insert(elem.get_ret_type(self.ctxt));
@@ -271,8 +304,8 @@ impl<'a> EmbedderState<'a> {
 
         info!("Obtaining elaborator func schmears");
         let mut elaborator_func_schmears = HashMap::new();
-        for type_id in ret_type_set.drain() {
-            if (!self.ctxt.is_vector_type(type_id)) {
+        for type_id in ret_type_set {
+            if (self.ctxt.is_func_type(type_id)) {
                 let model_space = self.model_spaces.get(&type_id).unwrap();
                 let elaborator = &model_space.elaborator;
                 let elaborator_func_schmear = elaborator.get_expansion_func_schmear();
@@ -283,6 +316,7 @@ impl<'a> EmbedderState<'a> {
 
         while (!topo_sort.is_empty()) {
             let mut to_process = topo_sort.pop_all();
+            to_process.sort_by_key(|elem| first_seen.get(elem).copied());
             for elem in to_process.drain(..) {
                 let out_type = elem.get_ret_type(self.ctxt);
                 let elaborator_func_schmear = elaborator_func_schmears.get(&out_type).unwrap();
@@ -299,8 +333,8 @@ impl<'a> EmbedderState<'a> {
 
     //Propagates data updates upwards
     fn propagate_data_recursive(&mut self, interpreter_state : &InterpreterState,
-                                    to_propagate : &HashSet::<TermApplicationResult>,
-                                    all_modified : &mut HashSet::<TermPointer>,
+                                    to_propagate : &[TermApplicationResult],
+                                    all_modified : &mut BTreeSet::<TermPointer>,
                                     newly_evaluated : &NewlyEvaluatedTerms) {
         let new_count_map = newly_evaluated.get_count_map();
 
@@ -311,8 +345,11 @@ impl<'a> EmbedderState<'a> {
             stack.push(elem.clone());
         }
 
+        let mut first_seen = HashMap::new();
         while (stack.len() > 0) {
             let elem = stack.pop().unwrap();
+            let num_seen = first_seen.len();
+            first_seen.entry(elem.clone()).or_insert(num_seen);
             let func_ptr = elem.get_func_ptr();
             let func_ref = TermReference::FuncRef(func_ptr.clone());
 
@@ -334,7 +371,10 @@ impl<'a> EmbedderState<'a> {
         }
 
         while (!topo_sort.is_empty()) {
-            let to_process = topo_sort.pop_all();
+            let mut to_process = topo_sort.pop_all();
+            //Applications without dependencies on one another are processed in the order they
+            //were found, since the order of updates to the same model affects its rounding
+            to_process.sort_by_key(|elem| first_seen.get(elem).copied());
             for elem in to_process {
                 let new_count = match (new_count_map.get(&elem)) {
                     Option::None => 0,
@@ -25,11 +25,14 @@ use std::collections::HashMap;
 
 ///Collection of all learned information about embeddings for a given type
 ///in and [`EmbedderState`]. This comprises the learned [`Elaborator`]s and
-///the learned [`TermModel`]s for the type.
+///the learned [`TermModel`]s for the type. Terms which were merged (see [`Self::merge_models`])
+///share the [`TermModel`] of the term they were merged into.
 pub struct EmbeddingSpace<'a> {
     pub type_id : TypeId,
     pub elaborator : Elaborator<'a>,
     pub models : HashMap<TermIndex, TermModel<'a>>,
+    ///Maps merged [`TermIndex`]es to the [`TermIndex`] whose [`TermModel`] they share.
+    pub aliases : HashMap<TermIndex, TermIndex>,
     pub ctxt : &'a Context
 }
 
@@ -37,7 +40,8 @@ pub struct EmbeddingSpace<'a> {
 pub struct SerializedEmbeddingSpace {
     pub type_id : TypeId,
     pub elaborator : SerializedElaborator,
-    pub models : HashMap<TermIndex, SerializedTermModel>
+    pub models : HashMap<TermIndex, SerializedTermModel>,
+    pub aliases : HashMap<TermIndex, TermIndex>
 }
 
 impl SerializedEmbeddingSpace {
@@ -52,6 +56,7 @@ impl SerializedEmbeddingSpace {
             type_id : self.type_id,
             elaborator,
             models,
+            aliases : self.aliases,
             ctxt
         }
     }
@@ -68,25 +73,30 @@ impl <'a> EmbeddingSpace<'a> {
         SerializedEmbeddingSpace {
             type_id : self.type_id,
             elaborator,
-            models
+            models,
+            aliases : self.aliases
         }
     }
 
     ///Draws a sample from the distribution defined by this [`EmbeddingSpace`]
     ///over collections of [`TermModel`]s of the same type, to yield
     ///a corresponding `SampledEmbeddingSpace` containing information about
-    ///sampled embeddings.
-    pub fn sample(&self, rng : &mut ThreadRng) -> SampledEmbeddingSpace<'a> {
+    ///sampled embeddings. Models are sampled in [`TermIndex`] order, so that
+    ///a seeded `rng` always yields the same sample.
+    pub fn sample<R : Rng + ?Sized>(&self, rng : &mut R) -> SampledEmbeddingSpace<'a> {
         //We do this for speed, but also because the variation should
         //already mostly be captured in the values for the embeddings
         //of various models. In light of that, this is taken as the MLE
         let elaborator = self.elaborator.get_mean();
 
         let mut result = SampledEmbeddingSpace::new(self.type_id, elaborator, self.ctxt);
-        for (key, model) in self.models.iter() {
-            let sample = SampledModelEmbedding::new(&model, rng);
-            result.models.insert(*key, sample);
+        let mut keys : Vec<TermIndex> = self.models.keys().copied().collect();
+        keys.sort_unstable();
+        for key in keys {
+            let sample = SampledModelEmbedding::new(&self.models[&key], rng);
+            result.models.insert(key, sample);
         }
+        result.aliases = self.aliases.clone();
         result
     }
 
@@ -135,15 +145,60 @@ impl <'a> EmbeddingSpace<'a> {
     
     ///Gets a handle to the [`TermModel`] with the given [`TermIndex`].
     pub fn get_model_mut(&mut self, model_key : TermIndex) -> &mut TermModel<'a> {
+        let model_key = self.resolve(model_key);
         self.models.get_mut(&model_key).unwrap()
     }
     ///Gets a reference to the [`TermModel`] with the given [`TermIndex`].
     pub fn get_model(&self, model_key : TermIndex) -> &TermModel<'a> {
-        self.models.get(&model_key).unwrap()
+        self.models.get(&self.resolve(model_key)).unwrap()
     }
     ///Determines if a [`TermModel`] exists with the given [`TermIndex`].
     pub fn has_model(&self, model_key : TermIndex) -> bool {
-        self.models.contains_key(&model_key)
+        self.models.contains_key(&self.resolve(model_key))
+    }
+    ///Yields the [`TermIndex`] whose [`TermModel`] is used for the given [`TermIndex`],
+    ///which differs from it only if it was merged into another.
+    pub fn resolve(&self, model_key : TermIndex) -> TermIndex {
+        *self.aliases.get(&model_key).unwrap_or(&model_key)
+    }
+
+    ///Merges the [`TermModel`] for the term with the `merged` [`TermIndex`] into the
+    ///[`TermModel`] for the term with the `kept` [`TermIndex`], so that both terms share
+    ///it from now on. If the [`Elaborator`] had data for either term, it's replaced by
+    ///data for the merged [`TermModel`]. Does nothing if both already share a [`TermModel`].
+    pub fn merge_models(&mut self, kept : TermIndex, merged : TermIndex) {
+        let kept = self.resolve(kept);
+        let merged = self.resolve(merged);
+        if (kept == merged) {
+            return;
+        }
+        let had_elaborator_data = self.elaborator.has_data(&kept) || self.elaborator.has_data(&merged);
+        if (self.elaborator.has_data(&kept)) {
+            self.elaborator.downdate_data(&kept);
+        }
+        if (self.elaborator.has_data(&merged)) {
+            self.elaborator.downdate_data(&merged);
+        }
+        if let Option::Some(merged_model) = self.models.remove(&merged) {
+            match (self.models.get_mut(&kept)) {
+                Option::Some(kept_model) => kept_model.absorb(merged_model),
+                Option::None => {
+                    self.models.insert(kept, merged_model);
+                }
+            }
+        }
+        for target in self.aliases.values_mut() {
+            if (*target == merged) {
+                *target = kept;
+            }
+        }
+        self.aliases.insert(merged, kept);
+
+        if (had_elaborator_data) {
+            if let Option::Some(kept_model) = self.models.get(&kept) {
+                self.elaborator.update_data(kept, &kept_model.model);
+            }
+        }
     }
 
     ///Constructs a new embedding space with the given [`TypeId`] for [`TermModel`]s
@@ -152,6 +207,7 @@ impl <'a> EmbeddingSpace<'a> {
         let elaborator = Elaborator::new(type_id, ctxt);
         EmbeddingSpace {
             models : HashMap::new(),
+            aliases : HashMap::new(),
             elaborator,
             type_id,
             ctxt
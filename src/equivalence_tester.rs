@@ -0,0 +1,291 @@
+use crate::type_id::*;
+use crate::context::*;
+use crate::term_pointer::*;
+use crate::primitive_term_pointer::*;
+use crate::nonprimitive_term_pointer::*;
+use crate::term_reference::*;
+use crate::term_application::*;
+use crate::interpreter_state::*;
+use crate::newly_evaluated_terms::*;
+use crate::rand_utils::*;
+use crate::array_utils::*;
+use crate::fetish_error::*;
+use rand::prelude::*;
+use std::collections::HashMap;
+
+///Decides whether function terms of the same type behave the same, by evaluating them
+///on a set of probe inputs and comparing the results. Vector arguments are probed with
+///random standard normal vectors, function arguments with existing terms of the argument type,
+///and tuple arguments with tuples of probes for their components. Function-valued results
+///are compared in the same way, recursively.
+pub struct EquivalenceTester {
+    ///The number of random vectors to probe vector arguments with.
+    pub num_random_probes : usize,
+    ///The maximal number of existing terms to probe function arguments with.
+    pub max_term_probes : usize,
+    ///Probes to use in addition to the generated ones, by argument [`TypeId`].
+    pub extra_probes : HashMap<TypeId, Vec<TermReference>>,
+    ///The maximal absolute difference between corresponding elements of vector
+    ///results which are considered to be the same.
+    pub tolerance : f32
+}
+
+impl EquivalenceTester {
+    ///Constructs an [`EquivalenceTester`] with the given number of random vector probes
+    ///and tolerance, which probes function arguments with up to 16 existing terms.
+    pub fn new(num_random_probes : usize, tolerance : f32) -> EquivalenceTester {
+        EquivalenceTester {
+            num_random_probes,
+            max_term_probes : 16,
+            extra_probes : HashMap::new(),
+            tolerance
+        }
+    }
+
+    ///Adds the given [`TermReference`] to the probes for arguments of its type.
+    pub fn add_probe(&mut self, probe : TermReference) {
+        self.extra_probes.entry(probe.get_type()).or_default().push(probe);
+    }
+
+    ///Yields probe inputs of the given [`TypeId`], drawing random vectors from `rng`.
+    pub fn get_probes<R : Rng + ?Sized>(&self, state : &InterpreterState, type_id : TypeId, rng : &mut R)
+                                       -> Vec<TermReference> {
+        let ctxt = state.get_context();
+        let mut result = match (ctxt.get_type(type_id)) {
+            Type::VecType(dim) => (0..self.num_random_probes).map(|_| {
+                let vec = gen_standard_normal_random(rng, dim);
+                TermReference::VecRef(type_id, to_noisy(vec.view()))
+            }).collect(),
+            Type::FuncType(_, _) => self.get_existing_terms(state, type_id),
+            Type::ProductType(component_type_ids) => {
+                let component_probes : Vec<Vec<TermReference>> = component_type_ids.iter()
+                                        .map(|component_type_id| self.get_probes(state, *component_type_id, rng))
+                                        .collect();
+                let num_probes = component_probes.iter().map(|probes| probes.len()).max().unwrap_or(0);
+                if (component_probes.iter().any(|probes| probes.is_empty())) {
+                    Vec::new()
+                } else {
+                    (0..num_probes).map(|i| {
+                        let components = component_probes.iter().map(|probes| probes[i % probes.len()].clone()).collect();
+                        TermReference::ProductRef(type_id, components)
+                    }).collect()
+                }
+            }
+        };
+        if let Option::Some(extra_probes) = self.extra_probes.get(&type_id) {
+            result.extend(extra_probes.iter().cloned());
+        }
+        result
+    }
+
+    fn get_existing_terms(&self, state : &InterpreterState, type_id : TypeId) -> Vec<TermReference> {
+        let ctxt = state.get_context();
+        let mut result = Vec::new();
+        if let Option::Some(primitive_space) = ctxt.primitive_directory.primitive_type_spaces.get(&type_id) {
+            for index in 0..primitive_space.terms.len() {
+                result.push(TermReference::FuncRef(TermPointer::from(PrimitiveTermPointer {
+                    type_id,
+                    index
+                })));
+            }
+        }
+        if let Option::Some(type_space) = state.type_spaces.get(&type_id) {
+            for index in 0..type_space.get_num_terms() {
+                result.push(TermReference::FuncRef(TermPointer::from(NonPrimitiveTermPointer {
+                    type_id,
+                    index
+                })));
+            }
+        }
+        result.truncate(self.max_term_probes);
+        result
+    }
+
+    ///Determines whether the function terms which the given [`TermPointer`]s point to
+    ///yield the same results on every probe input, yielding the [`NewlyEvaluatedTerms`] from
+    ///evaluating them. Distinct terms are never considered equivalent if there are no probe
+    ///inputs for their argument type. Yields an error if the terms are of different types,
+    ///or if any evaluation fails.
+    pub fn are_equivalent<R : Rng + ?Sized>(&self, state : &mut InterpreterState, one : TermPointer,
+                                            two : TermPointer, rng : &mut R)
+                                           -> Result<(bool, NewlyEvaluatedTerms), FetishError> {
+        if (one.type_id != two.type_id) {
+            return Result::Err(FetishError::TypeMismatch {
+                expected : one.type_id,
+                actual : two.type_id
+            });
+        }
+        let mut newly_evaluated_terms = NewlyEvaluatedTerms::new();
+        let equivalent = self.compare_funcs(state, one, two, rng, &mut newly_evaluated_terms)?;
+        Result::Ok((equivalent, newly_evaluated_terms))
+    }
+
+    fn compare_funcs<R : Rng + ?Sized>(&self, state : &mut InterpreterState, one : TermPointer, two : TermPointer,
+                                       rng : &mut R, newly_evaluated_terms : &mut NewlyEvaluatedTerms)
+                                      -> Result<bool, FetishError> {
+        if (one == two) {
+            return Result::Ok(true);
+        }
+        let arg_type_id = state.get_context().try_get_arg_type_id(one.type_id)?;
+        let probes = self.get_probes(state, arg_type_id, rng);
+        //Without any probes, there's no evidence that the terms behave the same
+        if (probes.is_empty()) {
+            return Result::Ok(false);
+        }
+        for probe in probes {
+            let mut results = Vec::new();
+            for func_ptr in [one, two].iter() {
+                let term_app = TermApplication {
+                    func_ptr : *func_ptr,
+                    arg_ref : probe.clone()
+                };
+                let (result_ref, more_evaluated_terms) = state.evaluate_memoized(&term_app)?;
+                newly_evaluated_terms.merge(more_evaluated_terms);
+                results.push(result_ref);
+            }
+            if (!self.compare_results(state, &results[0], &results[1], rng, newly_evaluated_terms)?) {
+                return Result::Ok(false);
+            }
+        }
+        Result::Ok(true)
+    }
+
+    fn compare_results<R : Rng + ?Sized>(&self, state : &mut InterpreterState, one : &TermReference, two : &TermReference,
+                                         rng : &mut R, newly_evaluated_terms : &mut NewlyEvaluatedTerms)
+                                        -> Result<bool, FetishError> {
+        match (one, two) {
+            (TermReference::VecRef(_, one_vec), TermReference::VecRef(_, two_vec)) => {
+                let diff = from_noisy(one_vec.view()) - from_noisy(two_vec.view());
+                Result::Ok(diff.iter().all(|elem| elem.abs() <= self.tolerance))
+            },
+            (TermReference::FuncRef(one_ptr), TermReference::FuncRef(two_ptr)) => {
+                self.compare_funcs(state, *one_ptr, *two_ptr, rng, newly_evaluated_terms)
+            },
+            (TermReference::ProductRef(_, one_components), TermReference::ProductRef(_, two_components)) => {
+                for (one_component, two_component) in one_components.iter().zip(two_components.iter()) {
+                    if (!self.compare_results(state, one_component, two_component, rng, newly_evaluated_terms)?) {
+                        return Result::Ok(false);
+                    }
+                }
+                Result::Ok(true)
+            },
+            _ => Result::Ok(false)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitive_template::*;
+    use crate::term_expression::*;
+    use crate::interpreter_and_embedder_state::*;
+    use crate::test_utils::*;
+    use crate::elaborator::*;
+
+    fn get_equivalence_context() -> Context {
+        get_test_template_context(&["(Vec2 -> Vec2) -> (Vec2 -> Vec2) -> Vec2 -> Vec2"],
+                                  vec![Box::new(RotateTemplate {}), Box::new(ComposeTemplate {}), Box::new(ITemplate {})])
+    }
+
+    #[test]
+    fn distinct_terms_with_equal_behavior_are_equivalent() {
+        let ctxt = get_equivalence_context();
+        let mut state = InterpreterState::new(&ctxt);
+        let mut rng = StdRng::seed_from_u64(TEST_SEED);
+        let tester = EquivalenceTester::new(8, 0.0001f32);
+
+        let identity = evaluate_test_func(&mut state, "Vec2 -> Vec2", "I");
+        let rotate = evaluate_test_func(&mut state, "Vec2 -> Vec2", "rotate");
+        let twice = evaluate_test_func(&mut state, "Vec2 -> Vec2", "compose rotate rotate");
+        assert!(twice != identity);
+
+        assert!(tester.are_equivalent(&mut state, identity, twice, &mut rng).unwrap().0);
+        assert!(!tester.are_equivalent(&mut state, rotate, twice, &mut rng).unwrap().0);
+        assert!(!tester.are_equivalent(&mut state, rotate, identity, &mut rng).unwrap().0);
+    }
+
+    #[test]
+    fn terms_without_probes_are_not_equivalent() {
+        let ctxt = get_equivalence_context();
+        let mut state = InterpreterState::new(&ctxt);
+        let mut rng = StdRng::seed_from_u64(TEST_SEED);
+        let tester = EquivalenceTester::new(0, 0.0001f32);
+
+        let identity = evaluate_test_func(&mut state, "Vec2 -> Vec2", "I");
+        let twice = evaluate_test_func(&mut state, "Vec2 -> Vec2", "compose rotate rotate");
+        assert!(tester.get_probes(&state, TEST_VECTOR_T, &mut rng).is_empty());
+        assert!(!tester.are_equivalent(&mut state, identity, twice, &mut rng).unwrap().0);
+        assert!(tester.are_equivalent(&mut state, identity, identity, &mut rng).unwrap().0);
+    }
+
+    #[test]
+    fn higher_order_terms_are_probed_with_existing_terms() {
+        let ctxt = get_equivalence_context();
+        let mut state = InterpreterState::new(&ctxt);
+        let mut rng = StdRng::seed_from_u64(TEST_SEED);
+        let tester = EquivalenceTester::new(4, 0.0001f32);
+        let type_text = "(Vec2 -> Vec2) -> Vec2 -> Vec2";
+
+        let one = evaluate_test_func(&mut state, type_text, "compose rotate");
+        let two = evaluate_test_func(&mut state, type_text, "compose (compose rotate (compose rotate rotate))");
+        let three = evaluate_test_func(&mut state, type_text, "compose (compose rotate rotate)");
+        assert!(one != two);
+        assert!(tester.are_equivalent(&mut state, one, two, &mut rng).unwrap().0);
+        assert!(!tester.are_equivalent(&mut state, one, three, &mut rng).unwrap().0);
+    }
+
+    #[test]
+    fn equivalent_terms_may_share_embeddings() {
+        let ctxt = get_equivalence_context();
+        let mut state = InterpreterAndEmbedderState::new(&ctxt);
+        let mut rng = StdRng::seed_from_u64(TEST_SEED);
+        let tester = EquivalenceTester::new(4, 0.0001f32);
+
+        let mut evaluate_func = |text : &str| match (state.evaluate_expression(text).unwrap()) {
+            TermReference::FuncRef(func_ptr) => func_ptr,
+            _ => panic!()
+        };
+        let identity = evaluate_func("(I : Vec2 -> Vec2)");
+        let twice = evaluate_func("(compose rotate rotate : Vec2 -> Vec2)");
+        assert!(state.merge_if_equivalent(&tester, identity, twice, &mut rng).unwrap());
+        assert!(!state.newly_evaluated_terms.term_app_results.is_empty());
+        state.bayesian_update_step();
+
+        let identity_model = state.embedder_state.get_embedding(identity);
+        let twice_model = state.embedder_state.get_embedding(twice);
+        assert!(std::ptr::eq(identity_model, twice_model));
+        assert_eq!(state.embedder_state.model_spaces[&identity.type_id].models.len(), 2);
+    }
+
+    #[test]
+    fn merging_refreshes_elaborator_data() {
+        let ctxt = get_equivalence_context();
+        let mut state = InterpreterAndEmbedderState::new(&ctxt);
+        let mut rng = StdRng::seed_from_u64(TEST_SEED);
+        let tester = EquivalenceTester::new(4, 0.0001f32);
+
+        let mut evaluate_func = |text : &str| match (state.evaluate_expression(text).unwrap()) {
+            TermReference::FuncRef(func_ptr) => func_ptr,
+            _ => panic!()
+        };
+        let once = evaluate_func("(rotate : Vec2 -> Vec2)");
+        let also_once = evaluate_func("(compose rotate (compose rotate rotate) : Vec2 -> Vec2)");
+        state.evaluate_expression("rotate [1, 2]").unwrap();
+        state.evaluate_expression("compose rotate (compose rotate rotate) [3, 4]").unwrap();
+        state.bayesian_update_step();
+        let elaborator = &state.embedder_state.model_spaces[&once.type_id].elaborator;
+        assert!(elaborator.has_data(&once.index) && elaborator.has_data(&also_once.index));
+        assert!(state.merge_if_equivalent(&tester, once, also_once, &mut rng).unwrap());
+
+        //Without another update step, the elaborator already reflects the merged model
+        let model_space = &state.embedder_state.model_spaces[&once.type_id];
+        let merged_model = &model_space.get_model(once.index).model;
+        let mut expected_elaborator = Elaborator::new(once.type_id, &ctxt);
+        expected_elaborator.update_data(once.index, merged_model);
+        assert!(model_space.elaborator.has_data(&once.index));
+        assert!(!model_space.elaborator.has_data(&also_once.index));
+        assert_eq!(model_space.elaborator.updates[&once.index][0].in_vec,
+                   expected_elaborator.updates[&once.index][0].in_vec);
+    }
+}
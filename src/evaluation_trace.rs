@@ -0,0 +1,355 @@
+use crate::term::*;
+use crate::term_reference::*;
+use crate::term_application::*;
+use crate::interpreter_state::*;
+use crate::displayable_with_state::*;
+use crate::fetish_error::*;
+use std::fmt;
+
+use serde::{Serialize, Deserialize};
+
+///A record of a single application performed by an [`InterpreterState`] while tracing
+///(see [`InterpreterState::start_tracing`]), together with the applications which were
+///performed in the course of evaluating it. The terms which an event refers to may have been
+///removed again by the time it's inspected (see [`InterpreterState::evaluate`]), so every event
+///also keeps the text of everything it refers to, as displayed when it was recorded.
+///Events are displayed as indented trees of applications, either against an [`InterpreterState`]
+///(see [`DisplayableWithState`]), which falls back to the recorded text only for terms that are
+///no longer present, or purely from the recorded text (see [`std::fmt::Display`]).
+#[derive(Clone, Serialize, Deserialize)]
+pub struct EvaluationEvent {
+    ///The application which was evaluated.
+    pub term_app : TermApplication,
+    ///The display text of [`Self::term_app`].
+    pub term_app_text : String,
+    ///The primitive which was invoked, together with all of the arguments which it received,
+    ///or `Option::None` if evaluation failed before the primitive could be determined.
+    ///Applications which don't supply the last argument of a primitive only store a new
+    ///[`PartiallyAppliedTerm`], and don't invoke it.
+    pub invocation : Option<PartiallyAppliedTerm>,
+    ///The display text of [`Self::invocation`], if any.
+    pub invocation_text : Option<String>,
+    ///The result of the application, or `Option::None` if its evaluation failed.
+    pub result_ref : Option<TermReference>,
+    ///The display text of [`Self::result_ref`], if any.
+    pub result_text : Option<String>,
+    ///The message of the [`FetishError`] which ended the evaluation, if it failed.
+    pub error : Option<String>,
+    ///Whether this application had no previously-recorded result.
+    pub newly_created : bool,
+    ///Whether the result was looked up from an [`crate::application_table::ApplicationTable`]
+    ///instead of being evaluated, in which case there are no child events.
+    pub cached : bool,
+    ///The applications performed while evaluating this one, in order.
+    pub children : Vec<EvaluationEvent>
+}
+
+///A forest of [`EvaluationEvent`]s, one tree for every top-level application which was
+///performed while an [`InterpreterState`] was tracing.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct EvaluationTrace {
+    pub events : Vec<EvaluationEvent>
+}
+
+impl EvaluationEvent {
+    fn new(term_app : &TermApplication, state : &InterpreterState) -> EvaluationEvent {
+        EvaluationEvent {
+            term_app : term_app.clone(),
+            term_app_text : term_app.display(state),
+            invocation : Option::None,
+            invocation_text : Option::None,
+            result_ref : Option::None,
+            result_text : Option::None,
+            error : Option::None,
+            newly_created : false,
+            cached : false,
+            children : Vec::new()
+        }
+    }
+
+    fn set_result(&mut self, result_ref : &TermReference, state : &InterpreterState) {
+        self.result_ref = Option::Some(result_ref.clone());
+        self.result_text = Option::Some(result_ref.display(state));
+    }
+
+    //Displays the given value against the given state if every term that it refers to is
+    //still present there, and otherwise falls back to the recorded text
+    fn resolve_text<T : DisplayableWithState>(value : &T, term_refs : &[&TermReference], text : &str,
+                                              state : Option<&InterpreterState>) -> String {
+        match (state) {
+            Option::Some(state) if term_refs.iter().all(|term_ref| is_present(term_ref, state)) => value.display(state),
+            _ => String::from(text)
+        }
+    }
+
+    fn display_indented(&self, state : Option<&InterpreterState>, depth : usize, lines : &mut Vec<String>) {
+        let func_ref = TermReference::FuncRef(self.term_app.func_ptr);
+        let term_app_text = Self::resolve_text(&self.term_app, &[&func_ref, &self.term_app.arg_ref],
+                                               &self.term_app_text, state);
+        let outcome = match (&self.result_ref, &self.result_text, &self.error) {
+            (Option::Some(result_ref), Option::Some(result_text), _) => {
+                Self::resolve_text(result_ref, &[result_ref], result_text, state)
+            },
+            (_, _, Option::Some(error)) => format!("error: {}", error),
+            _ => String::from("?")
+        };
+        let mut line = format!("{}{} => {}", "  ".repeat(depth), term_app_text, outcome);
+        if let (Option::Some(invocation), Option::Some(invocation_text)) = (&self.invocation, &self.invocation_text) {
+            let arg_refs : Vec<&TermReference> = invocation.args.iter().collect();
+            line.push_str(&format!(" via {}", Self::resolve_text(invocation, &arg_refs, invocation_text, state)));
+        }
+        if (self.cached) {
+            line.push_str(" [cached]");
+        } else if (self.newly_created) {
+            line.push_str(" [new]");
+        }
+        lines.push(line);
+        for child in self.children.iter() {
+            child.display_indented(state, depth + 1, lines);
+        }
+    }
+
+    fn display_with(&self, state : Option<&InterpreterState>) -> String {
+        let mut lines = Vec::new();
+        self.display_indented(state, 0, &mut lines);
+        lines.join("\n")
+    }
+}
+
+fn is_present(term_ref : &TermReference, state : &InterpreterState) -> bool {
+    match (term_ref) {
+        TermReference::FuncRef(func_ptr) => state.has_term(*func_ptr),
+        TermReference::VecRef(_, _) => true,
+        TermReference::ProductRef(_, components) => components.iter().all(|component| is_present(component, state))
+    }
+}
+
+impl DisplayableWithState for EvaluationEvent {
+    fn display(&self, state : &InterpreterState) -> String {
+        self.display_with(Option::Some(state))
+    }
+}
+
+impl DisplayableWithState for EvaluationTrace {
+    fn display(&self, state : &InterpreterState) -> String {
+        let displayed : Vec<String> = self.events.iter().map(|event| event.display(state)).collect();
+        displayed.join("\n")
+    }
+}
+
+impl fmt::Display for EvaluationEvent {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.display_with(Option::None))
+    }
+}
+
+impl fmt::Display for EvaluationTrace {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        let displayed : Vec<String> = self.events.iter().map(|event| event.to_string()).collect();
+        write!(f, "{}", displayed.join("\n"))
+    }
+}
+
+impl EvaluationTrace {
+    ///Serializes this [`EvaluationTrace`] as (pretty-printed) JSON.
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+///Records [`EvaluationEvent`]s as they happen. Events are opened when an application
+///starts being evaluated, and closed once its evaluation finishes.
+pub struct EvaluationTracer {
+    open_events : Vec<EvaluationEvent>,
+    trace : EvaluationTrace
+}
+
+impl EvaluationTracer {
+    ///Constructs an [`EvaluationTracer`] which hasn't recorded anything yet.
+    pub fn new() -> EvaluationTracer {
+        EvaluationTracer {
+            open_events : Vec::new(),
+            trace : EvaluationTrace {
+                events : Vec::new()
+            }
+        }
+    }
+
+    ///Opens an event for the evaluation of the given [`TermApplication`] against the given state.
+    pub fn open(&mut self, term_app : &TermApplication, state : &InterpreterState) {
+        self.open_events.push(EvaluationEvent::new(term_app, state));
+    }
+
+    ///Records on the innermost open event whether its application is newly-created,
+    ///and which primitive invocation it performs, if any.
+    pub fn record_invocation(&mut self, newly_created : bool, invocation : Option<PartiallyAppliedTerm>,
+                             state : &InterpreterState) {
+        if let Option::Some(event) = self.open_events.last_mut() {
+            event.newly_created = newly_created;
+            event.invocation_text = invocation.as_ref().map(|invocation| invocation.display(state));
+            event.invocation = invocation;
+        }
+    }
+
+    ///Closes the innermost open event with the given outcome of its evaluation.
+    pub fn close(&mut self, outcome : &Result<TermReference, FetishError>, state : &InterpreterState) {
+        if let Option::Some(mut event) = self.open_events.pop() {
+            match (outcome) {
+                Result::Ok(result_ref) => event.set_result(result_ref, state),
+                Result::Err(err) => event.error = Option::Some(err.to_string())
+            }
+            self.add_closed(event);
+        }
+    }
+
+    ///Records that the result of the given [`TermApplication`] was looked up,
+    ///rather than evaluated.
+    pub fn record_cached(&mut self, term_app : &TermApplication, result_ref : &TermReference,
+                         state : &InterpreterState) {
+        let mut event = EvaluationEvent::new(term_app, state);
+        event.set_result(result_ref, state);
+        event.cached = true;
+        self.add_closed(event);
+    }
+
+    fn add_closed(&mut self, event : EvaluationEvent) {
+        match (self.open_events.last_mut()) {
+            Option::Some(parent) => parent.children.push(event),
+            Option::None => self.trace.events.push(event)
+        }
+    }
+
+    ///Yields everything which was recorded.
+    pub fn finish(self) -> EvaluationTrace {
+        self.trace
+    }
+}
+
+impl Default for EvaluationTracer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::*;
+    use crate::primitive_template::*;
+    use crate::term_expression::*;
+    use crate::test_utils::*;
+    use crate::func_impl::*;
+    use crate::term_pointer::*;
+    use crate::newly_evaluated_terms::*;
+    use crate::type_id::*;
+
+    fn trace_text(state : &mut InterpreterState, text : &str) -> (Result<TermReference, FetishError>, EvaluationTrace) {
+        state.start_tracing();
+        let result = TermExpression::parse(text).unwrap().evaluate(state).map(|(result_ref, _)| result_ref);
+        (result, state.stop_tracing().unwrap())
+    }
+
+    #[test]
+    fn nested_applications_are_traced_as_children() {
+        let ctxt = get_test_compose_context();
+        let mut state = InterpreterState::new(&ctxt);
+        assert!(!state.is_tracing());
+        let (result, trace) = trace_text(&mut state, "compose rotate rotate [1, 2]");
+        assert!(!state.is_tracing());
+
+        assert_eq!(trace.events.len(), 3);
+        assert!(trace.events.iter().all(|event| event.newly_created && !event.cached));
+        assert!(trace.events[0].invocation.is_none());
+        assert!(trace.events[0].children.is_empty());
+
+        let full_app = &trace.events[2];
+        assert!(full_app.result_ref == Option::Some(result.unwrap()));
+        assert_eq!(full_app.invocation.as_ref().unwrap().args.len(), 3);
+        assert_eq!(full_app.children.len(), 2);
+        assert!(full_app.children.iter().all(|child| child.children.is_empty() && child.invocation.is_some()));
+
+        let text = trace.display(&state);
+        assert_eq!(text.lines().count(), 5);
+        assert_eq!(text.lines().filter(|line| line.starts_with("  ")).count(), 2);
+        assert_eq!(text, trace.to_string());
+
+        let parsed : serde_json::Value = serde_json::from_str(&trace.to_json().unwrap()).unwrap();
+        assert_eq!(parsed["events"][2]["children"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn memoized_applications_are_traced_as_cached() {
+        let ctxt = get_test_compose_context();
+        let mut state = InterpreterState::new(&ctxt);
+        trace_text(&mut state, "compose rotate rotate [1, 2]").0.unwrap();
+        let (_, trace) = trace_text(&mut state, "compose rotate rotate [1, 2]");
+
+        assert_eq!(trace.events.len(), 3);
+        assert!(trace.events.iter().all(|event| event.cached && event.children.is_empty()));
+        assert!(trace.display(&state).lines().all(|line| line.ends_with("[cached]")));
+    }
+
+    #[test]
+    fn failed_evaluations_record_errors() {
+        let ctxt = get_test_compose_context();
+        let mut state = InterpreterState::new(&ctxt);
+        state.limits.max_depth = Option::Some(1);
+        let (result, trace) = trace_text(&mut state, "compose rotate rotate [1, 2]");
+        let err = result.err().unwrap();
+
+        let full_app = &trace.events[2];
+        assert!(full_app.result_ref.is_none());
+        assert_eq!(full_app.error, Option::Some(err.to_string()));
+        assert_eq!(full_app.children.len(), 1);
+        assert!(full_app.children[0].error.is_some());
+    }
+
+    //Curries its argument into `+`, and then fails, so that the partial application is removed again
+    #[derive(Clone)]
+    struct CurryThenFailImpl {}
+
+    impl HasFuncSignature for CurryThenFailImpl {
+        fn get_name(&self) -> String {
+            String::from("curryThenFail")
+        }
+        fn required_arg_types(&self) -> Vec<TypeId> {
+            vec![TEST_VECTOR_T]
+        }
+        fn ret_type(&self) -> TypeId {
+            TEST_VECTOR_T
+        }
+    }
+    impl FuncImpl for CurryThenFailImpl {
+        fn evaluate(&self, state : &mut InterpreterState, args : Vec<TermReference>)
+                   -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+            let add_ptr = TermPointer::from(state.get_context().primitive_directory.find_primitives("+")[0]);
+            state.apply(add_ptr, args[0].clone())?;
+            Result::Err(FetishError::NonFiniteValue { name : self.get_name() })
+        }
+    }
+
+    #[test]
+    fn traces_of_rolled_back_evaluations_are_displayable() {
+        let mut builder = get_test_template_context_builder(&["Vec2 -> Vec2 -> Vec2"], Vec::new());
+        builder.add_binary_func(TEST_VECTOR_T, Box::new(AddOperator {}));
+        builder.add_primitive(Box::new(CurryThenFailImpl {}));
+        let ctxt = builder.build().unwrap();
+        let mut state = InterpreterState::new(&ctxt);
+        let (result, trace) = trace_text(&mut state, "curryThenFail [1, 2]");
+        assert!(result.is_err());
+
+        //The partial application of `+` was removed again, but its event still displays it
+        let curried = &trace.events[0].children[0];
+        assert!(curried.newly_created && curried.invocation.is_none());
+        let curried_ptr = match (curried.result_ref.as_ref().unwrap()) {
+            TermReference::FuncRef(func_ptr) => *func_ptr,
+            _ => panic!()
+        };
+        assert!(!state.has_term(curried_ptr));
+
+        let text = trace.display(&state);
+        assert_eq!(text.lines().count(), 2);
+        assert!(text.lines().nth(1).unwrap().contains(curried.result_text.as_ref().unwrap()));
+        assert_eq!(text, trace.to_string());
+    }
+}
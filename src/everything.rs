@@ -11,7 +11,19 @@ pub use crate::term_index::*;
 pub use crate::displayable_with_context::*;
 pub use crate::primitive_type_space::*;
 pub use crate::primitive_directory::*;
+pub use crate::constant_directory::*;
+pub use crate::primitive_template::*;
 pub use crate::context::*;
+pub use crate::context_builder::*;
+pub use crate::fetish_error::*;
+pub use crate::type_parser::*;
+pub use crate::term_expression::*;
+pub use crate::expression_tree::*;
+pub use crate::evaluation_trace::*;
+pub use crate::term_enumerator::*;
+pub use crate::synthesizer::*;
+pub use crate::equivalence_tester::*;
+pub use crate::vector_type_metadata::*;
 pub use crate::compressed_inv_schmear::*;
 pub use crate::prior_specification::*;
 pub use crate::elaborator::*;
@@ -0,0 +1,178 @@
+use crate::type_id::*;
+use crate::context::*;
+use crate::term_pointer::*;
+use crate::primitive_term_pointer::*;
+use crate::term_reference::*;
+use crate::term_application::*;
+use crate::interpreter_state::*;
+use crate::newly_evaluated_terms::*;
+use crate::displayable_with_state::*;
+use crate::fetish_error::*;
+
+///A tree of nested function applications whose leaves are primitives, literal
+///[`TermReference`]s and pointers to existing terms, which may be evaluated
+///against an [`InterpreterState`] in one go via [`ExpressionTree::evaluate`].
+#[derive(Clone, PartialEq)]
+pub enum ExpressionTree {
+    ///The primitive function that the given [`PrimitiveTermPointer`] points to.
+    Primitive(PrimitiveTermPointer),
+    ///The function term that the given [`TermPointer`] points to.
+    Term(TermPointer),
+    ///A literal term, such as a vector.
+    Literal(TermReference),
+    ///An application of the former tree to the latter.
+    Application(Box<ExpressionTree>, Box<ExpressionTree>)
+}
+
+impl DisplayableWithState for ExpressionTree {
+    fn display(&self, state : &InterpreterState) -> String {
+        match (self) {
+            ExpressionTree::Primitive(primitive_ptr) => TermPointer::from(*primitive_ptr).display(state),
+            ExpressionTree::Term(term_ptr) => term_ptr.display(state),
+            ExpressionTree::Literal(term_ref) => term_ref.display(state),
+            ExpressionTree::Application(func, arg) => {
+                format!("({} {})", func.display(state), arg.display(state))
+            }
+        }
+    }
+}
+
+impl ExpressionTree {
+    ///Convenience constructor for the application of `func` to `arg`.
+    pub fn apply(func : ExpressionTree, arg : ExpressionTree) -> ExpressionTree {
+        ExpressionTree::Application(Box::new(func), Box::new(arg))
+    }
+
+    ///Yields the [`TypeId`] of the result of evaluating this [`ExpressionTree`] in the
+    ///given [`Context`], or an error if some application within it is ill-typed.
+    pub fn get_type(&self, ctxt : &Context) -> Result<TypeId, FetishError> {
+        match (self) {
+            ExpressionTree::Primitive(primitive_ptr) => Result::Ok(primitive_ptr.type_id),
+            ExpressionTree::Term(term_ptr) => Result::Ok(term_ptr.type_id),
+            ExpressionTree::Literal(term_ref) => Result::Ok(term_ref.get_type()),
+            ExpressionTree::Application(func, arg) => {
+                let func_type_id = func.get_type(ctxt)?;
+                let (arg_type_id, ret_type_id) = ctxt.type_info_directory.try_get_arg_and_ret_type_ids(func_type_id)?;
+                let actual_arg_type_id = arg.get_type(ctxt)?;
+                if (actual_arg_type_id != arg_type_id) {
+                    return Result::Err(FetishError::TypeMismatch {
+                        expected : arg_type_id,
+                        actual : actual_arg_type_id
+                    });
+                }
+                Result::Ok(ret_type_id)
+            }
+        }
+    }
+
+    ///Evaluates this [`ExpressionTree`] bottom-up against the given [`InterpreterState`],
+    ///yielding a [`TermReference`] to the result together with the [`NewlyEvaluatedTerms`]
+    ///merged from every application that was performed along the way. Applications whose
+    ///results were already recorded in the [`InterpreterState`]'s
+    ///[`crate::application_table::ApplicationTable`]s are not re-evaluated.
+    pub fn evaluate(&self, state : &mut InterpreterState) -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+        self.get_type(state.get_context())?;
+        let mut newly_evaluated_terms = NewlyEvaluatedTerms::new();
+        let result_ref = self.evaluate_into(state, &mut newly_evaluated_terms)?;
+        Result::Ok((result_ref, newly_evaluated_terms))
+    }
+
+    fn evaluate_into(&self, state : &mut InterpreterState, newly_evaluated_terms : &mut NewlyEvaluatedTerms)
+                    -> Result<TermReference, FetishError> {
+        match (self) {
+            ExpressionTree::Primitive(primitive_ptr) => Result::Ok(TermReference::FuncRef(TermPointer::from(*primitive_ptr))),
+            ExpressionTree::Term(term_ptr) => {
+                let term_ref = TermReference::FuncRef(*term_ptr);
+                state.check_term_reference(&term_ref, term_ptr.type_id)?;
+                Result::Ok(term_ref)
+            },
+            ExpressionTree::Literal(term_ref) => {
+                state.check_term_reference(term_ref, term_ref.get_type())?;
+                Result::Ok(term_ref.clone())
+            },
+            ExpressionTree::Application(func, arg) => {
+                let func_ptr = match (func.evaluate_into(state, newly_evaluated_terms)?) {
+                    TermReference::FuncRef(func_ptr) => func_ptr,
+                    other_ref => return Result::Err(FetishError::NotAFunctionType(other_ref.get_type()))
+                };
+                let arg_ref = arg.evaluate_into(state, newly_evaluated_terms)?;
+                let term_app = TermApplication::new(func_ptr, arg_ref, state.get_context())?;
+                let (result_ref, more_evaluated_terms) = state.evaluate_memoized(&term_app)?;
+                newly_evaluated_terms.merge(more_evaluated_terms);
+                Result::Ok(result_ref)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::*;
+    use crate::array_utils::*;
+    use crate::test_utils::*;
+
+    fn vector_literal(elements : Vec<f32>) -> ExpressionTree {
+        ExpressionTree::Literal(TermReference::VecRef(TEST_VECTOR_T, to_noisy(Array::from(elements).view())))
+    }
+
+    fn add_primitive() -> ExpressionTree {
+        ExpressionTree::Primitive(PrimitiveTermPointer {
+            type_id : TEST_BINARY_VECTOR_FUNC_T,
+            index : 0
+        })
+    }
+
+    fn rotate_primitive() -> ExpressionTree {
+        ExpressionTree::Primitive(PrimitiveTermPointer {
+            type_id : TEST_VECTOR_FUNC_T,
+            index : 0
+        })
+    }
+
+    #[test]
+    fn nested_applications_evaluate_bottom_up() {
+        let ctxt = get_test_function_context();
+        let mut state = InterpreterState::new(&ctxt);
+        let rotated = ExpressionTree::apply(rotate_primitive(), vector_literal(vec![1.0, 2.0]));
+        let tree = ExpressionTree::apply(ExpressionTree::apply(add_primitive(), rotated), vector_literal(vec![10.0, 20.0]));
+        assert_eq!(tree.get_type(&ctxt), Result::Ok(TEST_VECTOR_T));
+
+        let (result_ref, newly_evaluated_terms) = tree.evaluate(&mut state).unwrap();
+        assert!(result_ref == TermReference::VecRef(TEST_VECTOR_T, to_noisy(array![12.0, 21.0].view())));
+        assert_eq!(newly_evaluated_terms.term_app_results.len(), 3);
+        assert_eq!(newly_evaluated_terms.terms.len(), 1);
+    }
+
+    #[test]
+    fn memoized_applications_are_reused() {
+        let ctxt = get_test_function_context();
+        let mut state = InterpreterState::new(&ctxt);
+        let partial = ExpressionTree::apply(add_primitive(), vector_literal(vec![1.0, 1.0]));
+        let tree = ExpressionTree::apply(partial, vector_literal(vec![2.0, 3.0]));
+
+        let (first_ref, _) = tree.evaluate(&mut state).unwrap();
+        let num_terms = state.type_spaces.get(&TEST_VECTOR_FUNC_T).unwrap().get_num_terms();
+        let (second_ref, newly_evaluated_terms) = tree.evaluate(&mut state).unwrap();
+
+        assert!(first_ref == second_ref);
+        assert!(newly_evaluated_terms.term_app_results.is_empty());
+        assert!(newly_evaluated_terms.terms.is_empty());
+        assert_eq!(state.type_spaces.get(&TEST_VECTOR_FUNC_T).unwrap().get_num_terms(), num_terms);
+    }
+
+    #[test]
+    fn ill_typed_trees_are_rejected_before_evaluation() {
+        let ctxt = get_test_function_context();
+        let mut state = InterpreterState::new(&ctxt);
+        let tree = ExpressionTree::apply(rotate_primitive(), rotate_primitive());
+        assert_eq!(tree.evaluate(&mut state).err(), Option::Some(FetishError::TypeMismatch {
+            expected : TEST_VECTOR_T,
+            actual : TEST_VECTOR_FUNC_T
+        }));
+        assert!(state.get_app_results_with_func(TermPointer::from(PrimitiveTermPointer {
+            type_id : TEST_VECTOR_FUNC_T,
+            index : 0
+        })).is_empty());
+    }
+}
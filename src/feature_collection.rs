@@ -7,6 +7,8 @@ use crate::quadratic_feature_collection::*;
 use crate::sketched_linear_feature_collection::*;
 use crate::rand_utils::*;
 
+use serde::{Serialize, Deserialize};
+
 pub trait FeatureCollection {
     ///Return the number of input dimensions
     fn get_in_dimensions(&self) -> usize;
@@ -23,6 +25,10 @@ pub trait FeatureCollection {
     ///in the format f x s, for s the input space size
     fn get_jacobian(&self, in_vec: ArrayView1<f32>) -> Array2<f32>;
 
+    ///Yields a [`SerializedFeatureCollection`] from which an identical
+    ///feature collection may later be restored.
+    fn to_serialized(&self) -> SerializedFeatureCollection;
+
     ///Given a matrix whose rows are each input vectors, yields a new
     ///matrix where every row of the output is the featurized version
     ///of the corresponding input vector
@@ -39,6 +45,25 @@ pub trait FeatureCollection {
     }
 }
 
+///Serializable form of any of the [`FeatureCollection`]s provided by this crate.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum SerializedFeatureCollection {
+    Fourier(FourierFeatureCollection),
+    Quadratic(SerializableQuadraticFeatureCollection),
+    SketchedLinear(SketchedLinearFeatureCollection)
+}
+
+impl SerializedFeatureCollection {
+    ///Restores the [`FeatureCollection`] that this [`SerializedFeatureCollection`] was created from.
+    pub fn deserialize(self) -> Box<dyn FeatureCollection> {
+        match (self) {
+            SerializedFeatureCollection::Fourier(collection) => Box::new(collection),
+            SerializedFeatureCollection::Quadratic(collection) => Box::new(collection.deserialize()),
+            SerializedFeatureCollection::SketchedLinear(collection) => Box::new(collection)
+        }
+    }
+}
+
 ///Gets the total number of feature dimensions in the passed [`Vec`] of [`FeatureCollection`] trait
 ///objects.
 pub fn get_total_feat_dims(feature_collections : &Vec<Box<dyn FeatureCollection>>) -> usize {
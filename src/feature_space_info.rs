@@ -11,6 +11,8 @@ use crate::params::*;
 use crate::schmear::*;
 use crate::inverse_schmear::*;
 
+use serde::{Serialize, Deserialize};
+
 ///Information about the base space dimension, the preferred
 ///sketcher for the space, the feature dimensions, and the feature mapping.
 ///In other words, this struct carries all information defining spaces which
@@ -25,7 +27,43 @@ pub struct FeatureSpaceInfo {
     pub sketcher : Option<LinearSketch>
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SerializedFeatureSpaceInfo {
+    pub base_dimensions : usize,
+    pub feature_dimensions : usize,
+    pub feature_collections : Vec<SerializedFeatureCollection>,
+    pub sketcher : Option<LinearSketch>
+}
+
+impl SerializedFeatureSpaceInfo {
+    pub fn deserialize(mut self) -> FeatureSpaceInfo {
+        let feature_collections = self.feature_collections.drain(..)
+                                      .map(|collection| collection.deserialize())
+                                      .collect();
+        FeatureSpaceInfo {
+            base_dimensions : self.base_dimensions,
+            feature_dimensions : self.feature_dimensions,
+            feature_collections,
+            sketcher : self.sketcher
+        }
+    }
+}
+
 impl FeatureSpaceInfo {
+    ///Yields a [`SerializedFeatureSpaceInfo`] which preserves the exact sketcher
+    ///and feature mapping for this [`FeatureSpaceInfo`].
+    pub fn serialize(&self) -> SerializedFeatureSpaceInfo {
+        let feature_collections = self.feature_collections.iter()
+                                      .map(|collection| collection.to_serialized())
+                                      .collect();
+        SerializedFeatureSpaceInfo {
+            base_dimensions : self.base_dimensions,
+            feature_dimensions : self.feature_dimensions,
+            feature_collections,
+            sketcher : self.sketcher.clone()
+        }
+    }
+
     ///Gets the projection matrix from the base space to the compressed space.
     pub fn get_projection_matrix(&self) -> Array2<f32> {
         match (&self.sketcher) {
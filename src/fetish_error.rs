@@ -0,0 +1,161 @@
+use std::fmt;
+use crate::type_id::*;
+use crate::term_pointer::*;
+use crate::context_builder::*;
+
+///Crate-wide error type for recoverable failures, such as attempting to evaluate
+///a malformed [`crate::term_application::TermApplication`] against an
+///[`crate::interpreter_state::InterpreterState`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum FetishError {
+    ///The referenced [`TypeId`] is not registered in the [`crate::context::Context`].
+    UnknownType(TypeId),
+    ///The referenced [`TypeId`] was expected to be a function type, but it isn't.
+    NotAFunctionType(TypeId),
+    ///The referenced [`TypeId`] was expected to be a vector type, but it isn't.
+    NotAVectorType(TypeId),
+    ///The referenced [`TypeId`] was expected to be a product type, but it isn't.
+    NotAProductType(TypeId),
+    ///There is no registered product type with the given component [`TypeId`]s.
+    MissingProductType(Vec<TypeId>),
+    ///There is no registered function type `arg -> ret` for the given [`TypeId`]s.
+    MissingFuncType { arg_type_id : TypeId, ret_type_id : TypeId },
+    ///There is no [`crate::prior_info::PriorInfo`] for the given function type.
+    MissingPrior(TypeId),
+    ///A term of the `expected` type was required, but a term of the `actual` type was supplied.
+    TypeMismatch { expected : TypeId, actual : TypeId },
+    ///A vector of the given type had `actual` elements, but the type declares `expected` of them.
+    DimensionMismatch { type_id : TypeId, expected : usize, actual : usize },
+    ///The given [`TermPointer`] doesn't point to any known term.
+    UnknownTerm(TermPointer),
+    ///The primitive with the given name expected a different number of arguments.
+    WrongArgumentCount { name : String, expected : usize, actual : usize },
+    ///The argument at the given position to the primitive with the given name was
+    ///a vector where a function was expected, or vice versa.
+    WrongArgumentVariant { name : String, index : usize },
+    ///Evaluating the primitive with the given name produced a NaN or infinite value.
+    NonFiniteValue { name : String },
+    ///The primitive with the given name was applied to an element outside of its domain,
+    ///see [`crate::func_impl::UnaryOperatorDomain`].
+    OutsideDomain { name : String, value : f32 },
+    ///The primitive with the given name needed an offset into a vector which has no elements.
+    EmptyVector { name : String },
+    ///An evaluation needed more applications than the given fuel budget allows,
+    ///see [`crate::interpreter_state::EvaluationLimits`].
+    OutOfFuel { fuel : usize },
+    ///An evaluation nested applications more deeply than the given maximum depth,
+    ///see [`crate::interpreter_state::EvaluationLimits`].
+    MaxDepthExceeded { max_depth : usize },
+    ///A recursive definition needed more unfoldings than the fuel given to the
+    ///primitive with the given name allows, see [`crate::func_impl::FixImpl`].
+    OutOfUnfoldings { name : String },
+    ///The primitive with the given name was given a count above the maximum it accepts,
+    ///such as [`crate::func_impl::MAX_ITERATIONS`].
+    CountTooLarge { name : String, max_count : usize },
+    ///There is no registered vector type with the given number of dimensions.
+    MissingVecType(usize),
+    ///No type has been registered under the given name.
+    UnknownTypeName(String),
+    ///The given name is already used for a different type.
+    DuplicateTypeName(String),
+    ///The given type already has a constant with the given name,
+    ///see [`crate::constant_directory::ConstantDirectory`].
+    DuplicateConstant { name : String, type_id : TypeId },
+    ///The given string can't be used as a type name.
+    InvalidTypeName(String),
+    ///The shape given for the named vector type implies `actual` dimensions,
+    ///but the type has `expected` dimensions.
+    ShapeMismatch { name : String, expected : usize, actual : usize },
+    ///Textual input was malformed at the given (character) position.
+    Parse { position : usize, message : String },
+    ///No primitive has the given name.
+    UnknownPrimitive(String),
+    ///The given expression has no well-typed reading.
+    IllTypedExpression(String),
+    ///The given expression has more than one well-typed reading.
+    AmbiguousExpression(String),
+    ///There is no instance of the primitive with the given name at the given function type,
+    ///such as a combinator needed to compile a lambda abstraction.
+    MissingPrimitiveInstance { name : String, type_id : TypeId },
+    ///Constructing or restoring a [`crate::context::Context`] failed.
+    ContextBuild(ContextBuildError)
+}
+
+impl fmt::Display for FetishError {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self) {
+            FetishError::UnknownType(type_id) =>
+                write!(f, "type {} is not registered", type_id),
+            FetishError::NotAFunctionType(type_id) =>
+                write!(f, "type {} is not a function type", type_id),
+            FetishError::NotAVectorType(type_id) =>
+                write!(f, "type {} is not a vector type", type_id),
+            FetishError::NotAProductType(type_id) =>
+                write!(f, "type {} is not a product type", type_id),
+            FetishError::MissingProductType(components) =>
+                write!(f, "the product type with components {:?} is not registered", components),
+            FetishError::MissingFuncType { arg_type_id, ret_type_id } =>
+                write!(f, "the function type ({} -> {}) is not registered", arg_type_id, ret_type_id),
+            FetishError::MissingPrior(type_id) =>
+                write!(f, "function type {} has no prior information", type_id),
+            FetishError::TypeMismatch { expected, actual } =>
+                write!(f, "expected a term of type {}, but got a term of type {}", expected, actual),
+            FetishError::DimensionMismatch { type_id, expected, actual } =>
+                write!(f, "vectors of type {} have {} elements, but got {}", type_id, expected, actual),
+            FetishError::UnknownTerm(term_ptr) =>
+                write!(f, "no term of type {} exists at {:?}", term_ptr.type_id, term_ptr.index),
+            FetishError::WrongArgumentCount { name, expected, actual } =>
+                write!(f, "primitive \"{}\" takes {} arguments, but got {}", name, expected, actual),
+            FetishError::WrongArgumentVariant { name, index } =>
+                write!(f, "argument {} to primitive \"{}\" is a vector where a function was expected, or vice versa",
+                       index, name),
+            FetishError::NonFiniteValue { name } =>
+                write!(f, "primitive \"{}\" produced a non-finite value", name),
+            FetishError::OutsideDomain { name, value } =>
+                write!(f, "{} lies outside of the domain of primitive \"{}\"", value, name),
+            FetishError::EmptyVector { name } =>
+                write!(f, "primitive \"{}\" can't take an offset into an empty vector", name),
+            FetishError::OutOfFuel { fuel } =>
+                write!(f, "evaluation ran out of fuel after {} applications", fuel),
+            FetishError::MaxDepthExceeded { max_depth } =>
+                write!(f, "evaluation exceeded the maximum nesting depth of {}", max_depth),
+            FetishError::OutOfUnfoldings { name } =>
+                write!(f, "primitive \"{}\" ran out of fuel for unfolding its recursive definition", name),
+            FetishError::CountTooLarge { name, max_count } =>
+                write!(f, "primitive \"{}\" accepts counts of at most {}", name, max_count),
+            FetishError::MissingVecType(dimensions) =>
+                write!(f, "no vector type with {} dimensions is registered", dimensions),
+            FetishError::UnknownTypeName(name) =>
+                write!(f, "no type is named \"{}\"", name),
+            FetishError::DuplicateTypeName(name) =>
+                write!(f, "the name \"{}\" already refers to a different type", name),
+            FetishError::DuplicateConstant { name, type_id } =>
+                write!(f, "type {} already has a constant named \"{}\"", type_id, name),
+            FetishError::InvalidTypeName(name) =>
+                write!(f, "\"{}\" is not a valid type name", name),
+            FetishError::ShapeMismatch { name, expected, actual } =>
+                write!(f, "the shape of vector type \"{}\" has {} elements, but the type has {} dimensions",
+                       name, actual, expected),
+            FetishError::Parse { position, message } =>
+                write!(f, "parse error at position {}: {}", position, message),
+            FetishError::UnknownPrimitive(name) =>
+                write!(f, "no primitive is named \"{}\"", name),
+            FetishError::IllTypedExpression(expression) =>
+                write!(f, "the expression \"{}\" is ill-typed", expression),
+            FetishError::AmbiguousExpression(expression) =>
+                write!(f, "the expression \"{}\" is ambiguous, consider annotating it with a type", expression),
+            FetishError::MissingPrimitiveInstance { name, type_id } =>
+                write!(f, "no instance of the primitive \"{}\" has type {}", name, type_id),
+            FetishError::ContextBuild(err) =>
+                write!(f, "{}", err)
+        }
+    }
+}
+
+impl std::error::Error for FetishError {}
+
+impl From<ContextBuildError> for FetishError {
+    fn from(err : ContextBuildError) -> Self {
+        FetishError::ContextBuild(err)
+    }
+}
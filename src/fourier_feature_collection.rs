@@ -24,15 +24,15 @@ impl FourierFeatureCollection {
     ///input dimensions, the given number of features, and the given closure which samples random
     ///coefficient vectors for the fourier features.
     ///Concretely, the features are of the form `x -> alpha * <w_i, x>`, where each `w_i` is
-    ///a sampled coefficient vector.
-    pub fn new(in_dimensions: usize, num_features : usize,
+    ///a sampled coefficient vector. All randomness is drawn from the passed `rng`.
+    pub fn new<R : Rng + ?Sized>(in_dimensions: usize, num_features : usize,
                alpha : f32,
-               generator : impl Fn(&mut ThreadRng, usize) -> Array1<f32>) -> FourierFeatureCollection {
+               generator : impl Fn(&mut R, usize) -> Array1<f32>,
+               rng : &mut R) -> FourierFeatureCollection {
 
         let mut ws = Array::zeros((num_features, in_dimensions));
-        let mut rng = rand::thread_rng();
         for i in 0..num_features {
-            let feature = generator(&mut rng, in_dimensions);
+            let feature = generator(rng, in_dimensions);
             for j in 0..in_dimensions {
                 ws[[i, j]] = feature[[j,]];
             }
@@ -82,6 +82,10 @@ impl FeatureCollection for FourierFeatureCollection {
     fn get_dimension(&self) -> usize {
         self.num_features * 2
     }
+
+    fn to_serialized(&self) -> SerializedFeatureCollection {
+        SerializedFeatureCollection::Fourier(self.clone())
+    }
 }
 
 #[cfg(test)]
@@ -94,7 +98,8 @@ mod tests {
     fn empirical_jacobian_is_jacobian() {
         let mut successes : usize = 0;
         for _ in 0..10 {
-            let fourier_feature_collection = FourierFeatureCollection::new(10, 15, 1.0f32, gen_nsphere_random);
+            let mut rng = rand::thread_rng();
+            let fourier_feature_collection = FourierFeatureCollection::new(10, 15, 1.0f32, gen_nsphere_random, &mut rng);
             let in_vec = random_vector(10);
             let jacobian = fourier_feature_collection.get_jacobian(in_vec.view());
             let empirical_jacobian = empirical_jacobian(|x| fourier_feature_collection.get_features(x),
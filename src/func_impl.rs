@@ -5,15 +5,19 @@ use crate::type_id::*;
 use crate::interpreter_state::*;
 use crate::term_reference::*;
 use crate::term_application::*;
+use crate::term_pointer::*;
+use crate::term::*;
+use crate::primitive_term_pointer::*;
 
 use ndarray::*;
 use noisy_float::prelude::*;
 
 use std::cmp::*;
-use std::fmt::*;
 use std::hash::*;
 use crate::params::*;
 use crate::newly_evaluated_terms::*;
+use crate::fetish_error::*;
+use crate::array_utils::*;
 
 ///Trait which gives a "signature" for
 ///functions to be included in a [`crate::primitive_directory::PrimitiveDirectory`].
@@ -56,8 +60,172 @@ pub trait FuncImpl : HasFuncSignature {
     ///apply this function implementation to, yields a [`TermReference`] to the result, along
     ///with a collection of any `NewlyEvaluatedTerms` which may have arisen as part of the
     ///implementation of this method. See `func_impl.rs` in the source for sample implementations.
+    ///Implementations should yield a [`FetishError`] rather than panicking on malformed arguments,
+    ///and should pass along any [`FetishError`]s from nested evaluations.
     fn evaluate(&self, state : &mut InterpreterState, args : Vec::<TermReference>)
-                -> (TermReference, NewlyEvaluatedTerms);
+                -> Result<(TermReference, NewlyEvaluatedTerms), FetishError>;
+
+    ///Given the arguments of a partial application of this function which is about to be
+    ///stored in the given [`InterpreterState`] (see [`InterpreterState::store_term`]), yields
+    ///an equivalent term in canonical form, if the partial application isn't canonical itself.
+    ///Along with the canonical term, yields any `NewlyEvaluatedTerms` from storing or evaluating
+    ///terms in the course of rewriting. This is where primitives declare rewriting rules, such
+    ///as associativity, identity elements, or constant folding. By default, every partial
+    ///application is canonical.
+    fn normalize(&self, _state : &mut InterpreterState, _args : &[TermReference])
+                -> Result<Option<(TermPointer, NewlyEvaluatedTerms)>, FetishError> {
+        Result::Ok(Option::None)
+    }
+}
+
+///Helper for [`FuncImpl`] implementations which yields the vector argument at the
+///given position, or a [`FetishError`] if it's missing or not a vector.
+pub fn get_vec_arg<'b>(func : &dyn HasFuncSignature, args : &'b [TermReference], index : usize)
+                      -> Result<&'b Array1<R32>, FetishError> {
+    match (get_arg(func, args, index)?) {
+        TermReference::VecRef(_, vec) => Result::Ok(vec),
+        _ => Result::Err(FetishError::WrongArgumentVariant {
+            name : func.get_name(),
+            index
+        })
+    }
+}
+
+///Helper for [`FuncImpl`] implementations which yields the components of the tuple argument
+///at the given position, or a [`FetishError`] if it's missing or not a tuple.
+pub fn get_product_arg<'b>(func : &dyn HasFuncSignature, args : &'b [TermReference], index : usize)
+                          -> Result<&'b [TermReference], FetishError> {
+    match (get_arg(func, args, index)?) {
+        TermReference::ProductRef(_, components) => Result::Ok(components),
+        _ => Result::Err(FetishError::WrongArgumentVariant {
+            name : func.get_name(),
+            index
+        })
+    }
+}
+
+///Helper for [`FuncImpl`] implementations which yields the function argument at the
+///given position, or a [`FetishError`] if it's missing or not a function.
+pub fn get_func_arg(func : &dyn HasFuncSignature, args : &[TermReference], index : usize)
+                   -> Result<TermPointer, FetishError> {
+    match (get_arg(func, args, index)?) {
+        TermReference::FuncRef(func_ptr) => Result::Ok(*func_ptr),
+        _ => Result::Err(FetishError::WrongArgumentVariant {
+            name : func.get_name(),
+            index
+        })
+    }
+}
+
+fn get_arg<'b>(func : &dyn HasFuncSignature, args : &'b [TermReference], index : usize)
+              -> Result<&'b TermReference, FetishError> {
+    args.get(index).ok_or_else(|| FetishError::WrongArgumentCount {
+        name : func.get_name(),
+        expected : func.required_arg_types().len(),
+        actual : args.len()
+    })
+}
+
+//If the given reference points to a partial application of a primitive with one of the
+//given names to exactly the given number of arguments, yields those arguments
+fn match_partial_application(state : &InterpreterState, term_ref : &TermReference,
+                             names : &[&str], num_args : usize) -> Option<Vec<TermReference>> {
+    if let TermReference::FuncRef(func_ptr) = term_ref {
+        let term = state.get(*func_ptr);
+        let name = state.get_context().get_primitive(term.func_ptr).get_name();
+        if (term.args.len() == num_args && names.contains(&name.as_str())) {
+            return Option::Some(term.args);
+        }
+    }
+    Option::None
+}
+
+fn is_identity(state : &InterpreterState, term_ref : &TermReference) -> bool {
+    match_partial_application(state, term_ref, &["I"], 0).is_some()
+}
+
+fn find_primitive(state : &InterpreterState, name : &str, type_id : TypeId) -> Option<PrimitiveTermPointer> {
+    state.get_context().primitive_directory.find_primitives(name).into_iter()
+         .find(|primitive_ptr| primitive_ptr.type_id == type_id)
+}
+
+//Stores the partial application of the given primitive to the given arguments,
+//which is itself normalized in the process
+fn store_partial_application(state : &mut InterpreterState, func_ptr : PrimitiveTermPointer,
+                             args : Vec<TermReference>) -> Result<(TermPointer, NewlyEvaluatedTerms), FetishError> {
+    let mut type_id = func_ptr.type_id;
+    for _ in args.iter() {
+        type_id = state.get_context().try_get_ret_type_id(type_id)?;
+    }
+    state.store_term(type_id, PartiallyAppliedTerm {
+        func_ptr,
+        args
+    })
+}
+
+//Rewriting rules for function composition, `compose f g`, which drop identities,
+//reassociate to the right, and fold constant inner functions:
+//`compose (compose f g) h = compose f (compose g h)`, `compose f (const x) = const (f x)`
+fn normalize_composition(func : &dyn HasFuncSignature, state : &mut InterpreterState, args : &[TermReference])
+                        -> Result<Option<(TermPointer, NewlyEvaluatedTerms)>, FetishError> {
+    if (args.len() != 2) {
+        return Result::Ok(Option::None);
+    }
+    let outer_ptr = get_func_arg(func, args, 0)?;
+    let inner_ptr = get_func_arg(func, args, 1)?;
+    if (is_identity(state, &args[0])) {
+        return Result::Ok(Option::Some((inner_ptr, NewlyEvaluatedTerms::new())));
+    }
+    if (is_identity(state, &args[1])) {
+        return Result::Ok(Option::Some((outer_ptr, NewlyEvaluatedTerms::new())));
+    }
+    let name = func.get_name();
+    let ctxt = state.get_context();
+    let in_type = ctxt.try_get_arg_type_id(inner_ptr.type_id)?;
+    let ret_type = ctxt.try_get_ret_type_id(outer_ptr.type_id)?;
+
+    if let Option::Some(outer_args) = match_partial_application(state, &args[0], &COMPOSE_NAMES, 2) {
+        let first_ptr = get_func_arg(func, &outer_args, 0)?;
+        let second_ptr = get_func_arg(func, &outer_args, 1)?;
+        let inner_ret_type = ctxt.try_get_ret_type_id(second_ptr.type_id)?;
+
+        let composed_inner_type = ctxt.try_get_func_type_id(in_type, inner_ret_type)?;
+        let composed_type = ctxt.try_get_func_type_id(in_type, ret_type)?;
+        let inner_compose = ctxt.try_get_func_type_id(inner_ptr.type_id, composed_inner_type)
+                                .and_then(|partial_type| ctxt.try_get_func_type_id(second_ptr.type_id, partial_type))
+                                .ok().and_then(|type_id| find_primitive(state, &name, type_id));
+        let outer_compose = ctxt.try_get_func_type_id(composed_inner_type, composed_type)
+                                .and_then(|partial_type| ctxt.try_get_func_type_id(first_ptr.type_id, partial_type))
+                                .ok().and_then(|type_id| find_primitive(state, &name, type_id));
+        if let (Option::Some(inner_compose), Option::Some(outer_compose)) = (inner_compose, outer_compose) {
+            let (composed_inner_ptr, mut newly_evaluated_terms) =
+                store_partial_application(state, inner_compose, vec![outer_args[1].clone(), args[1].clone()])?;
+            let (result_ptr, more_evaluated_terms) =
+                store_partial_application(state, outer_compose, vec![outer_args[0].clone(),
+                                                                     TermReference::FuncRef(composed_inner_ptr)])?;
+            newly_evaluated_terms.merge(more_evaluated_terms);
+            return Result::Ok(Option::Some((result_ptr, newly_evaluated_terms)));
+        }
+    }
+
+    for const_name in CONST_NAMES.iter() {
+        if let Option::Some(inner_args) = match_partial_application(state, &args[1], &[const_name], 1) {
+            let const_type = ctxt.try_get_func_type_id(in_type, ret_type)
+                                 .and_then(|partial_type| ctxt.try_get_func_type_id(ret_type, partial_type))
+                                 .ok().and_then(|type_id| find_primitive(state, const_name, type_id));
+            if let Option::Some(const_ptr) = const_type {
+                let term_app = TermApplication {
+                    func_ptr : outer_ptr,
+                    arg_ref : inner_args[0].clone()
+                };
+                let (folded_ref, mut newly_evaluated_terms) = state.evaluate(&term_app)?;
+                let (result_ptr, more_evaluated_terms) = store_partial_application(state, const_ptr, vec![folded_ref])?;
+                newly_evaluated_terms.merge(more_evaluated_terms);
+                return Result::Ok(Option::Some((result_ptr, newly_evaluated_terms)));
+            }
+        }
+    }
+    Result::Ok(Option::None)
 }
 
 impl PartialEq for dyn FuncImpl + '_ {
@@ -82,7 +250,8 @@ impl Hash for dyn FuncImpl + '_ {
 ///and return type. To be used in tandem with [`BinaryFuncImpl`].
 pub trait BinaryArrayOperator {
     ///Given two arrays of equal dimension, act to yield an array of the same number of dimensions.
-    fn act(&self, arg_one : ArrayView1::<R32>, arg_two : ArrayView1::<R32>) -> Array1::<R32>;
+    ///The result may contain non-finite values, which are reported by [`BinaryFuncImpl`].
+    fn act(&self, arg_one : ArrayView1::<f32>, arg_two : ArrayView1::<f32>) -> Array1::<f32>;
     ///Gets the name of this binary operator
     fn get_name(&self) -> String;
 }
@@ -106,7 +275,7 @@ pub struct AddOperator {
 }
 
 impl BinaryArrayOperator for AddOperator {
-    fn act(&self, arg_one : ArrayView1::<R32>, arg_two : ArrayView1::<R32>) -> Array1::<R32> {
+    fn act(&self, arg_one : ArrayView1::<f32>, arg_two : ArrayView1::<f32>) -> Array1::<f32> {
         &arg_one + &arg_two
     }
     fn get_name(&self) -> String {
@@ -119,7 +288,7 @@ pub struct SubOperator {
 }
 
 impl BinaryArrayOperator for SubOperator {
-    fn act(&self, arg_one : ArrayView1::<R32>, arg_two : ArrayView1::<R32>) -> Array1::<R32> {
+    fn act(&self, arg_one : ArrayView1::<f32>, arg_two : ArrayView1::<f32>) -> Array1::<f32> {
         &arg_one - &arg_two
     }
     fn get_name(&self) -> String {
@@ -132,7 +301,7 @@ pub struct MulOperator {
 }
 
 impl BinaryArrayOperator for MulOperator {
-    fn act(&self, arg_one : ArrayView1::<R32>, arg_two : ArrayView1::<R32>) -> Array1::<R32> {
+    fn act(&self, arg_one : ArrayView1::<f32>, arg_two : ArrayView1::<f32>) -> Array1::<f32> {
         &arg_one * &arg_two
     }
     fn get_name(&self) -> String {
@@ -160,18 +329,283 @@ impl HasFuncSignature for BinaryFuncImpl {
 }
 
 impl FuncImpl for BinaryFuncImpl {
-    fn evaluate(&self, _state : &mut InterpreterState, args : Vec::<TermReference>) -> (TermReference, NewlyEvaluatedTerms) {
-        if let TermReference::VecRef(_, arg_one_vec) = &args[0] {
-            if let TermReference::VecRef(_, arg_two_vec) = &args[1] {
-                let result_vec = self.f.act(arg_one_vec.view(), arg_two_vec.view());
-                let result_ref = TermReference::VecRef(self.elem_type, result_vec);
-                (result_ref, NewlyEvaluatedTerms::new())
-            } else {
-                panic!();
+    fn evaluate(&self, _state : &mut InterpreterState, args : Vec::<TermReference>)
+               -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+        let arg_one_vec = from_noisy(get_vec_arg(self, &args, 0)?.view());
+        let arg_two_vec = from_noisy(get_vec_arg(self, &args, 1)?.view());
+        let result_vec = self.f.act(arg_one_vec.view(), arg_two_vec.view());
+        if (!result_vec.iter().all(|elem| elem.is_finite())) {
+            return Result::Err(FetishError::NonFiniteValue { name : self.get_name() });
+        }
+        let result_ref = TermReference::VecRef(self.elem_type, to_noisy(result_vec.view()));
+        Result::Ok((result_ref, NewlyEvaluatedTerms::new()))
+    }
+}
+
+///The set of scalar arguments on which a [`UnaryArrayOperator`] is defined.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UnaryOperatorDomain {
+    ///Every finite scalar.
+    All,
+    ///Every finite scalar strictly greater than the given bound.
+    GreaterThan(f32),
+    ///Every finite nonzero scalar.
+    NonZero
+}
+
+impl UnaryOperatorDomain {
+    ///Determines whether the given scalar lies within this domain.
+    pub fn contains(&self, x : f32) -> bool {
+        x.is_finite() && match (self) {
+            UnaryOperatorDomain::All => true,
+            UnaryOperatorDomain::GreaterThan(bound) => x > *bound,
+            UnaryOperatorDomain::NonZero => x != 0.0f32
+        }
+    }
+}
+
+///Trait to ease implementation of primitive elementwise unary operators on vectors, whose
+///argument type and return type are identical. To be used in tandem with [`UnaryFuncImpl`].
+pub trait UnaryArrayOperator {
+    ///Given a scalar within [`Self::get_domain`], act to yield a scalar, which may fail
+    ///to be finite if the result overflows.
+    fn act(&self, x : f32) -> f32;
+    ///Gets the name of this unary operator
+    fn get_name(&self) -> String;
+    ///Gets the set of scalars which this unary operator may be applied to.
+    fn get_domain(&self) -> UnaryOperatorDomain {
+        UnaryOperatorDomain::All
+    }
+}
+
+impl PartialEq for dyn UnaryArrayOperator + '_ {
+    fn eq(&self, other : &Self) -> bool {
+        self.get_name() == other.get_name()
+    }
+}
+
+impl Eq for dyn UnaryArrayOperator + '_ {}
+
+impl Hash for dyn UnaryArrayOperator + '_ {
+    fn hash<H : Hasher>(&self, state : &mut H) {
+        self.get_name().hash(state);
+    }
+}
+
+///[`UnaryArrayOperator`] for negation.
+pub struct NegateOperator {
+}
+
+impl UnaryArrayOperator for NegateOperator {
+    fn act(&self, x : f32) -> f32 {
+        -x
+    }
+    fn get_name(&self) -> String {
+        String::from("negate")
+    }
+}
+
+///[`UnaryArrayOperator`] for absolute value.
+pub struct AbsOperator {
+}
+
+impl UnaryArrayOperator for AbsOperator {
+    fn act(&self, x : f32) -> f32 {
+        x.abs()
+    }
+    fn get_name(&self) -> String {
+        String::from("abs")
+    }
+}
+
+///[`UnaryArrayOperator`] for the exponential function.
+pub struct ExpOperator {
+}
+
+impl UnaryArrayOperator for ExpOperator {
+    fn act(&self, x : f32) -> f32 {
+        x.exp()
+    }
+    fn get_name(&self) -> String {
+        String::from("exp")
+    }
+}
+
+///[`UnaryArrayOperator`] for `x -> ln(1 + x)`, defined for `x > -1`.
+pub struct Log1pOperator {
+}
+
+impl UnaryArrayOperator for Log1pOperator {
+    fn act(&self, x : f32) -> f32 {
+        x.ln_1p()
+    }
+    fn get_name(&self) -> String {
+        String::from("log1p")
+    }
+    fn get_domain(&self) -> UnaryOperatorDomain {
+        UnaryOperatorDomain::GreaterThan(-1.0f32)
+    }
+}
+
+///[`UnaryArrayOperator`] for the sine function.
+pub struct SinOperator {
+}
+
+impl UnaryArrayOperator for SinOperator {
+    fn act(&self, x : f32) -> f32 {
+        x.sin()
+    }
+    fn get_name(&self) -> String {
+        String::from("sin")
+    }
+}
+
+///[`UnaryArrayOperator`] for the cosine function.
+pub struct CosOperator {
+}
+
+impl UnaryArrayOperator for CosOperator {
+    fn act(&self, x : f32) -> f32 {
+        x.cos()
+    }
+    fn get_name(&self) -> String {
+        String::from("cos")
+    }
+}
+
+///[`UnaryArrayOperator`] for the hyperbolic tangent.
+pub struct TanhOperator {
+}
+
+impl UnaryArrayOperator for TanhOperator {
+    fn act(&self, x : f32) -> f32 {
+        x.tanh()
+    }
+    fn get_name(&self) -> String {
+        String::from("tanh")
+    }
+}
+
+///[`UnaryArrayOperator`] for the rectifier `x -> max(x, 0)`.
+pub struct ReluOperator {
+}
+
+impl UnaryArrayOperator for ReluOperator {
+    fn act(&self, x : f32) -> f32 {
+        x.max(0.0f32)
+    }
+    fn get_name(&self) -> String {
+        String::from("relu")
+    }
+}
+
+///[`UnaryArrayOperator`] for the logistic sigmoid `x -> 1 / (1 + e^(-x))`.
+pub struct SigmoidOperator {
+}
+
+impl UnaryArrayOperator for SigmoidOperator {
+    fn act(&self, x : f32) -> f32 {
+        1.0f32 / (1.0f32 + (-x).exp())
+    }
+    fn get_name(&self) -> String {
+        String::from("sigmoid")
+    }
+}
+
+///[`UnaryArrayOperator`] for squaring.
+pub struct SquareOperator {
+}
+
+impl UnaryArrayOperator for SquareOperator {
+    fn act(&self, x : f32) -> f32 {
+        x * x
+    }
+    fn get_name(&self) -> String {
+        String::from("square")
+    }
+}
+
+///[`UnaryArrayOperator`] for `x -> sqrt(|x|)`.
+pub struct SqrtAbsOperator {
+}
+
+impl UnaryArrayOperator for SqrtAbsOperator {
+    fn act(&self, x : f32) -> f32 {
+        x.abs().sqrt()
+    }
+    fn get_name(&self) -> String {
+        String::from("sqrtAbs")
+    }
+}
+
+///[`UnaryArrayOperator`] for `x -> 1 / x`, defined for nonzero `x`.
+pub struct ReciprocalOperator {
+}
+
+impl UnaryArrayOperator for ReciprocalOperator {
+    fn act(&self, x : f32) -> f32 {
+        1.0f32 / x
+    }
+    fn get_name(&self) -> String {
+        String::from("reciprocal")
+    }
+    fn get_domain(&self) -> UnaryOperatorDomain {
+        UnaryOperatorDomain::NonZero
+    }
+}
+
+///Yields the standard set of [`UnaryArrayOperator`]s, namely negate, abs, exp, log1p, sin, cos,
+///tanh, relu, sigmoid, square, sqrtAbs and reciprocal.
+pub fn standard_unary_operators() -> Vec<Box<dyn UnaryArrayOperator>> {
+    vec![Box::new(NegateOperator {}), Box::new(AbsOperator {}), Box::new(ExpOperator {}),
+         Box::new(Log1pOperator {}), Box::new(SinOperator {}), Box::new(CosOperator {}),
+         Box::new(TanhOperator {}), Box::new(ReluOperator {}), Box::new(SigmoidOperator {}),
+         Box::new(SquareOperator {}), Box::new(SqrtAbsOperator {}), Box::new(ReciprocalOperator {})]
+}
+
+///Wrapper around a [`UnaryArrayOperator`] to conveniently lift it to an elementwise [`FuncImpl`]
+///given the [`TypeId`] of the argument/return type. Evaluation fails with a [`FetishError`]
+///instead of yielding a non-finite vector if any element lies outside of the operator's
+///domain, or if any result overflows.
+pub struct UnaryFuncImpl {
+    pub elem_type : TypeId,
+    pub f : Box<dyn UnaryArrayOperator>
+}
+
+impl HasFuncSignature for UnaryFuncImpl {
+    fn get_name(&self) -> String {
+        self.f.get_name()
+    }
+    fn required_arg_types(&self) -> Vec<TypeId> {
+        vec![self.elem_type]
+    }
+    fn ret_type(&self) -> TypeId {
+        self.elem_type
+    }
+}
+
+impl FuncImpl for UnaryFuncImpl {
+    fn evaluate(&self, _state : &mut InterpreterState, args : Vec::<TermReference>)
+               -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+        let arg_vec = get_vec_arg(self, &args, 0)?;
+        let domain = self.f.get_domain();
+        let mut result_vec : Array1<R32> = Array::from_elem((arg_vec.len(),), R32::new(0.0));
+        for (result_elem, arg_elem) in result_vec.iter_mut().zip(arg_vec.iter()) {
+            let x = arg_elem.raw();
+            if (!domain.contains(x)) {
+                return Result::Err(FetishError::OutsideDomain {
+                    name : self.get_name(),
+                    value : x
+                });
             }
-        } else {
-            panic!();
+            let y = self.f.act(x);
+            if (!y.is_finite()) {
+                return Result::Err(FetishError::NonFiniteValue { name : self.get_name() });
+            }
+            *result_elem = R32::new(y);
         }
+        let result_ref = TermReference::VecRef(self.elem_type, result_vec);
+        Result::Ok((result_ref, NewlyEvaluatedTerms::new()))
     }
 }
 
@@ -195,19 +629,17 @@ impl HasFuncSignature for RotateImpl {
 }
 
 impl FuncImpl for RotateImpl {
-    fn evaluate(&self, _state : &mut InterpreterState, args : Vec<TermReference>) -> (TermReference, NewlyEvaluatedTerms) {
-        if let TermReference::VecRef(vector_type, arg_vec) = &args[0] {
-            let n = arg_vec.len();
-            let arg_vec_head : R32 = arg_vec[[0,]];
-            let mut result_vec : Array1::<R32> = Array::from_elem((n,), arg_vec_head);
-            for i in 1..n {
-                result_vec[[i-1,]] = arg_vec[[i,]];
-            }
-            let result : TermReference = TermReference::VecRef(*vector_type, result_vec);
-            (result, NewlyEvaluatedTerms::new())
-        } else {
-            panic!();
+    fn evaluate(&self, _state : &mut InterpreterState, args : Vec<TermReference>)
+               -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+        let arg_vec = get_vec_arg(self, &args, 0)?;
+        let n = arg_vec.len();
+        let arg_vec_head : R32 = arg_vec[[0,]];
+        let mut result_vec : Array1::<R32> = Array::from_elem((n,), arg_vec_head);
+        for i in 1..n {
+            result_vec[[i-1,]] = arg_vec[[i,]];
         }
+        let result : TermReference = TermReference::VecRef(self.vector_type, result_vec);
+        Result::Ok((result, NewlyEvaluatedTerms::new()))
     }
 }
 
@@ -231,20 +663,15 @@ impl HasFuncSignature for SetHeadImpl {
     }
 }
 impl FuncImpl for SetHeadImpl {
-    fn evaluate(&self, _state : &mut InterpreterState, args : Vec<TermReference>) -> (TermReference, NewlyEvaluatedTerms) {
-        if let TermReference::VecRef(vector_type, arg_vec) = &args[0] {
-            if let TermReference::VecRef(_, val_vec) = &args[1] {
-                let val : R32 = val_vec[[0,]];
-                let mut result_vec : Array1<R32> = arg_vec.clone();
-                result_vec[[0,]] = val;
-                let result = TermReference::VecRef(*vector_type, result_vec);
-                (result, NewlyEvaluatedTerms::new())
-            } else {
-                panic!();
-            }
-        } else {
-            panic!();
-        }
+    fn evaluate(&self, _state : &mut InterpreterState, args : Vec<TermReference>)
+               -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+        let arg_vec = get_vec_arg(self, &args, 0)?;
+        let val_vec = get_vec_arg(self, &args, 1)?;
+        let val : R32 = val_vec[[0,]];
+        let mut result_vec : Array1<R32> = arg_vec.clone();
+        result_vec[[0,]] = val;
+        let result = TermReference::VecRef(self.vector_type, result_vec);
+        Result::Ok((result, NewlyEvaluatedTerms::new()))
     }
 }
 
@@ -268,22 +695,25 @@ impl HasFuncSignature for HeadImpl {
     }
 }
 impl FuncImpl for HeadImpl {
-    fn evaluate(&self, _state : &mut InterpreterState, args : Vec<TermReference>) -> (TermReference, NewlyEvaluatedTerms) {
-        if let TermReference::VecRef(_, arg_vec) = &args[0] {
-            let ret_val : R32 = arg_vec[[0,]];
-            let result_array : Array1::<R32> = Array::from_elem((1,), ret_val);
-
-            let result = TermReference::VecRef(self.scalar_type, result_array);
-            (result, NewlyEvaluatedTerms::new())
-        } else {
-            panic!();
-        }
+    fn evaluate(&self, _state : &mut InterpreterState, args : Vec<TermReference>)
+               -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+        let arg_vec = get_vec_arg(self, &args, 0)?;
+        let ret_val : R32 = arg_vec[[0,]];
+        let result_array : Array1::<R32> = Array::from_elem((1,), ret_val);
+
+        let result = TermReference::VecRef(self.scalar_type, result_array);
+        Result::Ok((result, NewlyEvaluatedTerms::new()))
     }
 }
 
-///Implementation of a "function composition" [`FuncImpl`]
+///Names under which [`ComposeImpl`] is registered, as `compose` and as the `B` combinator.
+pub const COMPOSE_NAMES : [&str; 2] = ["compose", "B"];
+
+///Implementation of a "function composition" [`FuncImpl`]. Also serves as the `B` combinator,
+///`B f g x = f (g x)`, under that name (see [`COMPOSE_NAMES`]).
 #[derive(Clone)]
 pub struct ComposeImpl {
+    name : String,
     in_type : TypeId,
     middle_type : TypeId,
     func_one : TypeId,
@@ -299,6 +729,7 @@ impl ComposeImpl {
         let func_one : TypeId = type_info_directory.get_func_type_id(middle_type, ret_type);
         let func_two : TypeId = type_info_directory.get_func_type_id(in_type, middle_type);
         ComposeImpl {
+            name : String::from("compose"),
             in_type,
             middle_type,
             func_one,
@@ -306,11 +737,25 @@ impl ComposeImpl {
             ret_type
         }
     }
+
+    ///Like [`Self::new`], but yields a [`ComposeImpl`] going by the given name, or an error
+    ///if any of the function types in its signature aren't registered.
+    pub fn named(name : &str, type_info_directory : &TypeInfoDirectory,
+                 in_type : TypeId, middle_type : TypeId, ret_type : TypeId) -> Result<ComposeImpl, FetishError> {
+        Result::Ok(ComposeImpl {
+            name : String::from(name),
+            in_type,
+            middle_type,
+            func_one : type_info_directory.try_get_func_type_id(middle_type, ret_type)?,
+            func_two : type_info_directory.try_get_func_type_id(in_type, middle_type)?,
+            ret_type
+        })
+    }
 }
 
 impl HasFuncSignature for ComposeImpl {
     fn get_name(&self) -> String {
-        String::from("compose")
+        self.name.clone()
     }
     fn required_arg_types(&self) -> Vec<TypeId> {
         vec![self.func_one, self.func_two, self.in_type]
@@ -321,28 +766,27 @@ impl HasFuncSignature for ComposeImpl {
 }
 
 impl FuncImpl for ComposeImpl {
-    fn evaluate(&self, state : &mut InterpreterState, args : Vec<TermReference>) -> (TermReference, NewlyEvaluatedTerms) {
-        if let TermReference::FuncRef(func_one) = &args[0] {
-            if let TermReference::FuncRef(func_two) = &args[1] {
-                let arg : TermReference = args[2].clone();
-                let application_one = TermApplication {
-                    func_ptr : func_two.clone(),
-                    arg_ref : arg
-                };
-                let (middle_ref, mut newly_evaluated_terms) = state.evaluate(&application_one);
-                let application_two = TermApplication {
-                    func_ptr : func_one.clone(),
-                    arg_ref : middle_ref
-                };
-                let (final_ref, more_evaluated_terms) = state.evaluate(&application_two);
-                newly_evaluated_terms.merge(more_evaluated_terms);
-                (final_ref, newly_evaluated_terms)
-            } else {
-                panic!();
-            }
-        } else {
-            panic!();
-        }
+    fn evaluate(&self, state : &mut InterpreterState, args : Vec<TermReference>)
+               -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+        let func_one = get_func_arg(self, &args, 0)?;
+        let func_two = get_func_arg(self, &args, 1)?;
+        let arg : TermReference = get_arg(self, &args, 2)?.clone();
+        let application_one = TermApplication {
+            func_ptr : func_two,
+            arg_ref : arg
+        };
+        let (middle_ref, mut newly_evaluated_terms) = state.evaluate(&application_one)?;
+        let application_two = TermApplication {
+            func_ptr : func_one,
+            arg_ref : middle_ref
+        };
+        let (final_ref, more_evaluated_terms) = state.evaluate(&application_two)?;
+        newly_evaluated_terms.merge(more_evaluated_terms);
+        Result::Ok((final_ref, newly_evaluated_terms))
+    }
+    fn normalize(&self, state : &mut InterpreterState, args : &[TermReference])
+                -> Result<Option<(TermPointer, NewlyEvaluatedTerms)>, FetishError> {
+        normalize_composition(self, state, args)
     }
 }
 
@@ -366,32 +810,46 @@ impl HasFuncSignature for FillImpl {
     }
 }
 impl FuncImpl for FillImpl {
-    fn evaluate(&self, state : &mut InterpreterState, args : Vec<TermReference>) -> (TermReference, NewlyEvaluatedTerms) {
-        let dim = state.get_context().get_dimension(self.vector_type);
+    fn evaluate(&self, state : &mut InterpreterState, args : Vec<TermReference>)
+               -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+        let dim = state.get_context().try_get_dimension(self.vector_type)?;
 
-        if let TermReference::VecRef(_, arg_vec) = &args[0] {
-            let arg_val : R32 = arg_vec[[0,]];
-            let ret_val : Array1::<R32> = Array::from_elem((dim,), arg_val);
+        let arg_vec = get_vec_arg(self, &args, 0)?;
+        let arg_val : R32 = arg_vec[[0,]];
+        let ret_val : Array1::<R32> = Array::from_elem((dim,), arg_val);
 
-            let result = TermReference::VecRef(self.vector_type, ret_val);
-            (result, NewlyEvaluatedTerms::new())
-        } else {
-            panic!();
-        }
+        let result = TermReference::VecRef(self.vector_type, ret_val);
+        Result::Ok((result, NewlyEvaluatedTerms::new()))
     }
 }
 
+///Names under which [`ConstImpl`] is registered, as `const` and as the `K` combinator.
+pub const CONST_NAMES : [&str; 2] = ["const", "K"];
+
 ///Implementation of the constant function for the given "return" and "ignored" types.
-///The result is of type `return -> ignored -> return`.
+///The result is of type `return -> ignored -> return`. Also serves as the `K` combinator,
+///`K x y = x`, under that name (see [`CONST_NAMES`]).
 #[derive(Clone)]
 pub struct ConstImpl {
+    pub name : String,
     pub ret_type : TypeId,
     pub ignored_type : TypeId
 }
 
+impl ConstImpl {
+    ///Constructs a [`ConstImpl`] named `const` for the given "return" and "ignored" types.
+    pub fn new(ret_type : TypeId, ignored_type : TypeId) -> ConstImpl {
+        ConstImpl {
+            name : String::from("const"),
+            ret_type,
+            ignored_type
+        }
+    }
+}
+
 impl HasFuncSignature for ConstImpl {
     fn get_name(&self) -> String {
-        String::from("const")
+        self.name.clone()
     }
     fn required_arg_types(&self) -> Vec<TypeId> {
         vec![self.ret_type.clone(), self.ignored_type.clone()]
@@ -401,9 +859,10 @@ impl HasFuncSignature for ConstImpl {
     }
 }
 impl FuncImpl for ConstImpl {
-    fn evaluate(&self, _state : &mut InterpreterState, args : Vec::<TermReference>) -> (TermReference, NewlyEvaluatedTerms) {
-        let result_ptr : TermReference = args[0].clone();
-        (result_ptr, NewlyEvaluatedTerms::new())
+    fn evaluate(&self, _state : &mut InterpreterState, args : Vec::<TermReference>)
+               -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+        let result_ptr : TermReference = get_arg(self, &args, 0)?.clone();
+        Result::Ok((result_ptr, NewlyEvaluatedTerms::new()))
     }
 }
 
@@ -430,46 +889,43 @@ impl HasFuncSignature for ReduceImpl {
 }
 
 impl FuncImpl for ReduceImpl {
-    fn evaluate(&self, state : &mut InterpreterState, args : Vec<TermReference>) -> (TermReference, NewlyEvaluatedTerms) {
-        let dim = state.get_context().get_dimension(self.vector_type);
+    fn evaluate(&self, state : &mut InterpreterState, args : Vec<TermReference>)
+               -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+        let dim = state.get_context().try_get_dimension(self.vector_type)?;
 
         let mut newly_evaluated_terms = NewlyEvaluatedTerms::new();
-        let mut accum_ref : TermReference = args[1].clone();
-        if let TermReference::FuncRef(func_ptr) = &args[0] {
-            if let TermReference::VecRef(_, vec) = &args[2] {
-                for i in 0..dim {
-                    //First, put the scalar term at this position into a term ref
-                    let val : R32 = vec[[i,]];
-                    let val_vec : Array1::<R32> = Array::from_elem((1,), val);
-                    let val_ref = TermReference::VecRef(self.scalar_type, val_vec);
-                     
-                    let term_app_one = TermApplication {
-                        func_ptr : func_ptr.clone(),
-                        arg_ref : val_ref
-                    };
-                    let (curry_ref, more_evaluated_terms) = state.evaluate(&term_app_one);
-                    newly_evaluated_terms.merge(more_evaluated_terms);
-
-                    if let TermReference::FuncRef(curry_ptr) = curry_ref {
-                        let term_app_two = TermApplication {
-                            func_ptr : curry_ptr,
-                            arg_ref : accum_ref
-                        };
-                        let (result_ref, more_evaluated_terms) = state.evaluate(&term_app_two);
-                        newly_evaluated_terms.merge(more_evaluated_terms);
-                        accum_ref = result_ref;
-                    } else {
-                        panic!();
-                    }
+        let func_ptr = get_func_arg(self, &args, 0)?;
+        let mut accum_ref : TermReference = get_arg(self, &args, 1)?.clone();
+        let vec = get_vec_arg(self, &args, 2)?;
+        for i in 0..dim {
+            //First, put the scalar term at this position into a term ref
+            let val : R32 = vec[[i,]];
+            let val_vec : Array1::<R32> = Array::from_elem((1,), val);
+            let val_ref = TermReference::VecRef(self.scalar_type, val_vec);
+             
+            let term_app_one = TermApplication {
+                func_ptr,
+                arg_ref : val_ref
+            };
+            let (curry_ref, more_evaluated_terms) = state.evaluate(&term_app_one)?;
+            newly_evaluated_terms.merge(more_evaluated_terms);
+
+            let curry_ptr = match (curry_ref) {
+                TermReference::FuncRef(curry_ptr) => curry_ptr,
+                other_ref => {
+                    return Result::Err(FetishError::NotAFunctionType(other_ref.get_type()));
                 }
-
-                (accum_ref, newly_evaluated_terms)
-            } else {
-                panic!();
-            }
-        } else {
-            panic!();
+            };
+            let term_app_two = TermApplication {
+                func_ptr : curry_ptr,
+                arg_ref : accum_ref
+            };
+            let (result_ref, more_evaluated_terms) = state.evaluate(&term_app_two)?;
+            newly_evaluated_terms.merge(more_evaluated_terms);
+            accum_ref = result_ref;
         }
+
+        Result::Ok((accum_ref, newly_evaluated_terms))
     }
 }
 
@@ -495,81 +951,1124 @@ impl HasFuncSignature for MapImpl {
 }
 
 impl FuncImpl for MapImpl {
-    fn evaluate(&self, state : &mut InterpreterState, args : Vec::<TermReference>) -> (TermReference, NewlyEvaluatedTerms) {
-        if let TermReference::FuncRef(func_ptr) = &args[0] {
-            if let TermReference::VecRef(_, arg_vec) = &args[1] {
-                let n = arg_vec.len();
-                let mut newly_evaluated_terms = NewlyEvaluatedTerms::new();
-                let mut result : Array1<R32> = Array::from_elem((n,), R32::new(0.0)); 
-                for i in 0..n {
-                    let boxed_scalar : Array1<R32> = Array::from_elem((1,), arg_vec[i]);
-                    let arg_ref = TermReference::VecRef(self.scalar_type, boxed_scalar);
-
-                    let term_app = TermApplication {
-                        func_ptr : func_ptr.clone(),
-                        arg_ref : arg_ref
-                    };
-                    let (result_ref, more_evaluated_terms) = state.evaluate(&term_app);
-                    newly_evaluated_terms.merge(more_evaluated_terms);
-                    if let TermReference::VecRef(_, result_scalar_vec) = result_ref {
-                        result[[i,]] = result_scalar_vec[[0,]];
-                    }
-                }
-                let result_ref = TermReference::VecRef(self.vector_type, result);
-                (result_ref, newly_evaluated_terms)
-            } else {
-                panic!();
+    fn evaluate(&self, state : &mut InterpreterState, args : Vec::<TermReference>)
+               -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+        let func_ptr = get_func_arg(self, &args, 0)?;
+        let arg_vec = get_vec_arg(self, &args, 1)?;
+        let n = arg_vec.len();
+        let mut newly_evaluated_terms = NewlyEvaluatedTerms::new();
+        let mut result : Array1<R32> = Array::from_elem((n,), R32::new(0.0)); 
+        for i in 0..n {
+            let boxed_scalar : Array1<R32> = Array::from_elem((1,), arg_vec[i]);
+            let arg_ref = TermReference::VecRef(self.scalar_type, boxed_scalar);
+
+            let term_app = TermApplication {
+                func_ptr,
+                arg_ref
+            };
+            let (result_ref, more_evaluated_terms) = state.evaluate(&term_app)?;
+            newly_evaluated_terms.merge(more_evaluated_terms);
+            match (result_ref) {
+                TermReference::VecRef(_, result_scalar_vec) => result[[i,]] = result_scalar_vec[[0,]],
+                other_ref => return Result::Err(FetishError::NotAVectorType(other_ref.get_type()))
             }
-        } else {
-            panic!();
         }
-
+        let result_ref = TermReference::VecRef(self.vector_type, result);
+        Result::Ok((result_ref, newly_evaluated_terms))
+    }
+    //Mapping the identity yields the identity
+    fn normalize(&self, state : &mut InterpreterState, args : &[TermReference])
+                -> Result<Option<(TermPointer, NewlyEvaluatedTerms)>, FetishError> {
+        if (args.len() != 1 || !is_identity(state, &args[0])) {
+            return Result::Ok(Option::None);
+        }
+        let vector_func_type = state.get_context().try_get_func_type_id(self.vector_type, self.vector_type)?;
+        let identity = find_primitive(state, "I", vector_func_type).map(|primitive_ptr| {
+            (TermPointer::from(primitive_ptr), NewlyEvaluatedTerms::new())
+        });
+        Result::Ok(identity)
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::test_utils::*;
-    use crate::array_utils::*;
+//Reads the scalar argument at the given position as an offset into a vector of the given
+//length, rounding it to the nearest integer and wrapping it around the length. Yields
+//an error for empty vectors, which have no offsets to wrap around to
+fn get_offset_arg(func : &dyn HasFuncSignature, args : &[TermReference], index : usize, len : usize)
+                 -> Result<usize, FetishError> {
+    if (len == 0) {
+        return Result::Err(FetishError::EmptyVector { name : func.get_name() });
+    }
+    let scalar_vec = get_vec_arg(func, args, index)?;
+    let rounded = scalar_vec[[0,]].raw().round() as i64;
+    Result::Ok(rounded.rem_euclid(len as i64) as usize)
+}
 
-    fn term_ref(in_array : Array1<f32>) -> TermReference {
-        let noisy_array = to_noisy(in_array.view());
-        if (in_array.shape()[0] == 1) {
-            TermReference::VecRef(TEST_SCALAR_T, noisy_array)
-        } else {
-            TermReference::VecRef(TEST_VECTOR_T, noisy_array)
-        }
+//Applies the given binary scalar function to the given scalars, in order
+fn apply_binary_scalar_func(state : &mut InterpreterState, func_ptr : TermPointer, scalar_type : TypeId,
+                            arg_one : R32, arg_two : R32, newly_evaluated_terms : &mut NewlyEvaluatedTerms)
+                           -> Result<R32, FetishError> {
+    let arg_one_ref = TermReference::VecRef(scalar_type, Array::from_elem((1,), arg_one));
+    let arg_two_ref = TermReference::VecRef(scalar_type, Array::from_elem((1,), arg_two));
+    let curry_ref = apply_func_ref(state, TermReference::FuncRef(func_ptr), arg_one_ref, newly_evaluated_terms)?;
+    match (apply_func_ref(state, curry_ref, arg_two_ref, newly_evaluated_terms)?) {
+        TermReference::VecRef(_, result_vec) => Result::Ok(result_vec[[0,]]),
+        other_ref => Result::Err(FetishError::NotAVectorType(other_ref.get_type()))
     }
+}
 
-    #[test]
-    fn test_addition() {
-        let ctxt = get_test_vector_only_context();
-        let mut state = InterpreterState::new(&ctxt);
-        let args = vec![term_ref(array![1.0f32, 2.0f32]), term_ref(array![3.0f32, 4.0f32])];
+///Implementation of a "concatenate two vectors" [`FuncImpl`] for the given [`TypeId`]s
+///of the two argument vector types and the return vector type, whose dimension is the
+///sum of their dimensions.
+#[derive(Clone)]
+pub struct ConcatImpl {
+    pub first_type : TypeId,
+    pub second_type : TypeId,
+    pub vector_type : TypeId
+}
 
-        let addition_func = BinaryFuncImpl {
-            elem_type : TEST_VECTOR_T,
-            f : Box::new(AddOperator {})
-        };
+impl HasFuncSignature for ConcatImpl {
+    fn get_name(&self) -> String {
+        String::from("concat")
+    }
+    fn required_arg_types(&self) -> Vec<TypeId> {
+        vec![self.first_type, self.second_type]
+    }
+    fn ret_type(&self) -> TypeId {
+        self.vector_type
+    }
+}
 
-        let (result, _) = addition_func.evaluate(&mut state, args);
-        assert_equal_vector_term(result, array![4.0f32, 6.0f32].view());
+impl FuncImpl for ConcatImpl {
+    fn evaluate(&self, _state : &mut InterpreterState, args : Vec<TermReference>)
+               -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+        let first_vec = get_vec_arg(self, &args, 0)?;
+        let second_vec = get_vec_arg(self, &args, 1)?;
+        let result_vec : Array1<R32> = first_vec.iter().chain(second_vec.iter()).cloned().collect();
+        let result = TermReference::VecRef(self.vector_type, result_vec);
+        Result::Ok((result, NewlyEvaluatedTerms::new()))
     }
-    #[test]
-    fn test_rotate() {
-        let ctxt = get_test_vector_only_context();
-        let mut state = InterpreterState::new(&ctxt);
-        let args = vec![term_ref(array![5.0f32, 10.0f32])];
+}
 
-        let rotate_func = RotateImpl {
-            vector_type : TEST_VECTOR_T
-        };
+///Implementation of a "take the contiguous slice starting at a fixed index" [`FuncImpl`]
+///for the given argument vector type and slice vector type, whose dimension determines
+///the length of the slice. The slice must lie within the argument vector, and the
+///implementation goes by the name `slice<start>`, such as `slice1` for `start = 1`.
+#[derive(Clone)]
+pub struct SliceImpl {
+    pub vector_type : TypeId,
+    pub slice_type : TypeId,
+    pub start : usize
+}
 
-        let (result, _) = rotate_func.evaluate(&mut state, args);
-        assert_equal_vector_term(result, array![10.0f32, 5.0f32].view());
+impl HasFuncSignature for SliceImpl {
+    fn get_name(&self) -> String {
+        format!("slice{}", self.start)
     }
-
+    fn required_arg_types(&self) -> Vec<TypeId> {
+        vec![self.vector_type]
+    }
+    fn ret_type(&self) -> TypeId {
+        self.slice_type
+    }
+}
+
+impl FuncImpl for SliceImpl {
+    fn evaluate(&self, state : &mut InterpreterState, args : Vec<TermReference>)
+               -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+        let slice_dim = state.get_context().try_get_dimension(self.slice_type)?;
+        let arg_vec = get_vec_arg(self, &args, 0)?;
+        let end = self.start + slice_dim;
+        if (end > arg_vec.len()) {
+            return Result::Err(FetishError::DimensionMismatch {
+                type_id : self.vector_type,
+                expected : end,
+                actual : arg_vec.len()
+            });
+        }
+        let result = TermReference::VecRef(self.slice_type, arg_vec.slice(s![self.start..end]).to_owned());
+        Result::Ok((result, NewlyEvaluatedTerms::new()))
+    }
+}
+
+///Implementation of a "reverse the order of elements" [`FuncImpl`] for the given vector [`TypeId`].
+#[derive(Clone)]
+pub struct ReverseImpl {
+    pub vector_type : TypeId
+}
+
+impl HasFuncSignature for ReverseImpl {
+    fn get_name(&self) -> String {
+        String::from("reverse")
+    }
+    fn required_arg_types(&self) -> Vec<TypeId> {
+        vec![self.vector_type]
+    }
+    fn ret_type(&self) -> TypeId {
+        self.vector_type
+    }
+}
+
+impl FuncImpl for ReverseImpl {
+    fn evaluate(&self, _state : &mut InterpreterState, args : Vec<TermReference>)
+               -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+        let arg_vec = get_vec_arg(self, &args, 0)?;
+        let result_vec : Array1<R32> = arg_vec.iter().rev().cloned().collect();
+        let result = TermReference::VecRef(self.vector_type, result_vec);
+        Result::Ok((result, NewlyEvaluatedTerms::new()))
+    }
+}
+
+///Implementation of a "dot product of two vectors" [`FuncImpl`] for the given
+///vector and scalar types.
+#[derive(Clone)]
+pub struct DotImpl {
+    pub vector_type : TypeId,
+    pub scalar_type : TypeId
+}
+
+impl HasFuncSignature for DotImpl {
+    fn get_name(&self) -> String {
+        String::from("dot")
+    }
+    fn required_arg_types(&self) -> Vec<TypeId> {
+        vec![self.vector_type, self.vector_type]
+    }
+    fn ret_type(&self) -> TypeId {
+        self.scalar_type
+    }
+}
+
+impl FuncImpl for DotImpl {
+    fn evaluate(&self, _state : &mut InterpreterState, args : Vec<TermReference>)
+               -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+        let arg_one_vec = from_noisy(get_vec_arg(self, &args, 0)?.view());
+        let arg_two_vec = from_noisy(get_vec_arg(self, &args, 1)?.view());
+        let dot = arg_one_vec.dot(&arg_two_vec);
+        if (!dot.is_finite()) {
+            return Result::Err(FetishError::NonFiniteValue { name : self.get_name() });
+        }
+        let result = TermReference::VecRef(self.scalar_type, Array::from_elem((1,), R32::new(dot)));
+        Result::Ok((result, NewlyEvaluatedTerms::new()))
+    }
+}
+
+///Implementation of a "combine two vectors elementwise by this binary operator" [`FuncImpl`]
+///for the given [`TypeId`]s of the binary scalar operator, the scalar type, and the vector type.
+#[derive(Clone)]
+pub struct ZipWithImpl {
+    pub binary_scalar_func_type : TypeId,
+    pub scalar_type : TypeId,
+    pub vector_type : TypeId
+}
+
+impl HasFuncSignature for ZipWithImpl {
+    fn get_name(&self) -> String {
+        String::from("zipWith")
+    }
+    fn required_arg_types(&self) -> Vec<TypeId> {
+        vec![self.binary_scalar_func_type, self.vector_type, self.vector_type]
+    }
+    fn ret_type(&self) -> TypeId {
+        self.vector_type
+    }
+}
+
+impl FuncImpl for ZipWithImpl {
+    fn evaluate(&self, state : &mut InterpreterState, args : Vec<TermReference>)
+               -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+        let func_ptr = get_func_arg(self, &args, 0)?;
+        let arg_one_vec = get_vec_arg(self, &args, 1)?;
+        let arg_two_vec = get_vec_arg(self, &args, 2)?;
+        let mut newly_evaluated_terms = NewlyEvaluatedTerms::new();
+        let mut result : Array1<R32> = Array::from_elem((arg_one_vec.len(),), R32::new(0.0));
+        for i in 0..arg_one_vec.len() {
+            result[[i,]] = apply_binary_scalar_func(state, func_ptr, self.scalar_type, arg_one_vec[[i,]],
+                                                    arg_two_vec[[i,]], &mut newly_evaluated_terms)?;
+        }
+        let result_ref = TermReference::VecRef(self.vector_type, result);
+        Result::Ok((result_ref, newly_evaluated_terms))
+    }
+}
+
+///Implementation of a "prefix scan of this vector by this binary operator" [`FuncImpl`]
+///for the given [`TypeId`]s of the binary scalar operator, the scalar type, and the vector type.
+///This is like [`ReduceImpl`], except that every intermediate accumulated value is kept,
+///so that the last element of the result is the reduction of the whole vector.
+#[derive(Clone)]
+pub struct ScanImpl {
+    pub binary_scalar_func_type : TypeId,
+    pub scalar_type : TypeId,
+    pub vector_type : TypeId
+}
+
+impl HasFuncSignature for ScanImpl {
+    fn get_name(&self) -> String {
+        String::from("scan")
+    }
+    fn required_arg_types(&self) -> Vec<TypeId> {
+        vec![self.binary_scalar_func_type, self.scalar_type, self.vector_type]
+    }
+    fn ret_type(&self) -> TypeId {
+        self.vector_type
+    }
+}
+
+impl FuncImpl for ScanImpl {
+    fn evaluate(&self, state : &mut InterpreterState, args : Vec<TermReference>)
+               -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+        let func_ptr = get_func_arg(self, &args, 0)?;
+        let mut accum : R32 = get_vec_arg(self, &args, 1)?[[0,]];
+        let arg_vec = get_vec_arg(self, &args, 2)?;
+        let mut newly_evaluated_terms = NewlyEvaluatedTerms::new();
+        let mut result : Array1<R32> = Array::from_elem((arg_vec.len(),), R32::new(0.0));
+        for i in 0..arg_vec.len() {
+            accum = apply_binary_scalar_func(state, func_ptr, self.scalar_type, arg_vec[[i,]],
+                                             accum, &mut newly_evaluated_terms)?;
+            result[[i,]] = accum;
+        }
+        let result_ref = TermReference::VecRef(self.vector_type, result);
+        Result::Ok((result_ref, newly_evaluated_terms))
+    }
+}
+
+///Implementation of a "get the element at the given index" [`FuncImpl`] for the given
+///scalar and vector types. The index is rounded to the nearest integer, and wraps
+///around the end of the vector.
+#[derive(Clone)]
+pub struct IndexImpl {
+    pub scalar_type : TypeId,
+    pub vector_type : TypeId
+}
+
+impl HasFuncSignature for IndexImpl {
+    fn get_name(&self) -> String {
+        String::from("index")
+    }
+    fn required_arg_types(&self) -> Vec<TypeId> {
+        vec![self.scalar_type, self.vector_type]
+    }
+    fn ret_type(&self) -> TypeId {
+        self.scalar_type
+    }
+}
+
+impl FuncImpl for IndexImpl {
+    fn evaluate(&self, _state : &mut InterpreterState, args : Vec<TermReference>)
+               -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+        let arg_vec = get_vec_arg(self, &args, 1)?;
+        let index = get_offset_arg(self, &args, 0, arg_vec.len())?;
+        let result = TermReference::VecRef(self.scalar_type, Array::from_elem((1,), arg_vec[[index,]]));
+        Result::Ok((result, NewlyEvaluatedTerms::new()))
+    }
+}
+
+///Implementation of a "rotate left by the given number of indices" [`FuncImpl`] for the
+///given scalar and vector types, generalizing [`RotateImpl`]. The number of indices is
+///rounded to the nearest integer, and may be negative to rotate right.
+#[derive(Clone)]
+pub struct RotateByImpl {
+    pub scalar_type : TypeId,
+    pub vector_type : TypeId
+}
+
+impl HasFuncSignature for RotateByImpl {
+    fn get_name(&self) -> String {
+        String::from("rotateBy")
+    }
+    fn required_arg_types(&self) -> Vec<TypeId> {
+        vec![self.scalar_type, self.vector_type]
+    }
+    fn ret_type(&self) -> TypeId {
+        self.vector_type
+    }
+}
+
+impl FuncImpl for RotateByImpl {
+    fn evaluate(&self, _state : &mut InterpreterState, args : Vec<TermReference>)
+               -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+        let arg_vec = get_vec_arg(self, &args, 1)?;
+        let n = arg_vec.len();
+        let offset = get_offset_arg(self, &args, 0, n)?;
+        let result_vec : Array1<R32> = (0..n).map(|i| arg_vec[[(offset + i) % n,]]).collect();
+        let result = TermReference::VecRef(self.vector_type, result_vec);
+        Result::Ok((result, NewlyEvaluatedTerms::new()))
+    }
+}
+
+///Implementation of a "choose the first branch if the condition is positive, and the second
+///one otherwise" [`FuncImpl`] for the given scalar condition type and branch type, which
+///may be any type, including function types.
+#[derive(Clone)]
+pub struct IfPositiveImpl {
+    pub scalar_type : TypeId,
+    pub ret_type : TypeId
+}
+
+impl HasFuncSignature for IfPositiveImpl {
+    fn get_name(&self) -> String {
+        String::from("ifPositive")
+    }
+    fn required_arg_types(&self) -> Vec<TypeId> {
+        vec![self.scalar_type, self.ret_type, self.ret_type]
+    }
+    fn ret_type(&self) -> TypeId {
+        self.ret_type
+    }
+}
+
+impl FuncImpl for IfPositiveImpl {
+    fn evaluate(&self, _state : &mut InterpreterState, args : Vec<TermReference>)
+               -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+        let condition : R32 = get_vec_arg(self, &args, 0)?[[0,]];
+        let branch_index = if (condition > 0.0f32) { 1 } else { 2 };
+        let result = get_arg(self, &args, branch_index)?.clone();
+        Result::Ok((result, NewlyEvaluatedTerms::new()))
+    }
+}
+
+///Implementation of the tupling function for the given component types,
+///which is of type `fst -> snd -> (fst, snd)`.
+#[derive(Clone)]
+pub struct PairImpl {
+    pub fst_type : TypeId,
+    pub snd_type : TypeId,
+    pub product_type : TypeId
+}
+
+impl PairImpl {
+    ///Given a [`TypeInfoDirectory`] and the component types, yields a [`PairImpl`],
+    ///or an error if the product type `(fst, snd)` isn't registered.
+    pub fn new(type_info_directory : &TypeInfoDirectory,
+               fst_type : TypeId, snd_type : TypeId) -> Result<PairImpl, FetishError> {
+        let product_type = type_info_directory.try_get_product_type_id(&[fst_type, snd_type])?;
+        Result::Ok(PairImpl {
+            fst_type,
+            snd_type,
+            product_type
+        })
+    }
+}
+
+impl HasFuncSignature for PairImpl {
+    fn get_name(&self) -> String {
+        String::from("pair")
+    }
+    fn required_arg_types(&self) -> Vec<TypeId> {
+        vec![self.fst_type, self.snd_type]
+    }
+    fn ret_type(&self) -> TypeId {
+        self.product_type
+    }
+}
+impl FuncImpl for PairImpl {
+    fn evaluate(&self, _state : &mut InterpreterState, args : Vec::<TermReference>)
+               -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+        let fst_ref = get_arg(self, &args, 0)?.clone();
+        let snd_ref = get_arg(self, &args, 1)?.clone();
+        let result = TermReference::ProductRef(self.product_type, vec![fst_ref, snd_ref]);
+        Result::Ok((result, NewlyEvaluatedTerms::new()))
+    }
+}
+
+///Implementation of the first projection out of the given binary product type,
+///which is of type `(fst, snd) -> fst`.
+#[derive(Clone)]
+pub struct FstImpl {
+    pub product_type : TypeId,
+    pub fst_type : TypeId
+}
+
+impl FstImpl {
+    ///Given a [`TypeInfoDirectory`] and a binary product type, yields a [`FstImpl`],
+    ///or an error if the type isn't a binary product type.
+    pub fn new(type_info_directory : &TypeInfoDirectory, product_type : TypeId) -> Result<FstImpl, FetishError> {
+        let (fst_type, _) = get_binary_component_type_ids(type_info_directory, product_type)?;
+        Result::Ok(FstImpl {
+            product_type,
+            fst_type
+        })
+    }
+}
+
+impl HasFuncSignature for FstImpl {
+    fn get_name(&self) -> String {
+        String::from("fst")
+    }
+    fn required_arg_types(&self) -> Vec<TypeId> {
+        vec![self.product_type]
+    }
+    fn ret_type(&self) -> TypeId {
+        self.fst_type
+    }
+}
+impl FuncImpl for FstImpl {
+    fn evaluate(&self, _state : &mut InterpreterState, args : Vec::<TermReference>)
+               -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+        let components = get_product_arg(self, &args, 0)?;
+        Result::Ok((components[0].clone(), NewlyEvaluatedTerms::new()))
+    }
+}
+
+///Implementation of the second projection out of the given binary product type,
+///which is of type `(fst, snd) -> snd`.
+#[derive(Clone)]
+pub struct SndImpl {
+    pub product_type : TypeId,
+    pub snd_type : TypeId
+}
+
+impl SndImpl {
+    ///Given a [`TypeInfoDirectory`] and a binary product type, yields a [`SndImpl`],
+    ///or an error if the type isn't a binary product type.
+    pub fn new(type_info_directory : &TypeInfoDirectory, product_type : TypeId) -> Result<SndImpl, FetishError> {
+        let (_, snd_type) = get_binary_component_type_ids(type_info_directory, product_type)?;
+        Result::Ok(SndImpl {
+            product_type,
+            snd_type
+        })
+    }
+}
+
+impl HasFuncSignature for SndImpl {
+    fn get_name(&self) -> String {
+        String::from("snd")
+    }
+    fn required_arg_types(&self) -> Vec<TypeId> {
+        vec![self.product_type]
+    }
+    fn ret_type(&self) -> TypeId {
+        self.snd_type
+    }
+}
+impl FuncImpl for SndImpl {
+    fn evaluate(&self, _state : &mut InterpreterState, args : Vec::<TermReference>)
+               -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+        let components = get_product_arg(self, &args, 0)?;
+        Result::Ok((components[1].clone(), NewlyEvaluatedTerms::new()))
+    }
+}
+
+//Applies the function that the given reference points to to the given argument,
+//for combinators whose intermediate results are functions
+fn apply_func_ref(state : &mut InterpreterState, func_ref : TermReference,
+                  arg_ref : TermReference, newly_evaluated_terms : &mut NewlyEvaluatedTerms)
+                 -> Result<TermReference, FetishError> {
+    let func_ptr = match (func_ref) {
+        TermReference::FuncRef(func_ptr) => func_ptr,
+        other_ref => return Result::Err(FetishError::NotAFunctionType(other_ref.get_type()))
+    };
+    let term_app = TermApplication {
+        func_ptr,
+        arg_ref
+    };
+    let (result_ref, more_evaluated_terms) = state.evaluate(&term_app)?;
+    newly_evaluated_terms.merge(more_evaluated_terms);
+    Result::Ok(result_ref)
+}
+
+///Implementation of the `S` combinator, `S f g x = f x (g x)`, which is of type
+///`(a -> b -> c) -> (a -> b) -> a -> c` for the given types `a`, `b` and `c`.
+#[derive(Clone)]
+pub struct SImpl {
+    pub in_type : TypeId,
+    pub binary_func_type : TypeId,
+    pub unary_func_type : TypeId,
+    pub ret_type : TypeId
+}
+
+impl SImpl {
+    ///Given a [`TypeInfoDirectory`] and the types `a`, `b` and `c`, yields a [`SImpl`],
+    ///or an error if any of the function types in its signature aren't registered.
+    pub fn new(type_info_directory : &TypeInfoDirectory,
+               in_type : TypeId, middle_type : TypeId, ret_type : TypeId) -> Result<SImpl, FetishError> {
+        let partial_func_type = type_info_directory.try_get_func_type_id(middle_type, ret_type)?;
+        Result::Ok(SImpl {
+            in_type,
+            binary_func_type : type_info_directory.try_get_func_type_id(in_type, partial_func_type)?,
+            unary_func_type : type_info_directory.try_get_func_type_id(in_type, middle_type)?,
+            ret_type
+        })
+    }
+}
+
+impl HasFuncSignature for SImpl {
+    fn get_name(&self) -> String {
+        String::from("S")
+    }
+    fn required_arg_types(&self) -> Vec<TypeId> {
+        vec![self.binary_func_type, self.unary_func_type, self.in_type]
+    }
+    fn ret_type(&self) -> TypeId {
+        self.ret_type
+    }
+}
+impl FuncImpl for SImpl {
+    fn evaluate(&self, state : &mut InterpreterState, args : Vec::<TermReference>)
+               -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+        let binary_func = TermReference::FuncRef(get_func_arg(self, &args, 0)?);
+        let unary_func = TermReference::FuncRef(get_func_arg(self, &args, 1)?);
+        let arg = get_arg(self, &args, 2)?.clone();
+        let mut newly_evaluated_terms = NewlyEvaluatedTerms::new();
+        let partial_ref = apply_func_ref(state, binary_func, arg.clone(), &mut newly_evaluated_terms)?;
+        let middle_ref = apply_func_ref(state, unary_func, arg, &mut newly_evaluated_terms)?;
+        let result_ref = apply_func_ref(state, partial_ref, middle_ref, &mut newly_evaluated_terms)?;
+        Result::Ok((result_ref, newly_evaluated_terms))
+    }
+}
+
+///Implementation of the `I` combinator, `I x = x`, which is of type `a -> a`.
+#[derive(Clone)]
+pub struct IImpl {
+    pub type_id : TypeId
+}
+
+impl HasFuncSignature for IImpl {
+    fn get_name(&self) -> String {
+        String::from("I")
+    }
+    fn required_arg_types(&self) -> Vec<TypeId> {
+        vec![self.type_id]
+    }
+    fn ret_type(&self) -> TypeId {
+        self.type_id
+    }
+}
+impl FuncImpl for IImpl {
+    fn evaluate(&self, _state : &mut InterpreterState, args : Vec::<TermReference>)
+               -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+        let result_ref = get_arg(self, &args, 0)?.clone();
+        Result::Ok((result_ref, NewlyEvaluatedTerms::new()))
+    }
+}
+
+///Implementation of the `C` combinator, `C f y x = f x y`, which is of type
+///`(a -> b -> c) -> b -> a -> c` for the given types `a`, `b` and `c`.
+///Also serves as argument reordering under the conventional functional name `flip`.
+#[derive(Clone)]
+pub struct CImpl {
+    pub name : String,
+    pub in_type : TypeId,
+    pub middle_type : TypeId,
+    pub binary_func_type : TypeId,
+    pub ret_type : TypeId
+}
+
+impl CImpl {
+    ///Given a [`TypeInfoDirectory`] and the types `a`, `b` and `c`, yields a [`CImpl`],
+    ///or an error if any of the function types in its signature aren't registered.
+    pub fn new(type_info_directory : &TypeInfoDirectory,
+               in_type : TypeId, middle_type : TypeId, ret_type : TypeId) -> Result<CImpl, FetishError> {
+        CImpl::named("C", type_info_directory, in_type, middle_type, ret_type)
+    }
+
+    ///Like [`Self::new`], but yields a [`CImpl`] going by the given name.
+    pub fn named(name : &str, type_info_directory : &TypeInfoDirectory,
+                 in_type : TypeId, middle_type : TypeId, ret_type : TypeId) -> Result<CImpl, FetishError> {
+        let partial_func_type = type_info_directory.try_get_func_type_id(middle_type, ret_type)?;
+        Result::Ok(CImpl {
+            name : String::from(name),
+            in_type,
+            middle_type,
+            binary_func_type : type_info_directory.try_get_func_type_id(in_type, partial_func_type)?,
+            ret_type
+        })
+    }
+}
+
+impl HasFuncSignature for CImpl {
+    fn get_name(&self) -> String {
+        self.name.clone()
+    }
+    fn required_arg_types(&self) -> Vec<TypeId> {
+        vec![self.binary_func_type, self.middle_type, self.in_type]
+    }
+    fn ret_type(&self) -> TypeId {
+        self.ret_type
+    }
+}
+impl FuncImpl for CImpl {
+    fn evaluate(&self, state : &mut InterpreterState, args : Vec::<TermReference>)
+               -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+        let binary_func = TermReference::FuncRef(get_func_arg(self, &args, 0)?);
+        let second_arg = get_arg(self, &args, 1)?.clone();
+        let first_arg = get_arg(self, &args, 2)?.clone();
+        let mut newly_evaluated_terms = NewlyEvaluatedTerms::new();
+        let partial_ref = apply_func_ref(state, binary_func, first_arg, &mut newly_evaluated_terms)?;
+        let result_ref = apply_func_ref(state, partial_ref, second_arg, &mut newly_evaluated_terms)?;
+        Result::Ok((result_ref, newly_evaluated_terms))
+    }
+}
+
+///The largest count accepted by [`IterateImpl`], which is the number of applications it performs.
+pub const MAX_ITERATIONS : usize = 1 << 16;
+
+///The largest fuel accepted by [`FixImpl`]. Every unfolding nests evaluations more deeply,
+///so this bounds how deeply a recursive definition may nest, and is kept small enough
+///for even unoptimized builds to stay within a spawned thread's default stack size.
+pub const MAX_UNFOLDINGS : usize = 100;
+
+//Interprets the scalar argument at the given index as a count, rounding it
+//to the nearest integer and treating negative counts as zero, or yields an
+//error if the count exceeds the given maximum
+fn get_count_arg(func : &dyn HasFuncSignature, args : &[TermReference], index : usize, max_count : usize)
+                -> Result<usize, FetishError> {
+    let scalar_vec = get_vec_arg(func, args, index)?;
+    let rounded = scalar_vec[[0,]].raw().round();
+    if (rounded > max_count as f32) {
+        return Result::Err(FetishError::CountTooLarge { name : func.get_name(), max_count });
+    }
+    Result::Ok(if (rounded > 0.0f32) { rounded as usize } else { 0 })
+}
+
+///Implementation of repeated application, `iterate n f x = f (f (... (f x)))` with `n`
+///applications of `f`, which is of type `scalar -> (a -> a) -> a -> a` for the given
+///scalar type and type `a`. The count is rounded to the nearest integer, and
+///negative counts are treated as zero, yielding `x` unchanged. Counts above
+///[`MAX_ITERATIONS`] yield [`FetishError::CountTooLarge`].
+#[derive(Clone)]
+pub struct IterateImpl {
+    pub scalar_type : TypeId,
+    pub func_type : TypeId,
+    pub elem_type : TypeId
+}
+
+impl IterateImpl {
+    ///Given a [`TypeInfoDirectory`], the scalar type and the type `a`, yields an [`IterateImpl`],
+    ///or an error if the function type `a -> a` isn't registered.
+    pub fn new(type_info_directory : &TypeInfoDirectory,
+               scalar_type : TypeId, elem_type : TypeId) -> Result<IterateImpl, FetishError> {
+        Result::Ok(IterateImpl {
+            scalar_type,
+            func_type : type_info_directory.try_get_func_type_id(elem_type, elem_type)?,
+            elem_type
+        })
+    }
+}
+
+impl HasFuncSignature for IterateImpl {
+    fn get_name(&self) -> String {
+        String::from("iterate")
+    }
+    fn required_arg_types(&self) -> Vec<TypeId> {
+        vec![self.scalar_type, self.func_type, self.elem_type]
+    }
+    fn ret_type(&self) -> TypeId {
+        self.elem_type
+    }
+}
+impl FuncImpl for IterateImpl {
+    fn evaluate(&self, state : &mut InterpreterState, args : Vec::<TermReference>)
+               -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+        let count = get_count_arg(self, &args, 0, MAX_ITERATIONS)?;
+        let func_ptr = get_func_arg(self, &args, 1)?;
+        let mut result_ref = get_arg(self, &args, 2)?.clone();
+        let mut newly_evaluated_terms = NewlyEvaluatedTerms::new();
+        for _ in 0..count {
+            result_ref = apply_func_ref(state, TermReference::FuncRef(func_ptr), result_ref,
+                                        &mut newly_evaluated_terms)?;
+        }
+        Result::Ok((result_ref, newly_evaluated_terms))
+    }
+}
+
+///Implementation of a fuel-bounded fixed-point combinator for recursive definitions,
+///`fix n f x = f (fix (n - 1) f) x`, which is of type
+///`scalar -> ((a -> b) -> a -> b) -> a -> b` for the given scalar type and types `a` and `b`.
+///The fuel `n` is the number of times the recursive definition `f` may be unfolded,
+///rounded to the nearest integer, and may be at most [`MAX_UNFOLDINGS`]. A recursion which
+///would need more unfoldings than that yields [`FetishError::OutOfUnfoldings`] instead of running away.
+#[derive(Clone)]
+pub struct FixImpl {
+    pub scalar_type : TypeId,
+    pub in_type : TypeId,
+    pub recursive_func_type : TypeId,
+    pub definition_type : TypeId,
+    pub ret_type : TypeId
+}
+
+impl FixImpl {
+    ///Given a [`TypeInfoDirectory`], the scalar type and the types `a` and `b`, yields a [`FixImpl`],
+    ///or an error if any of the function types in its signature aren't registered.
+    pub fn new(type_info_directory : &TypeInfoDirectory, scalar_type : TypeId,
+               in_type : TypeId, ret_type : TypeId) -> Result<FixImpl, FetishError> {
+        let recursive_func_type = type_info_directory.try_get_func_type_id(in_type, ret_type)?;
+        Result::Ok(FixImpl {
+            scalar_type,
+            in_type,
+            recursive_func_type,
+            definition_type : type_info_directory.try_get_func_type_id(recursive_func_type, recursive_func_type)?,
+            ret_type
+        })
+    }
+}
+
+impl HasFuncSignature for FixImpl {
+    fn get_name(&self) -> String {
+        String::from("fix")
+    }
+    fn required_arg_types(&self) -> Vec<TypeId> {
+        vec![self.scalar_type, self.definition_type, self.in_type]
+    }
+    fn ret_type(&self) -> TypeId {
+        self.ret_type
+    }
+}
+impl FuncImpl for FixImpl {
+    fn evaluate(&self, state : &mut InterpreterState, args : Vec::<TermReference>)
+               -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+        let fuel = get_count_arg(self, &args, 0, MAX_UNFOLDINGS)?;
+        if (fuel == 0) {
+            return Result::Err(FetishError::OutOfUnfoldings { name : self.get_name() });
+        }
+        let definition_ptr = get_func_arg(self, &args, 1)?;
+        let arg_ref = get_arg(self, &args, 2)?.clone();
+
+        //The recursive occurrence is this very primitive, partially applied to
+        //the remaining fuel and the definition
+        let ctxt = state.get_context();
+        let own_type = ctxt.try_get_func_type_id(self.definition_type, self.recursive_func_type)
+                           .and_then(|partial_type| ctxt.try_get_func_type_id(self.scalar_type, partial_type))?;
+        let fix_ptr = find_primitive(state, &self.get_name(), own_type)
+                      .ok_or(FetishError::MissingPrimitiveInstance { name : self.get_name(), type_id : own_type })?;
+        //The fuel is bounded by MAX_UNFOLDINGS, so it converts to a scalar exactly
+        let remaining_fuel = TermReference::VecRef(self.scalar_type, Array::from_elem((1,), R32::new((fuel - 1) as f32)));
+        let (recursive_ptr, mut newly_evaluated_terms) =
+            store_partial_application(state, fix_ptr, vec![remaining_fuel, TermReference::FuncRef(definition_ptr)])?;
+
+        let unfolded_ref = apply_func_ref(state, TermReference::FuncRef(definition_ptr),
+                                          TermReference::FuncRef(recursive_ptr), &mut newly_evaluated_terms)?;
+        let result_ref = apply_func_ref(state, unfolded_ref, arg_ref, &mut newly_evaluated_terms)?;
+        Result::Ok((result_ref, newly_evaluated_terms))
+    }
+}
+
+fn get_binary_component_type_ids(type_info_directory : &TypeInfoDirectory,
+                                 product_type : TypeId) -> Result<(TypeId, TypeId), FetishError> {
+    let components = type_info_directory.try_get_component_type_ids(product_type)?;
+    if (components.len() != 2) {
+        return Result::Err(FetishError::NotAProductType(product_type));
+    }
+    Result::Ok((components[0], components[1]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::*;
+    use crate::context_builder::*;
+    use crate::primitive_template::*;
+    use crate::term_expression::*;
+    use crate::interpreter_and_embedder_state::*;
+    use crate::test_utils::*;
+    use crate::array_utils::*;
+
+    fn term_ref(in_array : Array1<f32>) -> TermReference {
+        let noisy_array = to_noisy(in_array.view());
+        if (in_array.shape()[0] == 1) {
+            TermReference::VecRef(TEST_SCALAR_T, noisy_array)
+        } else {
+            TermReference::VecRef(TEST_VECTOR_T, noisy_array)
+        }
+    }
+
+    #[test]
+    fn test_addition() {
+        let ctxt = get_test_vector_only_context();
+        let mut state = InterpreterState::new(&ctxt);
+        let args = vec![term_ref(array![1.0f32, 2.0f32]), term_ref(array![3.0f32, 4.0f32])];
+
+        let addition_func = BinaryFuncImpl {
+            elem_type : TEST_VECTOR_T,
+            f : Box::new(AddOperator {})
+        };
+
+        let (result, _) = addition_func.evaluate(&mut state, args).unwrap();
+        assert_equal_vector_term(result, array![4.0f32, 6.0f32].view());
+    }
+
+    #[test]
+    fn test_unary_operators() {
+        let ctxt = get_test_vector_only_context();
+        let mut state = InterpreterState::new(&ctxt);
+        let expected = [("negate", array![4.0f32, 0.0f32]), ("abs", array![4.0f32, 0.0f32]),
+                       ("relu", array![0.0f32, 0.0f32]), ("square", array![16.0f32, 0.0f32]),
+                       ("sqrtAbs", array![2.0f32, 0.0f32]), ("sigmoid", array![0.01798621f32, 0.5f32])];
+        for operator in standard_unary_operators() {
+            let name = operator.get_name();
+            let unary_func = UnaryFuncImpl {
+                elem_type : TEST_VECTOR_T,
+                f : operator
+            };
+            let args = vec![term_ref(array![-4.0f32, 0.0f32])];
+            let result = unary_func.evaluate(&mut state, args);
+            match (expected.iter().find(|(expected_name, _)| *expected_name == name)) {
+                Option::Some((_, expected_vec)) => assert_equal_vector_term(result.unwrap().0, expected_vec.view()),
+                Option::None if name == "log1p" || name == "reciprocal" => {
+                    assert!(matches!(result, Result::Err(FetishError::OutsideDomain { .. })));
+                },
+                Option::None => assert!(result.unwrap().0.is_finite())
+            }
+        }
+    }
+
+    #[test]
+    fn test_unary_overflow_is_an_error() {
+        let ctxt = get_test_vector_only_context();
+        let mut state = InterpreterState::new(&ctxt);
+        let exp_func = UnaryFuncImpl {
+            elem_type : TEST_VECTOR_T,
+            f : Box::new(ExpOperator {})
+        };
+        let result = exp_func.evaluate(&mut state, vec![term_ref(array![1000.0f32, 0.0f32])]);
+        assert_eq!(result.err(), Option::Some(FetishError::NonFiniteValue { name : String::from("exp") }));
+    }
+    #[test]
+    fn test_binary_overflow_is_an_error() {
+        let ctxt = get_test_vector_only_context();
+        let mut state = InterpreterState::new(&ctxt);
+        let add_func = BinaryFuncImpl {
+            elem_type : TEST_VECTOR_T,
+            f : Box::new(AddOperator {})
+        };
+        let result = add_func.evaluate(&mut state, vec![term_ref(array![3e38f32, 0.0f32]), term_ref(array![3e38f32, 0.0f32])]);
+        assert_eq!(result.err(), Option::Some(FetishError::NonFiniteValue { name : String::from("+") }));
+    }
+    #[test]
+    fn test_offsets_into_empty_vectors_are_rejected() {
+        let mut builder = get_test_vector_only_context_builder();
+        let empty_t = builder.add_vector_type(0);
+        add_test_vector_features(&mut builder, empty_t, 0);
+        let ctxt = builder.build().unwrap();
+        let mut state = InterpreterState::new(&ctxt);
+        let empty_ref = || TermReference::VecRef(empty_t, Array::from(Vec::<R32>::new()));
+
+        let index_func = IndexImpl {
+            scalar_type : TEST_SCALAR_T,
+            vector_type : empty_t
+        };
+        assert_eq!(index_func.evaluate(&mut state, vec![term_ref(array![1.0f32]), empty_ref()]).err(),
+                   Option::Some(FetishError::EmptyVector { name : String::from("index") }));
+        let rotate_by_func = RotateByImpl {
+            scalar_type : TEST_SCALAR_T,
+            vector_type : empty_t
+        };
+        assert_eq!(rotate_by_func.evaluate(&mut state, vec![term_ref(array![1.0f32]), empty_ref()]).err(),
+                   Option::Some(FetishError::EmptyVector { name : String::from("rotateBy") }));
+    }
+
+    #[test]
+    fn test_structural_vector_impls() {
+        let ctxt = get_test_vector_only_context();
+        let mut state = InterpreterState::new(&ctxt);
+        let pair = || term_ref(array![5.0f32, 10.0f32]);
+
+        let concat_func = ConcatImpl {
+            first_type : TEST_SCALAR_T,
+            second_type : TEST_SCALAR_T,
+            vector_type : TEST_VECTOR_T
+        };
+        let (result, _) = concat_func.evaluate(&mut state, vec![term_ref(array![1.0f32]), term_ref(array![2.0f32])]).unwrap();
+        assert_equal_vector_term(result, array![1.0f32, 2.0f32].view());
+
+        let slice_func = SliceImpl {
+            vector_type : TEST_VECTOR_T,
+            slice_type : TEST_SCALAR_T,
+            start : 1
+        };
+        assert_eq!(slice_func.get_name(), "slice1");
+        let (result, _) = slice_func.evaluate(&mut state, vec![pair()]).unwrap();
+        assert_equal_vector_term(result, array![10.0f32].view());
+
+        let out_of_bounds_slice_func = SliceImpl {
+            vector_type : TEST_VECTOR_T,
+            slice_type : TEST_SCALAR_T,
+            start : 2
+        };
+        assert!(out_of_bounds_slice_func.evaluate(&mut state, vec![pair()]).is_err());
+
+        let reverse_func = ReverseImpl {
+            vector_type : TEST_VECTOR_T
+        };
+        let (result, _) = reverse_func.evaluate(&mut state, vec![pair()]).unwrap();
+        assert_equal_vector_term(result, array![10.0f32, 5.0f32].view());
+
+        let dot_func = DotImpl {
+            vector_type : TEST_VECTOR_T,
+            scalar_type : TEST_SCALAR_T
+        };
+        let (result, _) = dot_func.evaluate(&mut state, vec![pair(), term_ref(array![1.0f32, 2.0f32])]).unwrap();
+        assert_equal_vector_term(result, array![25.0f32].view());
+
+        let index_func = IndexImpl {
+            scalar_type : TEST_SCALAR_T,
+            vector_type : TEST_VECTOR_T
+        };
+        let (result, _) = index_func.evaluate(&mut state, vec![term_ref(array![-1.2f32]), pair()]).unwrap();
+        assert_equal_vector_term(result, array![10.0f32].view());
+
+        let rotate_by_func = RotateByImpl {
+            scalar_type : TEST_SCALAR_T,
+            vector_type : TEST_VECTOR_T
+        };
+        let (result, _) = rotate_by_func.evaluate(&mut state, vec![term_ref(array![2.0f32]), pair()]).unwrap();
+        assert_equal_vector_term(result, array![5.0f32, 10.0f32].view());
+        let (result, _) = rotate_by_func.evaluate(&mut state, vec![term_ref(array![1.0f32]), pair()]).unwrap();
+        assert_equal_vector_term(result, array![10.0f32, 5.0f32].view());
+    }
+
+    #[test]
+    fn test_zip_with_and_scan_record_nested_evaluations() {
+        let mut builder = get_test_template_context_builder(&["(Scalar -> Scalar -> Scalar) -> Vec2 -> Vec2 -> Vec2",
+                                                              "(Scalar -> Scalar -> Scalar) -> Scalar -> Vec2 -> Vec2"],
+                                                            vec![Box::new(ZipWithTemplate {}), Box::new(ScanTemplate {})]);
+        builder.add_binary_func(TEST_SCALAR_T, Box::new(AddOperator {}));
+        builder.add_binary_func(TEST_SCALAR_T, Box::new(MulOperator {}));
+        let ctxt = builder.build().unwrap();
+        let mut state = InterpreterState::new(&ctxt);
+
+        let (result, newly_evaluated_terms) = TermExpression::parse("zipWith * [1, 2] [3, 4]").unwrap()
+                                                                .evaluate(&mut state).unwrap();
+        assert_equal_vector_term(result, array![3.0f32, 8.0f32].view());
+        //zipWith *, zipWith * [1, 2], * 1 and * 2 are new
+        assert_eq!(newly_evaluated_terms.terms.len(), 4);
+
+        let (result, newly_evaluated_terms) = TermExpression::parse("scan + 1 [1, 2]").unwrap()
+                                                                .evaluate(&mut state).unwrap();
+        assert_equal_vector_term(result, array![2.0f32, 4.0f32].view());
+        assert!(newly_evaluated_terms.term_app_results.len() > 3);
+    }
+
+    #[test]
+    fn test_if_positive_selects_any_branch_type() {
+        let ctxt = get_test_template_context(&["Scalar -> Vec2 -> Vec2 -> Vec2",
+                                               "Scalar -> (Vec2 -> Vec2) -> (Vec2 -> Vec2) -> Vec2 -> Vec2"],
+                                             vec![Box::new(IfPositiveTemplate {}), Box::new(RotateTemplate {}),
+                                                  Box::new(ITemplate {})]);
+        let mut state = InterpreterAndEmbedderState::new(&ctxt);
+
+        let rotate_ref = state.evaluate_expression("(rotate : Vec2 -> Vec2)").unwrap();
+        assert!(state.evaluate_expression("ifPositive 1 rotate I").unwrap() == rotate_ref);
+        assert_equal_vector_term(state.evaluate_expression("ifPositive 0 rotate I [1, 2]").unwrap(),
+                                 array![1.0f32, 2.0f32].view());
+        assert_equal_vector_term(state.evaluate_expression("ifPositive 0.5 [1, 2] [3, 4]").unwrap(),
+                                 array![1.0f32, 2.0f32].view());
+
+        state.bayesian_update_step();
+        let if_positive_ptrs = ctxt.primitive_directory.find_primitives("ifPositive");
+        assert_eq!(if_positive_ptrs.len(), 2);
+        for primitive_ptr in if_positive_ptrs {
+            assert!(state.embedder_state.has_embedding(TermPointer::from(primitive_ptr)));
+        }
+    }
+
+    fn get_higher_order_context_builder(type_texts : &[&str]) -> ContextBuilder {
+        let all_type_texts : Vec<&str> = ["Vec2 -> Vec2 -> Vec2", "Scalar -> Scalar"].iter().chain(type_texts.iter())
+                                                                                   .copied().collect();
+        let templates = vec![Box::new(RotateTemplate {}) as Box<dyn PrimitiveTemplate>].into_iter()
+                            .chain(combinator_templates()).chain(higher_order_templates()).collect();
+        let mut builder = get_test_template_context_builder(&all_type_texts, templates);
+        builder.add_binary_func(TEST_VECTOR_T, Box::new(SubOperator {}));
+        builder
+    }
+
+    #[test]
+    fn test_flip_and_iterate() {
+        let ctxt = get_higher_order_context_builder(&["(Vec2 -> Vec2 -> Vec2) -> Vec2 -> Vec2 -> Vec2",
+                                                      "Scalar -> (Vec2 -> Vec2) -> Vec2 -> Vec2"]).build().unwrap();
+        let mut state = InterpreterAndEmbedderState::new(&ctxt);
+        assert_equal_vector_term(state.evaluate_expression("flip - [1, 2] [3, 4]").unwrap(),
+                                 array![2.0f32, 2.0f32].view());
+        assert_equal_vector_term(state.evaluate_expression("iterate 3 rotate [1, 2]").unwrap(),
+                                 array![2.0f32, 1.0f32].view());
+        assert_equal_vector_term(state.evaluate_expression("iterate -1 rotate [1, 2]").unwrap(),
+                                 array![1.0f32, 2.0f32].view());
+        assert_eq!(state.evaluate_expression("iterate 1e30 rotate [1, 2]").err(),
+                   Option::Some(FetishError::CountTooLarge { name : String::from("iterate"), max_count : MAX_ITERATIONS }));
+
+        //Every intermediate application is reported, and recorded in the application table
+        let (result, newly_evaluated_terms) = TermExpression::parse("iterate 2.6 rotate [1, 5]").unwrap()
+                                                             .evaluate(&mut state.interpreter_state).unwrap();
+        assert_equal_vector_term(result, array![5.0f32, 1.0f32].view());
+        let rotate_ptr = TermPointer::from(ctxt.primitive_directory.find_primitives("rotate")[0]);
+        let newly_rotated = newly_evaluated_terms.term_app_results.iter()
+                                                 .filter(|app_result| app_result.term_app.func_ptr == rotate_ptr)
+                                                 .count();
+        assert_eq!(newly_rotated, 3);
+        assert_eq!(state.interpreter_state.get_app_results_with_func(rotate_ptr).len(), 6);
+    }
+
+    //Recursive definition which counts down to zero, `countDown rec x = if x > 0 then 1 + rec (x - 1) else 0`
+    #[derive(Clone)]
+    struct CountDownImpl {
+        scalar_func_type : TypeId
+    }
+
+    impl HasFuncSignature for CountDownImpl {
+        fn get_name(&self) -> String {
+            String::from("countDown")
+        }
+        fn required_arg_types(&self) -> Vec<TypeId> {
+            vec![self.scalar_func_type, TEST_SCALAR_T]
+        }
+        fn ret_type(&self) -> TypeId {
+            TEST_SCALAR_T
+        }
+    }
+    impl FuncImpl for CountDownImpl {
+        fn evaluate(&self, state : &mut InterpreterState, args : Vec<TermReference>)
+                   -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+            let x = get_vec_arg(self, &args, 1)?[[0,]];
+            let mut newly_evaluated_terms = NewlyEvaluatedTerms::new();
+            let mut count = 0.0f32;
+            if (x > 0.0f32) {
+                let rec_ref = TermReference::FuncRef(get_func_arg(self, &args, 0)?);
+                let decremented = TermReference::VecRef(TEST_SCALAR_T, Array::from_elem((1,), x - 1.0f32));
+                match (apply_func_ref(state, rec_ref, decremented, &mut newly_evaluated_terms)?) {
+                    TermReference::VecRef(_, result_vec) => count = result_vec[[0,]].raw() + 1.0f32,
+                    other_ref => return Result::Err(FetishError::NotAVectorType(other_ref.get_type()))
+                }
+            }
+            Result::Ok((TermReference::VecRef(TEST_SCALAR_T, Array::from_elem((1,), R32::new(count))),
+                        newly_evaluated_terms))
+        }
+    }
+
+    #[test]
+    fn test_fix_unfolds_recursive_definitions_up_to_fuel() {
+        let mut builder = get_higher_order_context_builder(&["Scalar -> ((Scalar -> Scalar) -> Scalar -> Scalar) -> Scalar -> Scalar"]);
+        let scalar_func_type = builder.parse_type("Scalar -> Scalar").unwrap();
+        builder.add_primitive(Box::new(CountDownImpl { scalar_func_type }));
+        let ctxt = builder.build().unwrap();
+        let mut state = InterpreterAndEmbedderState::new(&ctxt);
+
+        //Counting down from three needs four unfoldings, the last of which doesn't recurse
+        assert_equal_vector_term(state.evaluate_expression("fix 4 countDown 3").unwrap(), array![3.0f32].view());
+        let count_down_ptr = TermPointer::from(ctxt.primitive_directory.find_primitives("countDown")[0]);
+        assert_eq!(state.interpreter_state.get_app_results_with_func(count_down_ptr).len(), 4);
+
+        assert_eq!(state.evaluate_expression("fix 3 countDown 3").err(),
+                   Option::Some(FetishError::OutOfUnfoldings { name : String::from("fix") }));
+        //Runaway recursion is stopped even with the most fuel accepted
+        assert_eq!(state.evaluate_expression(&format!("fix {} I 1", MAX_UNFOLDINGS)).err(),
+                   Option::Some(FetishError::OutOfUnfoldings { name : String::from("fix") }));
+        assert_eq!(state.evaluate_expression("fix 1e8 I 1").err(),
+                   Option::Some(FetishError::CountTooLarge { name : String::from("fix"), max_count : MAX_UNFOLDINGS }));
+    }
+
+    #[test]
+    fn test_rotate() {
+        let ctxt = get_test_vector_only_context();
+        let mut state = InterpreterState::new(&ctxt);
+        let args = vec![term_ref(array![5.0f32, 10.0f32])];
+
+        let rotate_func = RotateImpl {
+            vector_type : TEST_VECTOR_T
+        };
+
+        let (result, _) = rotate_func.evaluate(&mut state, args).unwrap();
+        assert_equal_vector_term(result, array![10.0f32, 5.0f32].view());
+    }
+
     #[test]
     fn test_set_head() {
         let ctxt = get_test_vector_only_context();
@@ -581,7 +2080,7 @@ mod tests {
             scalar_type : TEST_SCALAR_T
         };
 
-        let (result, _) = set_head_func.evaluate(&mut state, args);
+        let (result, _) = set_head_func.evaluate(&mut state, args).unwrap();
         assert_equal_vector_term(result, array![9.0f32, 2.0f32].view());
     }
 
@@ -597,7 +2096,7 @@ mod tests {
             scalar_type : TEST_SCALAR_T
         };
         
-        let (result, _) = head_func.evaluate(&mut state, args);
+        let (result, _) = head_func.evaluate(&mut state, args).unwrap();
         assert_equal_vector_term(result, array![1.0f32].view());
     }
 
@@ -612,7 +2111,7 @@ mod tests {
             scalar_type : TEST_SCALAR_T
         };
 
-        let (result, _) = fill_func.evaluate(&mut state, args);
+        let (result, _) = fill_func.evaluate(&mut state, args).unwrap();
         assert_equal_vector_term(result, array![3.0f32, 3.0f32].view());
     }
     
@@ -623,13 +2122,96 @@ mod tests {
 
         let args = vec![term_ref(array![1.0f32, 2.0f32]), term_ref(array![3.0f32])];
 
-        let const_func = ConstImpl {
-            ret_type : TEST_VECTOR_T,
-            ignored_type : TEST_SCALAR_T
-        };
+        let const_func = ConstImpl::new(TEST_VECTOR_T, TEST_SCALAR_T);
 
-        let (result, _) = const_func.evaluate(&mut state, args);
+        let (result, _) = const_func.evaluate(&mut state, args).unwrap();
         assert_equal_vector_term(result, array![1.0f32, 2.0f32].view());
     }
 
+    #[test]
+    fn test_missing_argument_is_reported() {
+        let ctxt = get_test_vector_only_context();
+        let mut state = InterpreterState::new(&ctxt);
+        let args = vec![term_ref(array![1.0f32, 2.0f32])];
+
+        let addition_func = BinaryFuncImpl {
+            elem_type : TEST_VECTOR_T,
+            f : Box::new(AddOperator {})
+        };
+
+        let result = addition_func.evaluate(&mut state, args);
+        assert_eq!(result.err(), Option::Some(FetishError::WrongArgumentCount {
+            name : String::from("+"),
+            expected : 2,
+            actual : 1
+        }));
+    }
+
+    #[test]
+    fn test_pair_and_projections() {
+        let (ctxt, product_t) = get_test_product_context();
+        let mut state = InterpreterState::new(&ctxt);
+        let args = vec![term_ref(array![1.0f32, 2.0f32]), term_ref(array![3.0f32])];
+
+        let pair_func = PairImpl::new(&ctxt.type_info_directory, TEST_VECTOR_T, TEST_SCALAR_T).unwrap();
+        let (pair, _) = pair_func.evaluate(&mut state, args).unwrap();
+        assert_eq!(pair.get_type(), product_t);
+        pair.check_type(product_t, &ctxt).unwrap();
+
+        let fst_func = FstImpl::new(&ctxt.type_info_directory, product_t).unwrap();
+        let (fst, _) = fst_func.evaluate(&mut state, vec![pair.clone()]).unwrap();
+        assert_equal_vector_term(fst, array![1.0f32, 2.0f32].view());
+
+        let snd_func = SndImpl::new(&ctxt.type_info_directory, product_t).unwrap();
+        let (snd, _) = snd_func.evaluate(&mut state, vec![pair]).unwrap();
+        assert_equal_vector_term(snd, array![3.0f32].view());
+
+        assert_eq!(FstImpl::new(&ctxt.type_info_directory, TEST_VECTOR_T).err(),
+                   Option::Some(FetishError::NotAProductType(TEST_VECTOR_T)));
+    }
+
+    fn get_normalization_context() -> Context {
+        get_test_template_context(&["(Vec2 -> Vec2) -> (Vec2 -> Vec2) -> Vec2 -> Vec2", "Vec2 -> Vec2 -> Vec2",
+                                    "(Scalar -> Scalar) -> Vec2 -> Vec2"],
+                                  vec![Box::new(RotateTemplate {}), Box::new(ComposeTemplate {}), Box::new(ConstTemplate {}),
+                                       Box::new(MapTemplate {}), Box::new(ITemplate {})])
+    }
+
+    #[test]
+    fn identities_are_dropped_from_compositions() {
+        let ctxt = get_normalization_context();
+        let mut state = InterpreterState::new(&ctxt);
+        let rotate = evaluate_test_func(&mut state, "Vec2 -> Vec2", "rotate");
+        assert!(evaluate_test_func(&mut state, "Vec2 -> Vec2", "compose rotate I") == rotate);
+        assert!(evaluate_test_func(&mut state, "Vec2 -> Vec2", "compose I rotate") == rotate);
+    }
+
+    #[test]
+    fn compositions_are_reassociated() {
+        let ctxt = get_normalization_context();
+        let mut state = InterpreterState::new(&ctxt);
+        let left = evaluate_test_func(&mut state, "Vec2 -> Vec2", "compose (compose rotate rotate) rotate");
+        let num_terms = state.type_spaces.get(&left.type_id).unwrap().get_num_terms();
+        let right = evaluate_test_func(&mut state, "Vec2 -> Vec2", "compose rotate (compose rotate rotate)");
+        assert!(left == right);
+        assert_eq!(state.type_spaces.get(&left.type_id).unwrap().get_num_terms(), num_terms);
+    }
+
+    #[test]
+    fn constant_inner_functions_are_folded() {
+        let ctxt = get_normalization_context();
+        let mut state = InterpreterState::new(&ctxt);
+        let composed = evaluate_test_func(&mut state, "Vec2 -> Vec2", "compose rotate (const [1, 2])");
+        let folded = evaluate_test_func(&mut state, "Vec2 -> Vec2", "const [2, 1]");
+        assert!(composed == folded);
+    }
+
+    #[test]
+    fn mapped_identities_are_identities() {
+        let ctxt = get_normalization_context();
+        let mut state = InterpreterState::new(&ctxt);
+        let mapped = evaluate_test_func(&mut state, "Vec2 -> Vec2", "map I");
+        let identity = evaluate_test_func(&mut state, "Vec2 -> Vec2", "I");
+        assert!(mapped == identity);
+    }
 }
@@ -19,6 +19,10 @@ use crate::term_application::*;
 use crate::embedder_state::*;
 use crate::interpreter_state::*;
 use crate::newly_evaluated_terms::*;
+use crate::fetish_error::*;
+use crate::term_expression::*;
+use crate::term_enumerator::*;
+use crate::equivalence_tester::*;
 
 use crate::term_application_result::*;
 use serde::{Serialize, Deserialize};
@@ -63,22 +67,59 @@ impl<'a> InterpreterAndEmbedderState<'a> {
     pub fn get_context(&self) -> &Context {
         self.interpreter_state.get_context()
     }
-    ///Given a [`TermApplication`], uses the wrapped [`InterpreterState`] to evaluate
-    ///the application, returning a `TermReference` for the result of the evaluation.
-    ///Any newly-evaluated terms which result from evaluation will be added to
-    ///this [`InterpreterAndEmbedderState`]'s `newly_evaluated_terms` member variable.
-    pub fn evaluate(&mut self, term_app : &TermApplication) -> TermReference {
-        let (result_ref, newly_evaluated_terms) = self.interpreter_state.evaluate(term_app);
+    ///Given a [`TermApplication`], uses the wrapped [`InterpreterState`] to type-check and
+    ///evaluate the application (see [`InterpreterState::apply`]), returning a `TermReference`
+    ///for the result of the evaluation. Any newly-evaluated terms which result from evaluation
+    ///will be added to this [`InterpreterAndEmbedderState`]'s `newly_evaluated_terms` member variable.
+    ///If type-checking or evaluation fails, the [`FetishError`] is passed along.
+    pub fn evaluate(&mut self, term_app : &TermApplication) -> Result<TermReference, FetishError> {
+        self.apply(term_app.func_ptr, term_app.arg_ref.clone())
+    }
+    ///Like [`Self::evaluate`], but takes the function and argument of the application separately.
+    pub fn apply(&mut self, func_ptr : TermPointer, arg_ref : TermReference) -> Result<TermReference, FetishError> {
+        let (result_ref, newly_evaluated_terms) = self.interpreter_state.apply(func_ptr, arg_ref)?;
+        self.newly_evaluated_terms.merge(newly_evaluated_terms);
+        Result::Ok(result_ref)
+    }
+    ///Parses the given text as a [`TermExpression`], and evaluates it against the wrapped
+    ///[`InterpreterState`] (see [`TermExpression::evaluate`]), adding any newly-evaluated
+    ///terms to the wrapped [`NewlyEvaluatedTerms`].
+    pub fn evaluate_expression(&mut self, text : &str) -> Result<TermReference, FetishError> {
+        let expr = TermExpression::parse(text)?;
+        let (result_ref, newly_evaluated_terms) = expr.evaluate(&mut self.interpreter_state)?;
         self.newly_evaluated_terms.merge(newly_evaluated_terms);
-        result_ref
+        Result::Ok(result_ref)
     }
     ///Convenience method to force the wrapped [`InterpreterState`] to have at least
     ///one term inhabiting every type, assuming that it doesn't really matter what these are.
     ///Calling this method will result in every newly-added term being added to the
     ///wrapped [`NewlyEvaluatedTerms`]
-    pub fn ensure_every_type_has_a_term_on_init(&mut self) {
-        let newly_evaluated_terms = self.interpreter_state.ensure_every_type_has_a_term_on_init();
+    pub fn ensure_every_type_has_a_term_on_init(&mut self) -> Result<(), FetishError> {
+        let newly_evaluated_terms = self.interpreter_state.ensure_every_type_has_a_term_on_init()?;
+        self.newly_evaluated_terms.merge(newly_evaluated_terms);
+        Result::Ok(())
+    }
+    ///Enumerates terms against the wrapped [`InterpreterState`] using the given [`TermEnumerator`]
+    ///(see [`TermEnumerator::enumerate`]), adding any newly-evaluated terms to the wrapped
+    ///[`NewlyEvaluatedTerms`], so that they may be used to seed the wrapped [`EmbedderState`].
+    pub fn enumerate_terms(&mut self, enumerator : &TermEnumerator) -> Vec<EnumeratedTerm> {
+        let (enumerated, newly_evaluated_terms) = enumerator.enumerate(&mut self.interpreter_state);
+        self.newly_evaluated_terms.merge(newly_evaluated_terms);
+        enumerated
+    }
+    ///Uses the given [`EquivalenceTester`] to determine whether the function terms which the
+    ///given [`TermPointer`]s point to behave the same, and if so, merges the embedding of
+    ///`merged` into that of `kept` (see [`EmbedderState::merge_embeddings`]). Any newly-evaluated
+    ///terms from probing are added to the wrapped [`NewlyEvaluatedTerms`]. Yields whether the
+    ///terms were found to be equivalent.
+    pub fn merge_if_equivalent<R : Rng + ?Sized>(&mut self, tester : &EquivalenceTester, kept : TermPointer,
+                                                 merged : TermPointer, rng : &mut R) -> Result<bool, FetishError> {
+        let (equivalent, newly_evaluated_terms) = tester.are_equivalent(&mut self.interpreter_state, kept, merged, rng)?;
         self.newly_evaluated_terms.merge(newly_evaluated_terms);
+        if (equivalent) {
+            self.embedder_state.merge_embeddings(kept, merged)?;
+        }
+        Result::Ok(equivalent)
     }
     ///Uses the wrapped [`NewlyEvaluatedTerms`] and [`InterpreterState`] to update the embeddings
     ///within the wrapped [`EmbedderState`]. Calling this method will not modfiy the wrapped
@@ -106,3 +147,43 @@ impl<'a> InterpreterAndEmbedderState<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+    use crate::term_index::*;
+
+    #[test]
+    fn product_arguments_support_embedding_updates() {
+        let (ctxt, product_t) = get_test_product_context();
+        let mut state = InterpreterAndEmbedderState::new(&ctxt);
+        state.ensure_every_type_has_a_term_on_init().unwrap();
+
+        let pair_ptr = TermPointer {
+            type_id : ctxt.parse_type("Vec2 -> Scalar -> (Vec2, Scalar)").unwrap(),
+            index : TermIndex::Primitive(0)
+        };
+        let snd_ptr = TermPointer {
+            type_id : ctxt.parse_type("(Vec2, Scalar) -> Scalar").unwrap(),
+            index : TermIndex::Primitive(0)
+        };
+        let vec_arg = TermReference::VecRef(TEST_VECTOR_T, to_noisy(array![1.0f32, 2.0f32].view()));
+        let scalar_arg = TermReference::VecRef(TEST_SCALAR_T, to_noisy(array![3.0f32].view()));
+        let curried_ref = state.apply(pair_ptr, vec_arg).unwrap();
+        let curried_ptr = match (curried_ref) {
+            TermReference::FuncRef(curried_ptr) => curried_ptr,
+            _ => panic!()
+        };
+        let pair_ref = state.apply(curried_ptr, scalar_arg).unwrap();
+        assert_eq!(pair_ref.get_type(), product_t);
+
+        let snd_ref = state.apply(snd_ptr, pair_ref).unwrap();
+        assert_equal_vector_term(snd_ref, array![3.0f32].view());
+
+        let mean_before = state.embedder_state.get_embedding(snd_ptr).get_mean_as_vec().to_owned();
+        state.bayesian_update_step();
+        let mean_after = state.embedder_state.get_embedding(snd_ptr).get_mean_as_vec().to_owned();
+        assert_ne!(mean_before, mean_after);
+    }
+}
@@ -18,17 +18,43 @@ use crate::term_application::*;
 use crate::term_application_result::*;
 use crate::primitive_term_pointer::*;
 use crate::func_impl::*;
+use crate::fetish_error::*;
+use crate::evaluation_trace::*;
 use topological_sort::TopologicalSort;
 use serde::{Serialize, Deserialize};
 
+///Limits on the work which a single top-level call to [`InterpreterState::evaluate`]
+///may perform, counting all of the nested evaluations performed by primitives
+///such as [`crate::func_impl::ComposeImpl`]. `Option::None` means "unlimited".
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct EvaluationLimits {
+    ///The maximum number of applications which may be evaluated.
+    pub fuel : Option<usize>,
+    ///The maximum nesting depth of applications, where a top-level application has depth one.
+    pub max_depth : Option<usize>
+}
+
+//Record of a change made to an InterpreterState during a top-level evaluation,
+//so that the change may be undone if the evaluation fails
+enum EvaluationChange {
+    StoredTerm(NonPrimitiveTermPointer),
+    LinkedApplication(TermApplicationResult)
+}
+
 ///Represents the state of a simple interpreter for the combinatorial language
 ///defined through the referenced [`Context`], with the given [`TypeId`]-indexed
 ///[`TypeSpace`]s and [`ApplicationTable`]s memoizing all known non-primitive terms
-///and results of term evaluations, respectively. 
+///and results of term evaluations, respectively. Evaluations are subject to the
+///given [`EvaluationLimits`], and may optionally be traced (see [`Self::start_tracing`]).
 pub struct InterpreterState<'a> {
     pub application_tables : HashMap::<TypeId, ApplicationTable>,
     pub type_spaces : HashMap::<TypeId, TypeSpace>,
-    pub ctxt : &'a Context
+    pub limits : EvaluationLimits,
+    pub ctxt : &'a Context,
+    fuel_used : usize,
+    depth : usize,
+    changes : Vec<EvaluationChange>,
+    tracer : Option<EvaluationTracer>
 }
 
 #[derive(Serialize, Deserialize)]
@@ -42,7 +68,12 @@ impl SerializedInterpreterState {
         InterpreterState {
             application_tables : self.application_tables,
             type_spaces : self.type_spaces,
-            ctxt
+            limits : EvaluationLimits::default(),
+            ctxt,
+            fuel_used : 0,
+            depth : 0,
+            changes : Vec::new(),
+            tracer : Option::None
         }
     }
 }
@@ -60,14 +91,38 @@ impl <'a> InterpreterState<'a> {
         self.ctxt
     }
 
-    ///Stores the given [`PartiallyAppliedTerm`] in the [`TypeSpace`] for the
-    ///given [`TypeId`], if it wasn't already present. Returns a
-    ///[`NonPrimitiveTermPointer`] referencing where it was stored
-    ///in this [`InterpreterState`].
-    pub fn store_term(&mut self, type_id : TypeId, term : PartiallyAppliedTerm) -> NonPrimitiveTermPointer {
+    ///Interns the canonical form of the given [`PartiallyAppliedTerm`] of the given [`TypeId`],
+    ///as determined by the rewriting rules of its primitive (see [`FuncImpl::normalize`]).
+    ///If the term is already canonical, it's stored in the [`TypeSpace`] for the given [`TypeId`]
+    ///if it wasn't already present. Returns a [`TermPointer`] to the canonical form together with
+    ///any [`NewlyEvaluatedTerms`] which arose from storing or normalizing it, or the first
+    ///[`FetishError`] encountered while evaluating terms during normalization.
+    pub fn store_term(&mut self, type_id : TypeId, term : PartiallyAppliedTerm)
+                     -> Result<(TermPointer, NewlyEvaluatedTerms), FetishError> {
+        let func_impl = self.ctxt.get_primitive(term.func_ptr);
+        if let Option::Some(normal_form) = func_impl.normalize(self, &term.args)? {
+            return Result::Ok(normal_form);
+        }
+
         let type_space : &mut TypeSpace = self.type_spaces.get_mut(&type_id).unwrap();
+        let num_terms = type_space.get_num_terms();
         let result = type_space.add(term);
-        result
+        if (self.depth > 0 && type_space.get_num_terms() > num_terms) {
+            self.changes.push(EvaluationChange::StoredTerm(result));
+        }
+        let mut newly_evaluated_terms = NewlyEvaluatedTerms::new();
+        newly_evaluated_terms.add_term(result);
+        Result::Ok((TermPointer::from(result), newly_evaluated_terms))
+    }
+
+    ///If the given [`PartiallyAppliedTerm`] is already stored in this [`InterpreterState`] under
+    ///the given [`TypeId`], yields a [`NonPrimitiveTermPointer`] to it, without storing anything.
+    pub fn find_term(&self, type_id : TypeId, term : &PartiallyAppliedTerm) -> Option<NonPrimitiveTermPointer> {
+        let index = self.type_spaces.get(&type_id)?.get_index(term)?;
+        Option::Some(NonPrimitiveTermPointer {
+            type_id,
+            index
+        })
     }
 
     ///Given a [`TermPointer`], yields the [`PartiallyAppliedTerm`] which
@@ -89,6 +144,50 @@ impl <'a> InterpreterState<'a> {
         }
     }
 
+    ///Returns true iff the given [`TermPointer`] points to a term which exists
+    ///in this [`InterpreterState`] (or, in the case of a primitive, within the containing [`Context`]).
+    pub fn has_term(&self, term_ptr : TermPointer) -> bool {
+        match (term_ptr.index) {
+            TermIndex::Primitive(index) => {
+                match (self.ctxt.primitive_directory.primitive_type_spaces.get(&term_ptr.type_id)) {
+                    Option::Some(primitive_type_space) => index < primitive_type_space.terms.len(),
+                    Option::None => false
+                }
+            },
+            TermIndex::NonPrimitive(index) => {
+                match (self.type_spaces.get(&term_ptr.type_id)) {
+                    Option::Some(type_space) => index < type_space.get_num_terms(),
+                    Option::None => false
+                }
+            }
+        }
+    }
+
+    ///Checks that the given [`TermReference`] is a well-formed term of the given [`TypeId`]
+    ///in this [`InterpreterState`]. That is, functions (including those within tuples)
+    ///must point to known terms, and vectors must have the declared dimension.
+    pub fn check_term_reference(&self, term_ref : &TermReference, type_id : TypeId) -> Result<(), FetishError> {
+        term_ref.check_type(type_id, self.ctxt)?;
+        self.check_term_pointers_exist(term_ref)
+    }
+
+    fn check_term_pointers_exist(&self, term_ref : &TermReference) -> Result<(), FetishError> {
+        match (term_ref) {
+            TermReference::FuncRef(func_ptr) => {
+                if (!self.has_term(*func_ptr)) {
+                    return Result::Err(FetishError::UnknownTerm(*func_ptr));
+                }
+            },
+            TermReference::VecRef(_, _) => {},
+            TermReference::ProductRef(_, components) => {
+                for component in components.iter() {
+                    self.check_term_pointers_exist(component)?;
+                }
+            }
+        }
+        Result::Ok(())
+    }
+
     ///Given a [`NonPrimitiveTermPointer`], yields the [`PartiallyAppliedTerm`]
     ///which is stored at that location within this [`InterpreterState`].
     pub fn get_nonprimitive(&self, term_ptr : NonPrimitiveTermPointer) -> &PartiallyAppliedTerm {
@@ -98,7 +197,7 @@ impl <'a> InterpreterState<'a> {
     ///Gets all currently-known [`TermApplicationResult`]s which use the given [`TermReference`] argument.
     pub fn get_app_results_with_arg(&self, arg : &TermReference) -> Vec<TermApplicationResult> {
         let mut result : Vec<TermApplicationResult> = Vec::new();
-        for table in self.application_tables.values() {
+        for table in self.get_application_tables_in_order() {
             let mut temp = table.get_app_results_with_arg(arg);
             result.append(&mut temp);
         }
@@ -109,7 +208,7 @@ impl <'a> InterpreterState<'a> {
     ///that the given [`TermPointer`] points to.
     pub fn get_app_results_with_func(&self, func : TermPointer) -> Vec<TermApplicationResult> {
         let mut result : Vec<TermApplicationResult> = Vec::new();
-        for table in self.application_tables.values() {
+        for table in self.get_application_tables_in_order() {
             let mut temp = table.get_app_results_with_func(func);
             result.append(&mut temp);
         }
@@ -120,19 +219,143 @@ impl <'a> InterpreterState<'a> {
     ///result.
     pub fn get_app_results_with_result(&self, result_term : &TermReference) -> Vec<TermApplicationResult> {
         let mut result : Vec<TermApplicationResult> = Vec::new();
-        for table in self.application_tables.values() {
+        for table in self.get_application_tables_in_order() {
             let mut temp = table.get_app_results_with_result(result_term);
             result.append(&mut temp);
         }
         result
     }
 
-    ///Evaluates the given [`TermApplication`] against this [`InterpreterState`], assuming
-    ///that the function pointed to in the [`TermApplication`] is a primitive. Yields
+    //Queries across application tables visit them in TypeId order, so that
+    //their results come out in the same order every time
+    fn get_application_tables_in_order(&self) -> Vec<&ApplicationTable> {
+        let mut type_ids : Vec<TypeId> = self.application_tables.keys().copied().collect();
+        type_ids.sort_unstable();
+        type_ids.iter().map(|type_id| &self.application_tables[type_id]).collect()
+    }
+
+    ///If the given [`TermApplication`] has been evaluated before, yields the [`TermReference`]
+    ///to its result which was recorded in the relevant [`ApplicationTable`].
+    pub fn get_memoized_result(&self, term_app : &TermApplication) -> Option<TermReference> {
+        let application_table = self.application_tables.get(&term_app.get_func_type())?;
+        application_table.get_results_from_application(term_app).into_iter().next()
+    }
+
+    ///Like [`Self::evaluate`], but if the given [`TermApplication`] has been evaluated
+    ///before, yields its memoized result (see [`Self::get_memoized_result`]) without
+    ///evaluating it again, and without any [`NewlyEvaluatedTerms`].
+    pub fn evaluate_memoized(&mut self, term_app : &TermApplication)
+                            -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+        match (self.get_memoized_result(term_app)) {
+            Option::Some(result_ref) => {
+                self.trace(|tracer, state| tracer.record_cached(term_app, &result_ref, state));
+                Result::Ok((result_ref, NewlyEvaluatedTerms::new()))
+            },
+            Option::None => self.evaluate(term_app)
+        }
+    }
+
+    ///Starts recording an [`EvaluationTrace`] of every subsequent evaluation,
+    ///discarding anything recorded so far.
+    pub fn start_tracing(&mut self) {
+        self.tracer = Option::Some(EvaluationTracer::new());
+    }
+
+    ///Stops tracing, and yields everything recorded since the last call
+    ///to [`Self::start_tracing`], or `Option::None` if this [`InterpreterState`]
+    ///wasn't tracing.
+    pub fn stop_tracing(&mut self) -> Option<EvaluationTrace> {
+        self.tracer.take().map(|tracer| tracer.finish())
+    }
+
+    ///Returns true iff this [`InterpreterState`] is recording an [`EvaluationTrace`].
+    pub fn is_tracing(&self) -> bool {
+        self.tracer.is_some()
+    }
+
+    //Records something with the tracer, if tracing, which may display terms in this state
+    fn trace<F : FnOnce(&mut EvaluationTracer, &InterpreterState)>(&mut self, record : F) {
+        if let Option::Some(mut tracer) = self.tracer.take() {
+            record(&mut tracer, self);
+            self.tracer = Option::Some(tracer);
+        }
+    }
+
+    ///Applies the function that the given [`TermPointer`] points to to the given
+    ///[`TermReference`] argument, after checking that the resulting [`TermApplication`]
+    ///is well-typed (see [`TermApplication::new`]). Otherwise identical to [`Self::evaluate`].
+    pub fn apply(&mut self, func_ptr : TermPointer, arg_ref : TermReference)
+                -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+        let term_app = TermApplication::new(func_ptr, arg_ref, self.ctxt)?;
+        self.evaluate(&term_app)
+    }
+
+    ///Evaluates the given [`TermApplication`] against this [`InterpreterState`]. Yields
     ///a [`TermReference`] to the result of the evaluation, and a list of [`NewlyEvaluatedTerms`]
     ///for this [`InterpreterState`] which resulted from evaluating the application.
-    pub fn evaluate(&mut self, term_app : &TermApplication) -> (TermReference, NewlyEvaluatedTerms) {
+    ///Yields a [`FetishError`] instead if the application is ill-typed, if the
+    ///evaluation of the underlying primitive fails, or if the evaluation exceeds
+    ///this [`InterpreterState`]'s [`EvaluationLimits`]. If a top-level evaluation fails,
+    ///every term stored and every application linked in the course of it is removed again,
+    ///leaving this [`InterpreterState`] as it was before the call.
+    pub fn evaluate(&mut self, term_app : &TermApplication) -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+        let is_top_level = (self.depth == 0);
+        if (is_top_level) {
+            self.fuel_used = 0;
+        }
+        self.trace(|tracer, state| tracer.open(term_app, state));
+        self.depth += 1;
+        let result = self.evaluate_within_limits(term_app);
+        self.depth -= 1;
+        if (self.is_tracing()) {
+            let outcome = match (&result) {
+                Result::Ok((result_ref, _)) => Result::Ok(result_ref.clone()),
+                Result::Err(err) => Result::Err(err.clone())
+            };
+            self.trace(|tracer, state| tracer.close(&outcome, state));
+        }
+        if (is_top_level) {
+            if (result.is_err()) {
+                self.undo_changes();
+            }
+            self.changes.clear();
+        }
+        result
+    }
+
+    fn undo_changes(&mut self) {
+        while let Option::Some(change) = self.changes.pop() {
+            match (change) {
+                EvaluationChange::StoredTerm(term_ptr) => {
+                    self.type_spaces.get_mut(&term_ptr.type_id).unwrap().remove_last();
+                },
+                EvaluationChange::LinkedApplication(term_app_result) => {
+                    let func_type_id = term_app_result.term_app.get_func_type();
+                    self.application_tables.get_mut(&func_type_id).unwrap()
+                        .unlink(&term_app_result.term_app, &term_app_result.result_ref);
+                }
+            }
+        }
+    }
+
+    fn evaluate_within_limits(&mut self, term_app : &TermApplication)
+                             -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+        if let Option::Some(max_depth) = self.limits.max_depth {
+            if (self.depth > max_depth) {
+                return Result::Err(FetishError::MaxDepthExceeded { max_depth });
+            }
+        }
+        self.fuel_used += 1;
+        if let Option::Some(fuel) = self.limits.fuel {
+            if (self.fuel_used > fuel) {
+                return Result::Err(FetishError::OutOfFuel { fuel });
+            }
+        }
+
         let func_type_id : TypeId = term_app.get_func_type();
+        let (arg_type_id, ret_type_id) = self.ctxt.type_info_directory.try_get_arg_and_ret_type_ids(func_type_id)?;
+        self.check_term_reference(&TermReference::FuncRef(term_app.func_ptr), func_type_id)?;
+        self.check_term_reference(&term_app.arg_ref, arg_type_id)?;
 
         let func_term : PartiallyAppliedTerm = self.get(term_app.func_ptr);
         let arg_ref : TermReference = term_app.arg_ref.clone();
@@ -142,10 +365,27 @@ impl <'a> InterpreterState<'a> {
 
         args_copy.push(arg_ref);
 
+        let newly_created = self.get_memoized_result(term_app).is_none();
+        if (self.is_tracing()) {
+            let invocation = if (func_impl.ready_to_evaluate(&args_copy)) {
+                Option::Some(PartiallyAppliedTerm {
+                    func_ptr : func_term.func_ptr,
+                    args : args_copy.clone()
+                })
+            } else {
+                Option::None
+            };
+            self.trace(|tracer, state| tracer.record_invocation(newly_created, invocation, state));
+        }
+
         let mut newly_evaluated_terms = NewlyEvaluatedTerms::new();
 
         let result_ref : TermReference = if (func_impl.ready_to_evaluate(&args_copy)) {
-            let (ret_ref, more_evaluated_terms) = func_impl.evaluate(self, args_copy);
+            let (ret_ref, more_evaluated_terms) = func_impl.evaluate(self, args_copy)?;
+            if (!ret_ref.is_finite()) {
+                return Result::Err(FetishError::NonFiniteValue { name : func_impl.get_name() });
+            }
+            self.check_term_reference(&ret_ref, ret_type_id)?;
             newly_evaluated_terms.merge(more_evaluated_terms);
             ret_ref
         } else {
@@ -153,13 +393,10 @@ impl <'a> InterpreterState<'a> {
                 func_ptr : func_term.func_ptr.clone(),
                 args : args_copy
             };
-            let ret_type_id : TypeId = term_app.get_ret_type(self.ctxt);
-            let ret_ptr = self.store_term(ret_type_id, result);
-
-            newly_evaluated_terms.add_term(ret_ptr);
+            let (ret_ptr, more_evaluated_terms) = self.store_term(ret_type_id, result)?;
+            newly_evaluated_terms.merge(more_evaluated_terms);
 
-            let ret_ref = TermReference::FuncRef(TermPointer::from(ret_ptr));
-            ret_ref
+            TermReference::FuncRef(ret_ptr)
         };
         let application_table : &mut ApplicationTable = self.application_tables.get_mut(&func_type_id).unwrap();
 
@@ -168,16 +405,20 @@ impl <'a> InterpreterState<'a> {
             result_ref : result_ref.clone()
         };
 
-        newly_evaluated_terms.add_term_app_result(term_app_result);
+        newly_evaluated_terms.add_term_app_result(term_app_result.clone());
 
         application_table.link(term_app.clone(), result_ref.clone());
-        (result_ref, newly_evaluated_terms)
+        self.changes.push(EvaluationChange::LinkedApplication(term_app_result));
+        Result::Ok((result_ref, newly_evaluated_terms))
     }
 
     ///Convenience method that ensures that every type has at least one term, assuming
-    ///that this [`InterpreterState`] was just-initialized. Returns [`NewlyEvaluatedTerms`]
-    ///for evaluations that were performed as a result of this operation.
-    pub fn ensure_every_type_has_a_term_on_init(&mut self) -> NewlyEvaluatedTerms {
+    ///that this [`InterpreterState`] was just-initialized. Vector types are populated with
+    ///their first constant in the [`crate::constant_directory::ConstantDirectory`], or with
+    ///the zero vector if they have none. Returns [`NewlyEvaluatedTerms`]
+    ///for evaluations that were performed as a result of this operation, or the
+    ///first [`FetishError`] encountered while evaluating.
+    pub fn ensure_every_type_has_a_term_on_init(&mut self) -> Result<NewlyEvaluatedTerms, FetishError> {
 	let mut type_to_term = HashMap::<TypeId, TermReference>::new();
         //Initial population
         for i in 0..self.ctxt.get_total_num_types() {
@@ -185,7 +426,9 @@ impl <'a> InterpreterState<'a> {
             let kind = self.ctxt.get_type(type_id);
             match (kind) {
                 Type::VecType(n) => {
-                    type_to_term.insert(type_id, TermReference::VecRef(type_id, Array::zeros((n,))));
+                    let term_ref = self.ctxt.constant_directory.get_constants_of_type(type_id).into_iter().next()
+                                       .unwrap_or_else(|| TermReference::VecRef(type_id, Array::zeros((n,))));
+                    type_to_term.insert(type_id, term_ref);
                 },
                 Type::FuncType(_, _) => {
                     let primitive_space = self.ctxt.primitive_directory.primitive_type_spaces.get(&type_id).unwrap();
@@ -197,12 +440,30 @@ impl <'a> InterpreterState<'a> {
                         };
                         type_to_term.insert(type_id, TermReference::FuncRef(func_ptr));
                     }
+                },
+                Type::ProductType(_) => {
+                    //Filled in below, once every component has a term
                 }
             }
         }
         let mut newly_evaluated_terms = NewlyEvaluatedTerms::new();
         loop {
             let mut found_something = false;
+            for i in 0..self.ctxt.get_total_num_types() {
+                let product_type_id = i as TypeId;
+                if (type_to_term.contains_key(&product_type_id)) {
+                    continue;
+                }
+                if let Type::ProductType(component_type_ids) = self.ctxt.get_type(product_type_id) {
+                    let components : Option<Vec<TermReference>> = component_type_ids.iter()
+                                                        .map(|component_type_id| type_to_term.get(component_type_id).cloned())
+                                                        .collect();
+                    if let Option::Some(components) = components {
+                        type_to_term.insert(product_type_id, TermReference::ProductRef(product_type_id, components));
+                        found_something = true;
+                    }
+                }
+            }
             for i in 0..self.ctxt.get_total_num_types() {
                 let func_type_id = i as TypeId;
 
@@ -215,7 +476,7 @@ impl <'a> InterpreterState<'a> {
                                         func_ptr : func_ptr.clone(),
                                         arg_ref : arg_ref.clone()
                                     };
-                                    let (result_ref, more_evaluated_terms) = self.evaluate(&application);
+                                    let (result_ref, more_evaluated_terms) = self.evaluate(&application)?;
                                     newly_evaluated_terms.merge(more_evaluated_terms);
                                     type_to_term.insert(ret_type_id, result_ref);
 
@@ -230,7 +491,7 @@ impl <'a> InterpreterState<'a> {
                 break;
             }
         }
-        newly_evaluated_terms
+        Result::Ok(newly_evaluated_terms)
     }
 
     ///Constructs a fresh [`InterpreterState`] operating within the given [`Context`].
@@ -240,8 +501,8 @@ impl <'a> InterpreterState<'a> {
         let mut type_spaces = HashMap::<TypeId, TypeSpace>::new();
 
         for type_id in 0..ctxt.get_total_num_types() {
-            if (!ctxt.is_vector_type(type_id)) {
-                application_tables.insert(type_id, ApplicationTable::new(type_id, ctxt));
+            if let Result::Ok(application_table) = ApplicationTable::new(type_id, ctxt) {
+                application_tables.insert(type_id, application_table);
                 type_spaces.insert(type_id, TypeSpace::new(type_id));
             }
         }
@@ -249,9 +510,245 @@ impl <'a> InterpreterState<'a> {
         let result = InterpreterState {
             application_tables,
             type_spaces,
-            ctxt
+            limits : EvaluationLimits::default(),
+            ctxt,
+            fuel_used : 0,
+            depth : 0,
+            changes : Vec::new(),
+            tracer : Option::None
         };
 
         result
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+    use crate::array_utils::*;
+    use crate::context_builder::*;
+
+    //Primitive which runs forever: "runaway x = runaway ((+ x) x)"
+    struct RunawayImpl {}
+
+    impl HasFuncSignature for RunawayImpl {
+        fn get_name(&self) -> String {
+            String::from("runaway")
+        }
+        fn required_arg_types(&self) -> Vec<TypeId> {
+            vec![TEST_VECTOR_T]
+        }
+        fn ret_type(&self) -> TypeId {
+            TEST_VECTOR_T
+        }
+    }
+
+    impl FuncImpl for RunawayImpl {
+        fn evaluate(&self, state : &mut InterpreterState, args : Vec<TermReference>)
+                   -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+            let arg_ref = args[0].clone();
+            let (curried_ref, _) = state.apply(add_ptr(), arg_ref.clone())?;
+            let curried_ptr = match (curried_ref) {
+                TermReference::FuncRef(curried_ptr) => curried_ptr,
+                _ => panic!()
+            };
+            let (doubled_ref, _) = state.apply(curried_ptr, arg_ref)?;
+            state.apply(runaway_ptr(), doubled_ref)
+        }
+    }
+
+    fn runaway_ptr() -> TermPointer {
+        TermPointer {
+            type_id : TEST_VECTOR_FUNC_T,
+            index : TermIndex::Primitive(0)
+        }
+    }
+
+    fn get_runaway_context() -> Context {
+        let mut builder = get_test_vector_only_context_builder();
+        builder.add_func_type(TEST_VECTOR_T, TEST_VECTOR_T).unwrap();
+        builder.add_func_type(TEST_VECTOR_T, TEST_VECTOR_FUNC_T).unwrap();
+        add_test_function_space(&mut builder, TEST_VECTOR_FUNC_T, 8);
+        add_test_function_space(&mut builder, TEST_BINARY_VECTOR_FUNC_T, 16);
+        builder.add_primitive(Box::new(RunawayImpl {}));
+        builder.add_binary_func(TEST_VECTOR_T, Box::new(AddOperator {}));
+        builder.build().unwrap()
+    }
+
+    fn assert_runaway_is_stopped(limits : EvaluationLimits, expected : FetishError) {
+        let ctxt = get_runaway_context();
+        let mut state = InterpreterState::new(&ctxt);
+        let arg_ref = TermReference::VecRef(TEST_VECTOR_T, to_noisy(array![1.0f32, 2.0f32].view()));
+        state.apply(add_ptr(), arg_ref.clone()).unwrap();
+        state.limits = limits;
+
+        assert_eq!(state.apply(runaway_ptr(), arg_ref).err(), Option::Some(expected));
+        assert_eq!(state.type_spaces.get(&TEST_VECTOR_FUNC_T).unwrap().get_num_terms(), 1);
+        assert_eq!(state.get_app_results_with_func(add_ptr()).len(), 1);
+        assert!(state.get_app_results_with_func(runaway_ptr()).is_empty());
+        let curried_ptr = TermPointer {
+            type_id : TEST_VECTOR_FUNC_T,
+            index : TermIndex::NonPrimitive(0)
+        };
+        assert!(state.get_app_results_with_func(curried_ptr).is_empty());
+    }
+
+    #[test]
+    fn runaway_evaluation_runs_out_of_fuel() {
+        let limits = EvaluationLimits {
+            fuel : Option::Some(100),
+            max_depth : Option::None
+        };
+        assert_runaway_is_stopped(limits, FetishError::OutOfFuel { fuel : 100 });
+    }
+
+    #[test]
+    fn runaway_evaluation_exceeds_max_depth() {
+        let limits = EvaluationLimits {
+            fuel : Option::None,
+            max_depth : Option::Some(20)
+        };
+        assert_runaway_is_stopped(limits, FetishError::MaxDepthExceeded { max_depth : 20 });
+    }
+
+    #[test]
+    fn fuel_is_per_top_level_evaluation() {
+        let ctxt = get_test_function_context();
+        let mut state = InterpreterState::new(&ctxt);
+        state.limits.fuel = Option::Some(1);
+        let arg_ref = TermReference::VecRef(TEST_VECTOR_T, to_noisy(array![1.0f32, 2.0f32].view()));
+        let (curried_ref, _) = state.apply(add_ptr(), arg_ref.clone()).unwrap();
+        let curried_ptr = match (curried_ref) {
+            TermReference::FuncRef(curried_ptr) => curried_ptr,
+            _ => panic!()
+        };
+        let (result_ref, _) = state.apply(curried_ptr, arg_ref).unwrap();
+        assert_equal_vector_term(result_ref, array![2.0f32, 4.0f32].view());
+    }
+
+    fn add_ptr() -> TermPointer {
+        TermPointer {
+            type_id : TEST_BINARY_VECTOR_FUNC_T,
+            index : TermIndex::Primitive(0)
+        }
+    }
+
+    #[test]
+    fn ill_typed_argument_is_reported() {
+        let ctxt = get_test_function_context();
+        let mut state = InterpreterState::new(&ctxt);
+        let term_app = TermApplication {
+            func_ptr : add_ptr(),
+            arg_ref : TermReference::VecRef(TEST_SCALAR_T, to_noisy(array![1.0f32].view()))
+        };
+        let result = state.evaluate(&term_app);
+        assert_eq!(result.err(), Option::Some(FetishError::TypeMismatch {
+            expected : TEST_VECTOR_T,
+            actual : TEST_SCALAR_T
+        }));
+        assert!(state.get_app_results_with_func(add_ptr()).is_empty());
+    }
+
+    #[test]
+    fn wrong_dimension_is_reported() {
+        let ctxt = get_test_function_context();
+        let mut state = InterpreterState::new(&ctxt);
+        let term_app = TermApplication {
+            func_ptr : add_ptr(),
+            arg_ref : TermReference::VecRef(TEST_VECTOR_T, to_noisy(array![1.0f32, 2.0f32, 3.0f32].view()))
+        };
+        let result = state.evaluate(&term_app);
+        assert_eq!(result.err(), Option::Some(FetishError::DimensionMismatch {
+            type_id : TEST_VECTOR_T,
+            expected : TEST_VECTOR_SIZE,
+            actual : 3
+        }));
+    }
+
+    #[test]
+    fn unknown_function_is_reported() {
+        let ctxt = get_test_function_context();
+        let mut state = InterpreterState::new(&ctxt);
+        let missing_ptr = TermPointer {
+            type_id : TEST_BINARY_VECTOR_FUNC_T,
+            index : TermIndex::NonPrimitive(0)
+        };
+        let term_app = TermApplication {
+            func_ptr : missing_ptr,
+            arg_ref : TermReference::VecRef(TEST_VECTOR_T, to_noisy(array![1.0f32, 2.0f32].view()))
+        };
+        assert_eq!(state.evaluate(&term_app).err(), Option::Some(FetishError::UnknownTerm(missing_ptr)));
+    }
+
+    #[test]
+    fn checked_term_application_rejects_wrong_dimension() {
+        let ctxt = get_test_function_context();
+        let arg_ref = TermReference::VecRef(TEST_VECTOR_T, to_noisy(array![1.0f32].view()));
+        let result = TermApplication::new(add_ptr(), arg_ref, &ctxt);
+        assert_eq!(result.err(), Option::Some(FetishError::DimensionMismatch {
+            type_id : TEST_VECTOR_T,
+            expected : TEST_VECTOR_SIZE,
+            actual : 1
+        }));
+    }
+
+    #[test]
+    fn checked_term_application_rejects_non_function() {
+        let ctxt = get_test_function_context();
+        let not_a_func_ptr = TermPointer {
+            type_id : TEST_VECTOR_T,
+            index : TermIndex::Primitive(0)
+        };
+        let arg_ref = TermReference::VecRef(TEST_VECTOR_T, to_noisy(array![1.0f32, 2.0f32].view()));
+        let result = TermApplication::new(not_a_func_ptr, arg_ref, &ctxt);
+        assert_eq!(result.err(), Option::Some(FetishError::NotAFunctionType(TEST_VECTOR_T)));
+    }
+
+    #[test]
+    fn apply_evaluates_well_typed_applications() {
+        let ctxt = get_test_function_context();
+        let mut state = InterpreterState::new(&ctxt);
+        let arg_ref = TermReference::VecRef(TEST_VECTOR_T, to_noisy(array![1.0f32, 2.0f32].view()));
+        let (curried_ref, _) = state.apply(add_ptr(), arg_ref.clone()).unwrap();
+        let curried_ptr = match (curried_ref) {
+            TermReference::FuncRef(curried_ptr) => curried_ptr,
+            _ => panic!()
+        };
+        let (result_ref, _) = state.apply(curried_ptr, arg_ref).unwrap();
+        assert_equal_vector_term(result_ref, array![2.0f32, 4.0f32].view());
+    }
+
+    #[test]
+    fn initial_terms_are_built_from_constants() {
+        let mut builder = get_test_vector_only_context_builder();
+        let binary_t = builder.parse_type("Vec2 -> Vec2 -> Vec2").unwrap();
+        add_test_function_space(&mut builder, TEST_VECTOR_FUNC_T, 8);
+        add_test_function_space(&mut builder, binary_t, 8);
+        builder.add_binary_func(TEST_VECTOR_T, Box::new(AddOperator {}));
+        builder.add_constant("one", TEST_VECTOR_T, &[1.0f32, 1.0f32]).unwrap();
+        let ctxt = builder.build().unwrap();
+        let mut state = InterpreterState::new(&ctxt);
+        state.ensure_every_type_has_a_term_on_init().unwrap();
+
+        let term_app = TermApplication {
+            func_ptr : add_ptr(),
+            arg_ref : TermReference::VecRef(TEST_VECTOR_T, to_noisy(array![1.0f32, 1.0f32].view()))
+        };
+        assert!(state.get_memoized_result(&term_app).is_some());
+    }
+
+    #[test]
+    fn vector_types_have_no_application_tables() {
+        let ctxt = get_test_function_context();
+        assert_eq!(ApplicationTable::new(TEST_VECTOR_T, &ctxt).err(),
+                   Option::Some(FetishError::NotAFunctionType(TEST_VECTOR_T)));
+        assert_eq!(ctxt.try_get_func_type_id(TEST_SCALAR_T, TEST_VECTOR_T).err(),
+                   Option::Some(FetishError::MissingFuncType {
+                       arg_type_id : TEST_SCALAR_T,
+                       ret_type_id : TEST_VECTOR_T
+                   }));
+        assert_eq!(ctxt.prior_directory.try_get_prior_info(TEST_VECTOR_T).err(),
+                   Option::Some(FetishError::MissingPrior(TEST_VECTOR_T)));
+    }
+}
@@ -39,7 +39,19 @@ pub mod term_index;
 pub mod displayable_with_context;
 pub mod primitive_type_space;
 pub mod primitive_directory;
+pub mod constant_directory;
+pub mod primitive_template;
 pub mod context;
+pub mod context_builder;
+pub mod fetish_error;
+pub mod type_parser;
+pub mod term_expression;
+pub mod expression_tree;
+pub mod evaluation_trace;
+pub mod term_enumerator;
+pub mod synthesizer;
+pub mod equivalence_tester;
+pub mod vector_type_metadata;
 pub mod compressed_inv_schmear;
 pub mod prior_specification;
 pub mod elaborator;
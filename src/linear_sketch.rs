@@ -10,6 +10,7 @@ use crate::schmear::*;
 use crate::kernel::*;
 use crate::inverse_schmear::*;
 use crate::pseudoinverse::*;
+use rand::prelude::*;
 
 use serde::{Serialize, Deserialize};
 
@@ -25,9 +26,10 @@ pub struct LinearSketch {
 
 impl LinearSketch {
     ///Generates a new [`LinearSketch`] with projection matrix with entries drawn
-    ///from a zero-centered normal distribution with standard deviation `alpha`.
-    pub fn new(in_dimensions : usize, out_dimensions : usize, alpha : f32) -> LinearSketch {
-        let mut projection_mat = Array::random((out_dimensions, in_dimensions), StandardNormal);
+    ///from a zero-centered normal distribution with standard deviation `alpha`, drawn from `rng`.
+    pub fn new<R : Rng + ?Sized>(in_dimensions : usize, out_dimensions : usize, alpha : f32,
+                                 rng : &mut R) -> LinearSketch {
+        let mut projection_mat = Array::random_using((out_dimensions, in_dimensions), StandardNormal, rng);
         let mut projection_mat_pinv = pseudoinverse(&projection_mat);
         
         projection_mat *= alpha;
@@ -92,7 +94,7 @@ mod tests {
 
     #[test]
     fn expand_then_sketch_is_identity() {
-        let linear_sketch = LinearSketch::new(20, 10, 1.0f32);
+        let linear_sketch = LinearSketch::new(20, 10, 1.0f32, &mut rand::thread_rng());
         let vector = random_vector(10); 
         let expanded = linear_sketch.projection_mat_pinv.dot(&vector);
         let sketched = linear_sketch.sketch(expanded.view());
@@ -119,11 +119,11 @@ impl <'a> Model<'a> {
     }
     ///Draws a sample of a linear mapping from the feature space of the input
     ///to the compressed space of the output from the distribution defined by this [`Model`].
-    pub fn sample(&self, rng : &mut ThreadRng) -> Array2<f32> {
+    pub fn sample<R : Rng + ?Sized>(&self, rng : &mut R) -> Array2<f32> {
         self.data.sample(rng)
     }
     ///Identical to [`Self::sample`], but the result is flattened.
-    pub fn sample_as_vec(&self, rng : &mut ThreadRng) -> Array1::<f32> {
+    pub fn sample_as_vec<R : Rng + ?Sized>(&self, rng : &mut R) -> Array1::<f32> {
         self.data.sample_as_vec(rng)
     }
     ///Gets the mean of the underlying [`NormalInverseWishart`] model from the feature
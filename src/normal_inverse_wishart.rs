@@ -59,13 +59,13 @@ impl NormalInverseWishart {
     }
 
     ///Draws a sample from the represented MNIW distribution
-    pub fn sample(&self, rng : &mut ThreadRng) -> Array2<f32> {
+    pub fn sample<R : Rng + ?Sized>(&self, rng : &mut R) -> Array2<f32> {
         let sampler = NormalInverseWishartSampler::new(&self);
         sampler.sample(rng)
     }
 
     ///The same as [`Self::sample`], but the result is flattened.
-    pub fn sample_as_vec(&self, rng : &mut ThreadRng) -> Array1<f32> {
+    pub fn sample_as_vec<R : Rng + ?Sized>(&self, rng : &mut R) -> Array1<f32> {
         let thick = self.sample(rng);
         let total_dims = self.get_total_dims();
         thick.into_shape((total_dims,)).unwrap()
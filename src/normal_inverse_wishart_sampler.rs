@@ -43,11 +43,11 @@ impl NormalInverseWishartSampler {
     }
     ///Draws a sample from the [`NormalInverseWishart`] distribution
     ///that this [`NormalInverseWishartSampler`] was constructed with.
-    pub fn sample(&self, rng : &mut ThreadRng) -> Array2<f32> {
+    pub fn sample<R : Rng + ?Sized>(&self, rng : &mut R) -> Array2<f32> {
         let out_chol = self.wishart.sample_inv_cholesky_factor(rng);
         let in_chol = &self.covariance_cholesky_factor;
 
-        let X = Array::random((self.t, self.s), StandardNormal);
+        let X = Array::random_using((self.t, self.s), StandardNormal, rng);
 	let T = out_chol.dot(&X).dot(in_chol);
 
         let mut result = self.mean.clone();
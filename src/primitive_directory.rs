@@ -4,6 +4,11 @@ use crate::primitive_type_space::*;
 use crate::func_impl::*;
 use crate::params::*;
 use crate::primitive_term_pointer::*;
+use crate::context_builder::*;
+use crate::primitive_template::*;
+use crate::fetish_error::*;
+
+use serde::{Serialize, Deserialize};
 
 ///A directory of primitive function terms ([`FuncImpl`]s),
 ///consisting of one [`PrimitiveTypeSpace`] for each function [`TypeId`]
@@ -12,7 +17,99 @@ pub struct PrimitiveDirectory {
     pub primitive_type_spaces : HashMap::<TypeId, PrimitiveTypeSpace>
 }
 
+///The name and signature of a primitive [`FuncImpl`], which is enough to
+///re-bind a freshly-constructed [`FuncImpl`] to the position it previously held
+///within a [`PrimitiveDirectory`].
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct PrimitiveSignature {
+    pub name : String,
+    pub arg_types : Vec<TypeId>,
+    pub ret_type : TypeId
+}
+
+impl PrimitiveSignature {
+    ///Gets the [`PrimitiveSignature`] of the given [`FuncImpl`].
+    pub fn of(func_impl : &dyn FuncImpl) -> PrimitiveSignature {
+        PrimitiveSignature {
+            name : func_impl.get_name(),
+            arg_types : func_impl.required_arg_types(),
+            ret_type : func_impl.ret_type()
+        }
+    }
+}
+
+///Serialized form of a [`PrimitiveDirectory`]. Since [`FuncImpl`]s are arbitrary code,
+///only their [`PrimitiveSignature`]s are stored, in the order that they appear for each type.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SerializedPrimitiveDirectory {
+    pub signatures : HashMap::<TypeId, Vec<PrimitiveSignature>>
+}
+
+impl SerializedPrimitiveDirectory {
+    ///Rebuilds a [`PrimitiveDirectory`] from the given collection of [`FuncImpl`]s,
+    ///placing each one at the same [`PrimitiveTermPointer`] that the primitive with
+    ///the same [`PrimitiveSignature`] had when this was serialized. Every stored signature
+    ///must be matched by exactly one of the passed [`FuncImpl`]s, and vice versa. Stored signatures
+    ///must be distinct, since otherwise the [`FuncImpl`]s matching them could be swapped.
+    pub fn deserialize(mut self, mut func_impls : Vec<Box<dyn FuncImpl>>,
+                       type_info_directory : &TypeInfoDirectory) -> Result<PrimitiveDirectory, FetishError> {
+        let mut result = PrimitiveDirectory::new(type_info_directory);
+        for (type_id, mut signatures) in self.signatures.drain() {
+            let primitive_type_space = result.primitive_type_spaces.get_mut(&type_id)
+                                             .ok_or(ContextBuildError::UnknownType(type_id))?;
+            for (index, signature) in signatures.iter().enumerate() {
+                if (signatures[..index].contains(signature)) {
+                    return Result::Err(ContextBuildError::DuplicatePrimitive {
+                        name : signature.name.clone(),
+                        func_type_id : type_id
+                    }.into());
+                }
+            }
+            for signature in signatures.drain(..) {
+                let position = func_impls.iter()
+                                         .position(|func_impl| PrimitiveSignature::of(func_impl.as_ref()) == signature);
+                match (position) {
+                    Option::Some(position) => {
+                        primitive_type_space.terms.push(func_impls.swap_remove(position));
+                    },
+                    Option::None => {
+                        return Result::Err(ContextBuildError::MissingPrimitive {
+                            name : signature.name,
+                            func_type_id : type_id
+                        }.into());
+                    }
+                }
+            }
+        }
+        if let Option::Some(func_impl) = func_impls.first() {
+            return Result::Err(ContextBuildError::UnexpectedPrimitive {
+                name : func_impl.get_name(),
+                func_type_id : func_impl.func_type(type_info_directory)
+            }.into());
+        }
+        Result::Ok(result)
+    }
+}
+
 impl PrimitiveDirectory {
+    ///Serializes the [`PrimitiveSignature`]s of the primitives in this [`PrimitiveDirectory`].
+    ///See [`SerializedPrimitiveDirectory::deserialize`] for how to restore it, which requires
+    ///the signatures of primitives of the same type to be distinct, as
+    ///[`crate::context_builder::ContextBuilder::build`] ensures.
+    pub fn serialize(&self) -> SerializedPrimitiveDirectory {
+        let signatures = self.primitive_type_spaces.iter()
+                             .map(|(type_id, primitive_type_space)| {
+                                 let type_signatures = primitive_type_space.terms.iter()
+                                                           .map(|term| PrimitiveSignature::of(term.as_ref()))
+                                                           .collect();
+                                 (*type_id, type_signatures)
+                             })
+                             .collect();
+        SerializedPrimitiveDirectory {
+            signatures
+        }
+    }
+
     ///Given a [`PrimitiveTermPointer`] pointing to a primitive term in this
     ///[`PrimitiveDirectory`], yields the primitive term as a [`FuncImpl`].
     pub fn get_primitive(&self, primitive_term_pointer : PrimitiveTermPointer) -> &dyn FuncImpl {
@@ -21,12 +118,30 @@ impl PrimitiveDirectory {
         term.as_ref()
     }
 
+    ///Finds every primitive in this [`PrimitiveDirectory`] with the given name,
+    ///ordered by [`TypeId`] and then by position.
+    pub fn find_primitives(&self, name : &str) -> Vec<PrimitiveTermPointer> {
+        let mut result = Vec::new();
+        for (type_id, primitive_type_space) in self.primitive_type_spaces.iter() {
+            for (index, term) in primitive_type_space.terms.iter().enumerate() {
+                if (term.get_name() == name) {
+                    result.push(PrimitiveTermPointer {
+                        type_id : *type_id,
+                        index
+                    });
+                }
+            }
+        }
+        result.sort_by_key(|primitive_ptr| (primitive_ptr.type_id, primitive_ptr.index));
+        result
+    }
+
     ///Constructs a new, initially-empty [`PrimitiveDirectory`].
     pub fn new(type_info_directory : &TypeInfoDirectory) -> PrimitiveDirectory {
         let mut primitive_type_spaces = HashMap::new();
 
         for type_id in 0..type_info_directory.get_total_num_types() {
-            if (!type_info_directory.is_vector_type(type_id)) {
+            if (type_info_directory.is_func_type(type_id)) {
                 primitive_type_spaces.insert(type_id, PrimitiveTypeSpace::new(type_id));
             }
         }
@@ -42,6 +157,34 @@ impl PrimitiveDirectory {
         primitive_type_space.terms.push(func_impl);
     }
 
+    ///Returns true iff this [`PrimitiveDirectory`] has a primitive of the given function type
+    ///with the given [`PrimitiveSignature`].
+    pub fn has_signature(&self, func_type_id : TypeId, signature : &PrimitiveSignature) -> bool {
+        match (self.primitive_type_spaces.get(&func_type_id)) {
+            Option::Some(primitive_type_space) => primitive_type_space.terms.iter()
+                                                      .any(|term| PrimitiveSignature::of(term.as_ref()) == *signature),
+            Option::None => false
+        }
+    }
+
+    ///Instantiates the given [`PrimitiveTemplate`] for every matching function type in the
+    ///given [`TypeInfoDirectory`] (see [`instantiate_template`]), and adds each instance
+    ///which isn't already present with the same [`PrimitiveSignature`]. Yields the number
+    ///of primitives which were added.
+    pub fn add_template(&mut self, template : &dyn PrimitiveTemplate,
+                        type_info_directory : &TypeInfoDirectory) -> Result<usize, FetishError> {
+        let mut num_added = 0;
+        for func_impl in instantiate_template(template, type_info_directory)? {
+            let func_type = func_impl.func_type(type_info_directory);
+            let signature = PrimitiveSignature::of(func_impl.as_ref());
+            if (!self.has_signature(func_type, &signature)) {
+                self.primitive_type_spaces.get_mut(&func_type).unwrap().terms.push(func_impl);
+                num_added += 1;
+            }
+        }
+        Result::Ok(num_added)
+    }
+
     ///Convenient wrapper around [`Self::add`] which allows adding a [`BinaryArrayOperator`]
     ///to this [`PrimitiveDirectory`] whose element type is the given [`TypeId`] within
     ///the given [`TypeInfoDirectory`].
@@ -53,4 +196,16 @@ impl PrimitiveDirectory {
         };
         self.add(Box::new(binary_func_impl), type_info_directory);
     }
+
+    ///Convenient wrapper around [`Self::add`] which allows adding a [`UnaryArrayOperator`]
+    ///to this [`PrimitiveDirectory`] whose element type is the given [`TypeId`] within
+    ///the given [`TypeInfoDirectory`].
+    pub fn add_unary_func(&mut self, type_id : TypeId, unary_func : Box<dyn UnaryArrayOperator>,
+                                     type_info_directory : &TypeInfoDirectory) {
+        let unary_func_impl = UnaryFuncImpl {
+            elem_type : type_id,
+            f : unary_func
+        };
+        self.add(Box::new(unary_func_impl), type_info_directory);
+    }
 }
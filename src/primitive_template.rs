@@ -0,0 +1,742 @@
+use std::collections::HashMap;
+use crate::type_id::*;
+use crate::type_parser::*;
+use crate::func_impl::*;
+use crate::fetish_error::*;
+
+///An assignment of [`TypeId`]s to the type variables of a [`PrimitiveTemplate`]'s signature.
+pub type TypeAssignment = HashMap<String, TypeId>;
+
+///Trait for polymorphic primitives, which describe a whole family of [`FuncImpl`]s at once.
+///The signature is a textual type expression (see [`TypeExpression`]) in which identifiers
+///starting with a lowercase letter are type variables (see [`is_type_variable_name`]),
+///such as `(b -> c) -> (a -> b) -> a -> c` for function composition.
+///A template is instantiated once for every registered function type which matches its
+///signature, see [`instantiate_template`] and
+///[`crate::primitive_directory::PrimitiveDirectory::add_template`].
+pub trait PrimitiveTemplate {
+    ///Gets the name for the implemented family of functions.
+    fn get_name(&self) -> String;
+    ///Gets the signature of the implemented family of functions, with type variables.
+    fn get_signature(&self) -> String;
+    ///Given a [`TypeInfoDirectory`] and an assignment of [`TypeId`]s to each of the type
+    ///variables in the signature, constructs the corresponding [`FuncImpl`], or yields
+    ///`Option::None` if the template doesn't apply to the given types (for instance,
+    ///if a type variable may only stand for vector types).
+    fn instantiate(&self, type_info_directory : &TypeInfoDirectory,
+                   assignment : &TypeAssignment) -> Option<Box<dyn FuncImpl>>;
+}
+
+fn match_type(pattern : &TypeExpression, type_id : TypeId, type_info_directory : &TypeInfoDirectory,
+              assignment : &mut TypeAssignment) -> bool {
+    match (pattern) {
+        TypeExpression::Named(name) if is_type_variable_name(name) => {
+            match (assignment.get(name)) {
+                Option::Some(assigned_type_id) => *assigned_type_id == type_id,
+                Option::None => {
+                    assignment.insert(name.clone(), type_id);
+                    true
+                }
+            }
+        },
+        TypeExpression::Func(arg, ret) => {
+            match (type_info_directory.get_type(type_id)) {
+                Type::FuncType(arg_type_id, ret_type_id) => {
+                    match_type(arg, arg_type_id, type_info_directory, assignment) &&
+                    match_type(ret, ret_type_id, type_info_directory, assignment)
+                },
+                _ => false
+            }
+        },
+        TypeExpression::Product(components) => {
+            match (type_info_directory.get_type(type_id)) {
+                Type::ProductType(component_type_ids) => {
+                    components.len() == component_type_ids.len() &&
+                    components.iter().zip(component_type_ids.iter())
+                              .all(|(component, component_type_id)| {
+                                  match_type(component, *component_type_id, type_info_directory, assignment)
+                              })
+                },
+                _ => false
+            }
+        },
+        _ => pattern.lookup(type_info_directory) == Result::Ok(type_id)
+    }
+}
+
+///Finds every registered function type in the given [`TypeInfoDirectory`] which matches the
+///given signature, yielding each matching [`TypeId`] in order, together with the
+///[`TypeAssignment`] for the signature's type variables which produces it.
+pub fn find_type_assignments(signature : &TypeExpression,
+                             type_info_directory : &TypeInfoDirectory) -> Vec<(TypeId, TypeAssignment)> {
+    let mut result = Vec::new();
+    for type_id in 0..type_info_directory.get_total_num_types() {
+        if (type_info_directory.is_func_type(type_id)) {
+            let mut assignment = TypeAssignment::new();
+            if (match_type(signature, type_id, type_info_directory, &mut assignment)) {
+                result.push((type_id, assignment));
+            }
+        }
+    }
+    result
+}
+
+///Instantiates the given [`PrimitiveTemplate`] for every registered function type in the
+///given [`TypeInfoDirectory`] which matches its signature, and which the template accepts.
+///Yields an error if the signature is malformed, or if an instantiated [`FuncImpl`] doesn't
+///have the function type that it was instantiated for.
+pub fn instantiate_template(template : &dyn PrimitiveTemplate,
+                            type_info_directory : &TypeInfoDirectory) -> Result<Vec<Box<dyn FuncImpl>>, FetishError> {
+    let signature = TypeExpression::parse(&template.get_signature())?;
+    let mut result = Vec::new();
+    for (func_type_id, assignment) in find_type_assignments(&signature, type_info_directory) {
+        if let Option::Some(func_impl) = template.instantiate(type_info_directory, &assignment) {
+            let mut actual_type_id = func_impl.ret_type();
+            for arg_type_id in func_impl.required_arg_types().iter().rev() {
+                actual_type_id = type_info_directory.try_get_func_type_id(*arg_type_id, actual_type_id)?;
+            }
+            if (actual_type_id != func_type_id) {
+                return Result::Err(FetishError::TypeMismatch {
+                    expected : func_type_id,
+                    actual : actual_type_id
+                });
+            }
+            result.push(func_impl);
+        }
+    }
+    Result::Ok(result)
+}
+
+fn get_assigned(assignment : &TypeAssignment, name : &str) -> Option<TypeId> {
+    assignment.get(name).copied()
+}
+
+fn get_vector(type_info_directory : &TypeInfoDirectory, assignment : &TypeAssignment, name : &str) -> Option<TypeId> {
+    get_assigned(assignment, name).filter(|type_id| type_info_directory.is_vector_type(*type_id))
+}
+
+fn get_scalar(type_info_directory : &TypeInfoDirectory, assignment : &TypeAssignment, name : &str) -> Option<TypeId> {
+    get_vector(type_info_directory, assignment, name)
+        .filter(|type_id| type_info_directory.get_dimension(*type_id) == 1)
+}
+
+///[`PrimitiveTemplate`] for [`ComposeImpl`], of signature `(b -> c) -> (a -> b) -> a -> c`.
+#[derive(Clone)]
+pub struct ComposeTemplate {}
+
+impl PrimitiveTemplate for ComposeTemplate {
+    fn get_name(&self) -> String {
+        String::from("compose")
+    }
+    fn get_signature(&self) -> String {
+        String::from("(b -> c) -> (a -> b) -> a -> c")
+    }
+    fn instantiate(&self, type_info_directory : &TypeInfoDirectory,
+                   assignment : &TypeAssignment) -> Option<Box<dyn FuncImpl>> {
+        let in_type = get_assigned(assignment, "a")?;
+        let middle_type = get_assigned(assignment, "b")?;
+        let ret_type = get_assigned(assignment, "c")?;
+        let compose_impl = ComposeImpl::named("compose", type_info_directory, in_type, middle_type, ret_type).ok()?;
+        Option::Some(Box::new(compose_impl))
+    }
+}
+
+///[`PrimitiveTemplate`] for [`ConstImpl`], of signature `a -> b -> a`.
+#[derive(Clone)]
+pub struct ConstTemplate {}
+
+impl PrimitiveTemplate for ConstTemplate {
+    fn get_name(&self) -> String {
+        String::from("const")
+    }
+    fn get_signature(&self) -> String {
+        String::from("a -> b -> a")
+    }
+    fn instantiate(&self, _type_info_directory : &TypeInfoDirectory,
+                   assignment : &TypeAssignment) -> Option<Box<dyn FuncImpl>> {
+        Option::Some(Box::new(ConstImpl::new(get_assigned(assignment, "a")?, get_assigned(assignment, "b")?)))
+    }
+}
+
+///[`PrimitiveTemplate`] for [`FillImpl`], of signature `s -> v`, where `s` must be
+///a one-dimensional vector type and `v` must be a vector type.
+#[derive(Clone)]
+pub struct FillTemplate {}
+
+impl PrimitiveTemplate for FillTemplate {
+    fn get_name(&self) -> String {
+        String::from("fill")
+    }
+    fn get_signature(&self) -> String {
+        String::from("s -> v")
+    }
+    fn instantiate(&self, type_info_directory : &TypeInfoDirectory,
+                   assignment : &TypeAssignment) -> Option<Box<dyn FuncImpl>> {
+        Option::Some(Box::new(FillImpl {
+            scalar_type : get_scalar(type_info_directory, assignment, "s")?,
+            vector_type : get_vector(type_info_directory, assignment, "v")?
+        }))
+    }
+}
+
+///[`PrimitiveTemplate`] for [`MapImpl`], of signature `(s -> s) -> v -> v`, where `s` must be
+///a one-dimensional vector type and `v` must be a vector type.
+#[derive(Clone)]
+pub struct MapTemplate {}
+
+impl PrimitiveTemplate for MapTemplate {
+    fn get_name(&self) -> String {
+        String::from("map")
+    }
+    fn get_signature(&self) -> String {
+        String::from("(s -> s) -> v -> v")
+    }
+    fn instantiate(&self, type_info_directory : &TypeInfoDirectory,
+                   assignment : &TypeAssignment) -> Option<Box<dyn FuncImpl>> {
+        let scalar_type = get_scalar(type_info_directory, assignment, "s")?;
+        Option::Some(Box::new(MapImpl {
+            scalar_type,
+            unary_scalar_func_type : type_info_directory.try_get_func_type_id(scalar_type, scalar_type).ok()?,
+            vector_type : get_vector(type_info_directory, assignment, "v")?
+        }))
+    }
+}
+
+///[`PrimitiveTemplate`] for [`ReduceImpl`], of signature `(s -> s -> s) -> s -> v -> s`,
+///where `s` must be a one-dimensional vector type and `v` must be a vector type.
+#[derive(Clone)]
+pub struct ReduceTemplate {}
+
+impl PrimitiveTemplate for ReduceTemplate {
+    fn get_name(&self) -> String {
+        String::from("reduce")
+    }
+    fn get_signature(&self) -> String {
+        String::from("(s -> s -> s) -> s -> v -> s")
+    }
+    fn instantiate(&self, type_info_directory : &TypeInfoDirectory,
+                   assignment : &TypeAssignment) -> Option<Box<dyn FuncImpl>> {
+        let scalar_type = get_scalar(type_info_directory, assignment, "s")?;
+        let unary_scalar_func_type = type_info_directory.try_get_func_type_id(scalar_type, scalar_type).ok()?;
+        Option::Some(Box::new(ReduceImpl {
+            binary_scalar_func_type : type_info_directory.try_get_func_type_id(scalar_type, unary_scalar_func_type).ok()?,
+            scalar_type,
+            vector_type : get_vector(type_info_directory, assignment, "v")?
+        }))
+    }
+}
+
+///[`PrimitiveTemplate`] for [`RotateImpl`], of signature `v -> v`, where `v` must be a vector type.
+#[derive(Clone)]
+pub struct RotateTemplate {}
+
+impl PrimitiveTemplate for RotateTemplate {
+    fn get_name(&self) -> String {
+        String::from("rotate")
+    }
+    fn get_signature(&self) -> String {
+        String::from("v -> v")
+    }
+    fn instantiate(&self, type_info_directory : &TypeInfoDirectory,
+                   assignment : &TypeAssignment) -> Option<Box<dyn FuncImpl>> {
+        Option::Some(Box::new(RotateImpl {
+            vector_type : get_vector(type_info_directory, assignment, "v")?
+        }))
+    }
+}
+
+///[`PrimitiveTemplate`] for [`ConcatImpl`], of signature `u -> v -> w`, where `u`, `v` and `w`
+///must be vector types such that the dimension of `w` is the sum of the dimensions of `u` and `v`.
+#[derive(Clone)]
+pub struct ConcatTemplate {}
+
+impl PrimitiveTemplate for ConcatTemplate {
+    fn get_name(&self) -> String {
+        String::from("concat")
+    }
+    fn get_signature(&self) -> String {
+        String::from("u -> v -> w")
+    }
+    fn instantiate(&self, type_info_directory : &TypeInfoDirectory,
+                   assignment : &TypeAssignment) -> Option<Box<dyn FuncImpl>> {
+        let first_type = get_vector(type_info_directory, assignment, "u")?;
+        let second_type = get_vector(type_info_directory, assignment, "v")?;
+        let vector_type = get_vector(type_info_directory, assignment, "w")?;
+        if (type_info_directory.get_dimension(first_type) + type_info_directory.get_dimension(second_type) !=
+            type_info_directory.get_dimension(vector_type)) {
+            return Option::None;
+        }
+        Option::Some(Box::new(ConcatImpl {
+            first_type,
+            second_type,
+            vector_type
+        }))
+    }
+}
+
+///[`PrimitiveTemplate`] for [`SliceImpl`]s starting at the given index, of signature `v -> w`,
+///where `v` and `w` must be vector types such that `w` has fewer dimensions than `v`, and
+///a slice of `w`'s dimension starting at the given index lies within `v`.
+#[derive(Clone)]
+pub struct SliceTemplate {
+    pub start : usize
+}
+
+impl PrimitiveTemplate for SliceTemplate {
+    fn get_name(&self) -> String {
+        format!("slice{}", self.start)
+    }
+    fn get_signature(&self) -> String {
+        String::from("v -> w")
+    }
+    fn instantiate(&self, type_info_directory : &TypeInfoDirectory,
+                   assignment : &TypeAssignment) -> Option<Box<dyn FuncImpl>> {
+        let vector_type = get_vector(type_info_directory, assignment, "v")?;
+        let slice_type = get_vector(type_info_directory, assignment, "w")?;
+        let vector_dim = type_info_directory.get_dimension(vector_type);
+        let slice_dim = type_info_directory.get_dimension(slice_type);
+        if (slice_dim >= vector_dim || self.start + slice_dim > vector_dim) {
+            return Option::None;
+        }
+        Option::Some(Box::new(SliceImpl {
+            vector_type,
+            slice_type,
+            start : self.start
+        }))
+    }
+}
+
+///[`PrimitiveTemplate`] for [`ReverseImpl`], of signature `v -> v`, where `v` must be a vector type.
+#[derive(Clone)]
+pub struct ReverseTemplate {}
+
+impl PrimitiveTemplate for ReverseTemplate {
+    fn get_name(&self) -> String {
+        String::from("reverse")
+    }
+    fn get_signature(&self) -> String {
+        String::from("v -> v")
+    }
+    fn instantiate(&self, type_info_directory : &TypeInfoDirectory,
+                   assignment : &TypeAssignment) -> Option<Box<dyn FuncImpl>> {
+        Option::Some(Box::new(ReverseImpl {
+            vector_type : get_vector(type_info_directory, assignment, "v")?
+        }))
+    }
+}
+
+///[`PrimitiveTemplate`] for [`DotImpl`], of signature `v -> v -> s`, where `s` must be
+///a one-dimensional vector type and `v` must be a vector type.
+#[derive(Clone)]
+pub struct DotTemplate {}
+
+impl PrimitiveTemplate for DotTemplate {
+    fn get_name(&self) -> String {
+        String::from("dot")
+    }
+    fn get_signature(&self) -> String {
+        String::from("v -> v -> s")
+    }
+    fn instantiate(&self, type_info_directory : &TypeInfoDirectory,
+                   assignment : &TypeAssignment) -> Option<Box<dyn FuncImpl>> {
+        Option::Some(Box::new(DotImpl {
+            vector_type : get_vector(type_info_directory, assignment, "v")?,
+            scalar_type : get_scalar(type_info_directory, assignment, "s")?
+        }))
+    }
+}
+
+///[`PrimitiveTemplate`] for [`ZipWithImpl`], of signature `(s -> s -> s) -> v -> v -> v`,
+///where `s` must be a one-dimensional vector type and `v` must be a vector type.
+#[derive(Clone)]
+pub struct ZipWithTemplate {}
+
+impl PrimitiveTemplate for ZipWithTemplate {
+    fn get_name(&self) -> String {
+        String::from("zipWith")
+    }
+    fn get_signature(&self) -> String {
+        String::from("(s -> s -> s) -> v -> v -> v")
+    }
+    fn instantiate(&self, type_info_directory : &TypeInfoDirectory,
+                   assignment : &TypeAssignment) -> Option<Box<dyn FuncImpl>> {
+        let scalar_type = get_scalar(type_info_directory, assignment, "s")?;
+        let unary_scalar_func_type = type_info_directory.try_get_func_type_id(scalar_type, scalar_type).ok()?;
+        Option::Some(Box::new(ZipWithImpl {
+            binary_scalar_func_type : type_info_directory.try_get_func_type_id(scalar_type, unary_scalar_func_type).ok()?,
+            scalar_type,
+            vector_type : get_vector(type_info_directory, assignment, "v")?
+        }))
+    }
+}
+
+///[`PrimitiveTemplate`] for [`ScanImpl`], of signature `(s -> s -> s) -> s -> v -> v`,
+///where `s` must be a one-dimensional vector type and `v` must be a vector type.
+#[derive(Clone)]
+pub struct ScanTemplate {}
+
+impl PrimitiveTemplate for ScanTemplate {
+    fn get_name(&self) -> String {
+        String::from("scan")
+    }
+    fn get_signature(&self) -> String {
+        String::from("(s -> s -> s) -> s -> v -> v")
+    }
+    fn instantiate(&self, type_info_directory : &TypeInfoDirectory,
+                   assignment : &TypeAssignment) -> Option<Box<dyn FuncImpl>> {
+        let scalar_type = get_scalar(type_info_directory, assignment, "s")?;
+        let unary_scalar_func_type = type_info_directory.try_get_func_type_id(scalar_type, scalar_type).ok()?;
+        Option::Some(Box::new(ScanImpl {
+            binary_scalar_func_type : type_info_directory.try_get_func_type_id(scalar_type, unary_scalar_func_type).ok()?,
+            scalar_type,
+            vector_type : get_vector(type_info_directory, assignment, "v")?
+        }))
+    }
+}
+
+///[`PrimitiveTemplate`] for [`IndexImpl`], of signature `s -> v -> s`, where `s` must be
+///a one-dimensional vector type and `v` must be a vector type.
+#[derive(Clone)]
+pub struct IndexTemplate {}
+
+impl PrimitiveTemplate for IndexTemplate {
+    fn get_name(&self) -> String {
+        String::from("index")
+    }
+    fn get_signature(&self) -> String {
+        String::from("s -> v -> s")
+    }
+    fn instantiate(&self, type_info_directory : &TypeInfoDirectory,
+                   assignment : &TypeAssignment) -> Option<Box<dyn FuncImpl>> {
+        Option::Some(Box::new(IndexImpl {
+            scalar_type : get_scalar(type_info_directory, assignment, "s")?,
+            vector_type : get_vector(type_info_directory, assignment, "v")?
+        }))
+    }
+}
+
+///[`PrimitiveTemplate`] for [`RotateByImpl`], of signature `s -> v -> v`, where `s` must be
+///a one-dimensional vector type and `v` must be a vector type.
+#[derive(Clone)]
+pub struct RotateByTemplate {}
+
+impl PrimitiveTemplate for RotateByTemplate {
+    fn get_name(&self) -> String {
+        String::from("rotateBy")
+    }
+    fn get_signature(&self) -> String {
+        String::from("s -> v -> v")
+    }
+    fn instantiate(&self, type_info_directory : &TypeInfoDirectory,
+                   assignment : &TypeAssignment) -> Option<Box<dyn FuncImpl>> {
+        Option::Some(Box::new(RotateByImpl {
+            scalar_type : get_scalar(type_info_directory, assignment, "s")?,
+            vector_type : get_vector(type_info_directory, assignment, "v")?
+        }))
+    }
+}
+
+///[`PrimitiveTemplate`] for [`IfPositiveImpl`], of signature `s -> a -> a -> a`, where `s` must be
+///a one-dimensional vector type.
+#[derive(Clone)]
+pub struct IfPositiveTemplate {}
+
+impl PrimitiveTemplate for IfPositiveTemplate {
+    fn get_name(&self) -> String {
+        String::from("ifPositive")
+    }
+    fn get_signature(&self) -> String {
+        String::from("s -> a -> a -> a")
+    }
+    fn instantiate(&self, type_info_directory : &TypeInfoDirectory,
+                   assignment : &TypeAssignment) -> Option<Box<dyn FuncImpl>> {
+        Option::Some(Box::new(IfPositiveImpl {
+            scalar_type : get_scalar(type_info_directory, assignment, "s")?,
+            ret_type : get_assigned(assignment, "a")?
+        }))
+    }
+}
+
+///[`PrimitiveTemplate`] for [`SImpl`], of signature `(a -> b -> c) -> (a -> b) -> a -> c`.
+#[derive(Clone)]
+pub struct STemplate {}
+
+impl PrimitiveTemplate for STemplate {
+    fn get_name(&self) -> String {
+        String::from("S")
+    }
+    fn get_signature(&self) -> String {
+        String::from("(a -> b -> c) -> (a -> b) -> a -> c")
+    }
+    fn instantiate(&self, type_info_directory : &TypeInfoDirectory,
+                   assignment : &TypeAssignment) -> Option<Box<dyn FuncImpl>> {
+        let s_impl = SImpl::new(type_info_directory, get_assigned(assignment, "a")?,
+                                get_assigned(assignment, "b")?, get_assigned(assignment, "c")?).ok()?;
+        Option::Some(Box::new(s_impl))
+    }
+}
+
+///[`PrimitiveTemplate`] for [`ConstImpl`] going by the name `K`, of signature `a -> b -> a`.
+#[derive(Clone)]
+pub struct KTemplate {}
+
+impl PrimitiveTemplate for KTemplate {
+    fn get_name(&self) -> String {
+        String::from("K")
+    }
+    fn get_signature(&self) -> String {
+        String::from("a -> b -> a")
+    }
+    fn instantiate(&self, _type_info_directory : &TypeInfoDirectory,
+                   assignment : &TypeAssignment) -> Option<Box<dyn FuncImpl>> {
+        Option::Some(Box::new(ConstImpl {
+            name : self.get_name(),
+            ..ConstImpl::new(get_assigned(assignment, "a")?, get_assigned(assignment, "b")?)
+        }))
+    }
+}
+
+///[`PrimitiveTemplate`] for [`IImpl`], of signature `a -> a`.
+#[derive(Clone)]
+pub struct ITemplate {}
+
+impl PrimitiveTemplate for ITemplate {
+    fn get_name(&self) -> String {
+        String::from("I")
+    }
+    fn get_signature(&self) -> String {
+        String::from("a -> a")
+    }
+    fn instantiate(&self, _type_info_directory : &TypeInfoDirectory,
+                   assignment : &TypeAssignment) -> Option<Box<dyn FuncImpl>> {
+        Option::Some(Box::new(IImpl {
+            type_id : get_assigned(assignment, "a")?
+        }))
+    }
+}
+
+///[`PrimitiveTemplate`] for [`ComposeImpl`] going by the name `B`, of signature `(b -> c) -> (a -> b) -> a -> c`.
+#[derive(Clone)]
+pub struct BTemplate {}
+
+impl PrimitiveTemplate for BTemplate {
+    fn get_name(&self) -> String {
+        String::from("B")
+    }
+    fn get_signature(&self) -> String {
+        String::from("(b -> c) -> (a -> b) -> a -> c")
+    }
+    fn instantiate(&self, type_info_directory : &TypeInfoDirectory,
+                   assignment : &TypeAssignment) -> Option<Box<dyn FuncImpl>> {
+        let b_impl = ComposeImpl::named(&self.get_name(), type_info_directory, get_assigned(assignment, "a")?,
+                                        get_assigned(assignment, "b")?, get_assigned(assignment, "c")?).ok()?;
+        Option::Some(Box::new(b_impl))
+    }
+}
+
+///[`PrimitiveTemplate`] for [`CImpl`], of signature `(a -> b -> c) -> b -> a -> c`.
+#[derive(Clone)]
+pub struct CTemplate {}
+
+impl PrimitiveTemplate for CTemplate {
+    fn get_name(&self) -> String {
+        String::from("C")
+    }
+    fn get_signature(&self) -> String {
+        String::from("(a -> b -> c) -> b -> a -> c")
+    }
+    fn instantiate(&self, type_info_directory : &TypeInfoDirectory,
+                   assignment : &TypeAssignment) -> Option<Box<dyn FuncImpl>> {
+        let c_impl = CImpl::new(type_info_directory, get_assigned(assignment, "a")?,
+                                get_assigned(assignment, "b")?, get_assigned(assignment, "c")?).ok()?;
+        Option::Some(Box::new(c_impl))
+    }
+}
+
+///[`PrimitiveTemplate`] for [`CImpl`] going by the name `flip`, of signature `(a -> b -> c) -> b -> a -> c`.
+#[derive(Clone)]
+pub struct FlipTemplate {}
+
+impl PrimitiveTemplate for FlipTemplate {
+    fn get_name(&self) -> String {
+        String::from("flip")
+    }
+    fn get_signature(&self) -> String {
+        String::from("(a -> b -> c) -> b -> a -> c")
+    }
+    fn instantiate(&self, type_info_directory : &TypeInfoDirectory,
+                   assignment : &TypeAssignment) -> Option<Box<dyn FuncImpl>> {
+        let flip_impl = CImpl::named(&self.get_name(), type_info_directory, get_assigned(assignment, "a")?,
+                                     get_assigned(assignment, "b")?, get_assigned(assignment, "c")?).ok()?;
+        Option::Some(Box::new(flip_impl))
+    }
+}
+
+///[`PrimitiveTemplate`] for [`IterateImpl`], of signature `s -> (a -> a) -> a -> a`,
+///where `s` must be a scalar type.
+#[derive(Clone)]
+pub struct IterateTemplate {}
+
+impl PrimitiveTemplate for IterateTemplate {
+    fn get_name(&self) -> String {
+        String::from("iterate")
+    }
+    fn get_signature(&self) -> String {
+        String::from("s -> (a -> a) -> a -> a")
+    }
+    fn instantiate(&self, type_info_directory : &TypeInfoDirectory,
+                   assignment : &TypeAssignment) -> Option<Box<dyn FuncImpl>> {
+        let iterate_impl = IterateImpl::new(type_info_directory, get_scalar(type_info_directory, assignment, "s")?,
+                                            get_assigned(assignment, "a")?).ok()?;
+        Option::Some(Box::new(iterate_impl))
+    }
+}
+
+///[`PrimitiveTemplate`] for [`FixImpl`], of signature `s -> ((a -> b) -> a -> b) -> a -> b`,
+///where `s` must be a scalar type.
+#[derive(Clone)]
+pub struct FixTemplate {}
+
+impl PrimitiveTemplate for FixTemplate {
+    fn get_name(&self) -> String {
+        String::from("fix")
+    }
+    fn get_signature(&self) -> String {
+        String::from("s -> ((a -> b) -> a -> b) -> a -> b")
+    }
+    fn instantiate(&self, type_info_directory : &TypeInfoDirectory,
+                   assignment : &TypeAssignment) -> Option<Box<dyn FuncImpl>> {
+        let fix_impl = FixImpl::new(type_info_directory, get_scalar(type_info_directory, assignment, "s")?,
+                                    get_assigned(assignment, "a")?, get_assigned(assignment, "b")?).ok()?;
+        Option::Some(Box::new(fix_impl))
+    }
+}
+
+///Yields [`PrimitiveTemplate`]s for each of the `S`, `K`, `I`, `B` and `C` combinators,
+///which lambda abstractions in [`crate::term_expression::TermExpression`]s are compiled to.
+pub fn combinator_templates() -> Vec<Box<dyn PrimitiveTemplate>> {
+    vec![Box::new(STemplate {}), Box::new(KTemplate {}), Box::new(ITemplate {}),
+         Box::new(BTemplate {}), Box::new(CTemplate {})]
+}
+
+///Yields [`PrimitiveTemplate`]s for each of the structural vector primitives `concat`, `slice0`
+///(taking leading elements, see [`SliceTemplate`] for other starting indices),
+///`reverse`, `dot`, `zipWith`, `scan`, `index` and `rotateBy`.
+pub fn structural_vector_templates() -> Vec<Box<dyn PrimitiveTemplate>> {
+    vec![Box::new(ConcatTemplate {}), Box::new(SliceTemplate { start : 0 }), Box::new(ReverseTemplate {}),
+         Box::new(DotTemplate {}), Box::new(ZipWithTemplate {}), Box::new(ScanTemplate {}),
+         Box::new(IndexTemplate {}), Box::new(RotateByTemplate {})]
+}
+
+///Yields [`PrimitiveTemplate`]s for each of the higher-order primitives `flip`, `iterate`
+///and `fix`.
+pub fn higher_order_templates() -> Vec<Box<dyn PrimitiveTemplate>> {
+    vec![Box::new(FlipTemplate {}), Box::new(IterateTemplate {}), Box::new(FixTemplate {})]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context_builder::*;
+    use crate::primitive_directory::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn compose_is_instantiated_for_every_matching_type() {
+        let mut type_info_directory = get_test_type_info_directory();
+        let vector_compose_t = type_info_directory.parse_type("(Vec2 -> Vec2) -> (Vec2 -> Vec2) -> Vec2 -> Vec2").unwrap();
+        let mixed_compose_t = type_info_directory.parse_type("(Vec2 -> Scalar) -> (Scalar -> Vec2) -> Scalar -> Scalar").unwrap();
+
+        let instances = instantiate_template(&ComposeTemplate {}, &type_info_directory).unwrap();
+        let instance_types : Vec<TypeId> = instances.iter()
+                                                    .map(|instance| instance.func_type(&type_info_directory))
+                                                    .collect();
+        assert_eq!(instance_types, vec![vector_compose_t, mixed_compose_t]);
+    }
+
+    #[test]
+    fn templates_may_reject_assignments() {
+        let mut type_info_directory = get_test_type_info_directory();
+        let vector_func_t = type_info_directory.parse_type("Vec2 -> Vec2").unwrap();
+        type_info_directory.parse_type("(Vec2 -> Vec2) -> (Vec2 -> Vec2)").unwrap();
+
+        let signature = TypeExpression::parse(&RotateTemplate {}.get_signature()).unwrap();
+        assert_eq!(find_type_assignments(&signature, &type_info_directory).len(), 2);
+
+        let instances = instantiate_template(&RotateTemplate {}, &type_info_directory).unwrap();
+        assert_eq!(instances.len(), 1);
+        assert_eq!(instances[0].func_type(&type_info_directory), vector_func_t);
+    }
+
+    #[test]
+    fn structural_templates_check_dimensions() {
+        let mut type_info_directory = get_test_type_info_directory();
+        let vector3_t = type_info_directory.add(Type::VecType(3));
+        type_info_directory.add_type_alias("Vec3", vector3_t).unwrap();
+        for type_text in ["Scalar -> Vec2 -> Vec3", "Vec2 -> Scalar -> Vec3", "Scalar -> Scalar -> Vec2",
+                          "Vec2 -> Vec2 -> Vec2", "Scalar -> Vec3 -> Vec2", "Scalar -> Vec2 -> Vec2"].iter() {
+            type_info_directory.parse_type(type_text).unwrap();
+        }
+        assert_eq!(instantiate_template(&ConcatTemplate {}, &type_info_directory).unwrap().len(), 3);
+
+        let slices = instantiate_template(&SliceTemplate { start : 1 }, &type_info_directory).unwrap();
+        assert_eq!(slices.len(), 1);
+        assert_eq!(slices[0].get_name(), "slice1");
+        assert_eq!(slices[0].func_type(&type_info_directory), type_info_directory.parse_type("Vec3 -> Vec2").unwrap());
+        assert!(instantiate_template(&SliceTemplate { start : 2 }, &type_info_directory).unwrap().is_empty());
+    }
+
+    #[test]
+    fn templates_skip_missing_intermediate_function_types() {
+        let type_info_directory = get_test_type_info_directory();
+        let scalar_t = type_info_directory.get_type_alias("Scalar").unwrap();
+        let vector_t = type_info_directory.get_type_alias("Vec2").unwrap();
+        let mut assignment = TypeAssignment::new();
+        for (name, type_id) in [("a", scalar_t), ("b", vector_t), ("c", scalar_t),
+                                ("s", scalar_t), ("v", vector_t)].iter() {
+            assignment.insert(String::from(*name), *type_id);
+        }
+        let templates : Vec<Box<dyn PrimitiveTemplate>> = vec![Box::new(ComposeTemplate {}), Box::new(MapTemplate {}),
+                                                               Box::new(ReduceTemplate {}), Box::new(ZipWithTemplate {}),
+                                                               Box::new(ScanTemplate {})];
+        for template in templates.iter() {
+            assert!(template.instantiate(&type_info_directory, &assignment).is_none());
+        }
+    }
+
+    #[test]
+    fn built_context_contains_instantiated_templates() {
+        let mut builder = get_test_vector_only_context_builder();
+        builder.add_func_type(TEST_VECTOR_T, TEST_VECTOR_T).unwrap();
+        add_test_function_space(&mut builder, TEST_VECTOR_FUNC_T, 8);
+        builder.add_primitive(Box::new(RotateImpl {
+            vector_type : TEST_VECTOR_T
+        }));
+        builder.add_primitive_template(Box::new(RotateTemplate {})).unwrap();
+        let ctxt = builder.build().unwrap();
+
+        //The hand-added rotation isn't duplicated by the template
+        let primitive_type_space = ctxt.primitive_directory.primitive_type_spaces.get(&TEST_VECTOR_FUNC_T).unwrap();
+        assert_eq!(primitive_type_space.terms.len(), 1);
+        assert_eq!(primitive_type_space.terms[0].get_name(), "rotate");
+    }
+
+    #[test]
+    fn malformed_signatures_are_rejected() {
+        struct BadTemplate {}
+        impl PrimitiveTemplate for BadTemplate {
+            fn get_name(&self) -> String {
+                String::from("bad")
+            }
+            fn get_signature(&self) -> String {
+                String::from("a ->")
+            }
+            fn instantiate(&self, _type_info_directory : &TypeInfoDirectory,
+                           _assignment : &TypeAssignment) -> Option<Box<dyn FuncImpl>> {
+                Option::None
+            }
+        }
+        let mut builder = ContextBuilder::new(TEST_SEED);
+        assert!(builder.add_primitive_template(Box::new(BadTemplate {})).is_err());
+    }
+}
@@ -1,14 +1,65 @@
 use crate::prior_info::*;
 use std::collections::HashMap;
 use crate::type_id::*;
+use crate::context::*;
+use crate::space_info::*;
+use crate::context_builder::*;
+use crate::fetish_error::*;
+
+use serde::{Serialize, Deserialize};
 
 ///A directory of `PriorInfo`s, indexed by [`TypeId`].
 pub struct PriorDirectory {
     pub priors : HashMap<TypeId, PriorInfo>
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SerializedPriorDirectory {
+    pub priors : HashMap<TypeId, SerializedPriorInfo>
+}
+
+impl SerializedPriorDirectory {
+    ///Restores the [`PriorDirectory`] for the given directories, checking that every function
+    ///type has a [`PriorInfo`] which was tabulated at the dimensions of its spaces
+    ///(see [`SerializedPriorInfo::deserialize`]).
+    pub fn deserialize(mut self, type_info_directory : &TypeInfoDirectory,
+                       space_info_directory : &SpaceInfoDirectory) -> Result<PriorDirectory, FetishError> {
+        let mut priors = HashMap::new();
+        for (type_id, prior_info) in self.priors.drain() {
+            priors.insert(type_id, prior_info.deserialize(type_id, type_info_directory, space_info_directory)?);
+        }
+        for type_id in 0..type_info_directory.get_total_num_types() {
+            if (type_info_directory.is_func_type(type_id) && !priors.contains_key(&type_id)) {
+                return Result::Err(ContextBuildError::MissingPrior(type_id).into());
+            }
+        }
+        Result::Ok(PriorDirectory {
+            priors
+        })
+    }
+}
+
 impl PriorDirectory {
+    ///Gets the [`PriorInfo`] for the given function type, assuming that it exists.
+    ///See [`Self::try_get_prior_info`] for a non-panicking version.
     pub fn get_prior_info(&self, type_id : TypeId) -> &PriorInfo {
-        self.priors.get(&type_id).unwrap()
+        self.try_get_prior_info(type_id).unwrap()
+    }
+
+    ///Gets the [`PriorInfo`] for the given function type, or [`FetishError::MissingPrior`]
+    ///if there is none.
+    pub fn try_get_prior_info(&self, type_id : TypeId) -> Result<&PriorInfo, FetishError> {
+        self.priors.get(&type_id).ok_or(FetishError::MissingPrior(type_id))
+    }
+
+    ///Serializes this [`PriorDirectory`], which is assumed to belong to the given [`Context`].
+    ///See [`PriorInfo::serialize`].
+    pub fn serialize(&self, ctxt : &Context) -> SerializedPriorDirectory {
+        let priors = self.priors.iter()
+                         .map(|(type_id, prior_info)| (*type_id, prior_info.serialize(*type_id, ctxt)))
+                         .collect();
+        SerializedPriorDirectory {
+            priors
+        }
     }
 }
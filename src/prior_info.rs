@@ -1,6 +1,85 @@
 use crate::prior_specification::*;
+use crate::type_id::*;
+use crate::context::*;
+use crate::space_info::*;
+use crate::function_space_info::*;
+use crate::fetish_error::*;
+
+use serde::{Serialize, Deserialize};
 
 pub struct PriorInfo {
     pub model_prior_specification : Box<dyn PriorSpecification>,
     pub elaborator_prior_specification : Box<dyn PriorSpecification>
 }
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SerializedPriorInfo {
+    pub model_prior_specification : TabulatedPriorSpecification,
+    pub elaborator_prior_specification : TabulatedPriorSpecification
+}
+
+impl SerializedPriorInfo {
+    ///Restores the [`PriorInfo`] for the given function [`TypeId`], checking that its
+    ///[`PriorSpecification`]s were tabulated at every dimension that the given directories
+    ///will query them at (see [`PriorInfo::serialize`]).
+    pub fn deserialize(self, func_type_id : TypeId, type_info_directory : &TypeInfoDirectory,
+                       space_info_directory : &SpaceInfoDirectory) -> Result<PriorInfo, FetishError> {
+        let (feat_dims, out_dims) = get_model_dimensions(func_type_id, type_info_directory, space_info_directory)?;
+        self.model_prior_specification.check_tabulated(func_type_id, feat_dims, out_dims)?;
+        let elaborator_dimensions = get_elaborator_dimensions(func_type_id, space_info_directory);
+        if let Option::Some((sketched_dimension, kernel_basis_dimension)) = elaborator_dimensions {
+            self.elaborator_prior_specification.check_tabulated(func_type_id, sketched_dimension,
+                                                                kernel_basis_dimension)?;
+        }
+        Result::Ok(PriorInfo {
+            model_prior_specification : Box::new(self.model_prior_specification),
+            elaborator_prior_specification : Box::new(self.elaborator_prior_specification)
+        })
+    }
+}
+
+//Yields the (feature, output) dimensions at which the model of the given function type queries its prior
+fn get_model_dimensions(func_type_id : TypeId, type_info_directory : &TypeInfoDirectory,
+                        space_info_directory : &SpaceInfoDirectory) -> Result<(usize, usize), FetishError> {
+    let (arg_type_id, ret_type_id) = type_info_directory.try_get_arg_and_ret_type_ids(func_type_id)?;
+    let func_space_info = FunctionSpaceInfo {
+        in_feat_info : space_info_directory.get_feature_space_info(arg_type_id),
+        out_feat_info : space_info_directory.get_feature_space_info(ret_type_id)
+    };
+    Result::Ok((func_space_info.get_feature_dimensions(), func_space_info.get_output_dimensions()))
+}
+
+//Yields the (feature, output) dimensions at which the elaborator of the given function type
+//queries its prior, if the type has an elaborator
+fn get_elaborator_dimensions(func_type_id : TypeId, space_info_directory : &SpaceInfoDirectory) -> Option<(usize, usize)> {
+    let sketcher = space_info_directory.get_feature_space_info(func_type_id).sketcher.as_ref();
+    sketcher.and_then(|sketcher| {
+        sketcher.get_kernel_matrix().as_ref()
+                .map(|kernel_mat| (sketcher.get_output_dimension(), kernel_mat.shape()[1]))
+    })
+}
+
+impl PriorInfo {
+    ///Serializes this [`PriorInfo`] for the given function [`TypeId`] by tabulating
+    ///its [`PriorSpecification`]s at the dimensions that the given [`Context`] uses
+    ///for [`crate::term_model::TermModel`]s and [`crate::elaborator::Elaborator`]s of that type.
+    ///If the type has no sketcher with a kernel, and hence no elaborator, the elaborator's
+    ///[`PriorSpecification`] is never queried, and so is left untabulated.
+    pub fn serialize(&self, func_type_id : TypeId, ctxt : &Context) -> SerializedPriorInfo {
+        let (feat_dims, out_dims) = get_model_dimensions(func_type_id, &ctxt.type_info_directory,
+                                                         &ctxt.space_info_directory).unwrap();
+        let model_prior_specification = TabulatedPriorSpecification::tabulate(
+                                            self.model_prior_specification.as_ref(), feat_dims, out_dims);
+        let elaborator_dimensions = get_elaborator_dimensions(func_type_id, &ctxt.space_info_directory);
+        let elaborator_prior_specification = match (elaborator_dimensions) {
+            Option::Some((sketched_dimension, kernel_basis_dimension)) =>
+                TabulatedPriorSpecification::tabulate(self.elaborator_prior_specification.as_ref(),
+                                                      sketched_dimension, kernel_basis_dimension),
+            Option::None => TabulatedPriorSpecification::default()
+        };
+        SerializedPriorInfo {
+            model_prior_specification,
+            elaborator_prior_specification
+        }
+    }
+}
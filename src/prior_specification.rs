@@ -1,3 +1,10 @@
+use std::collections::HashMap;
+use crate::type_id::*;
+use crate::context_builder::*;
+use crate::fetish_error::*;
+
+use serde::{Serialize, Deserialize};
+
 ///A generic specification for MNIW priors of arbitrary dimensionality.
 pub trait PriorSpecification {
     ///Returns the scaling factor to apply to the prior input precision
@@ -9,3 +16,101 @@ pub trait PriorSpecification {
     ///Returns the value for little_v, the number of observations of output covariance
     fn get_out_pseudo_observations(&self, out_dims : usize) -> f32;
 }
+
+///A [`PriorSpecification`] which only knows the values that some other
+///[`PriorSpecification`] took on at a fixed collection of dimensions. Since a
+///[`crate::context::Context`] only ever queries the [`PriorSpecification`]s for a given
+///type at the dimensions of that type's spaces, this is enough to serialize
+///arbitrary [`PriorSpecification`]s without loss. Restoring a serialized [`crate::context::Context`]
+///checks that the dimensions it needs were tabulated (see [`Self::check_tabulated`]), so querying
+///any other dimension is a logic error, and panics.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct TabulatedPriorSpecification {
+    in_precision_multipliers : HashMap<usize, f32>,
+    out_covariance_multipliers : HashMap<usize, f32>,
+    out_pseudo_observations : HashMap<usize, f32>
+}
+
+impl TabulatedPriorSpecification {
+    ///Records the values of the given [`PriorSpecification`] at the given
+    ///input feature dimension and output dimension.
+    pub fn tabulate(prior_specification : &dyn PriorSpecification,
+                    feat_dims : usize, out_dims : usize) -> TabulatedPriorSpecification {
+        let mut in_precision_multipliers = HashMap::new();
+        let mut out_covariance_multipliers = HashMap::new();
+        let mut out_pseudo_observations = HashMap::new();
+
+        in_precision_multipliers.insert(feat_dims, prior_specification.get_in_precision_multiplier(feat_dims));
+        out_covariance_multipliers.insert(out_dims, prior_specification.get_out_covariance_multiplier(out_dims));
+        out_pseudo_observations.insert(out_dims, prior_specification.get_out_pseudo_observations(out_dims));
+
+        TabulatedPriorSpecification {
+            in_precision_multipliers,
+            out_covariance_multipliers,
+            out_pseudo_observations
+        }
+    }
+
+    ///Checks that values were tabulated at the given input feature dimension and output
+    ///dimension, yielding [`ContextBuildError::UntabulatedPrior`] for the given function type if not.
+    pub fn check_tabulated(&self, type_id : TypeId, feat_dims : usize, out_dims : usize) -> Result<(), FetishError> {
+        if (self.in_precision_multipliers.contains_key(&feat_dims) &&
+            self.out_covariance_multipliers.contains_key(&out_dims) &&
+            self.out_pseudo_observations.contains_key(&out_dims)) {
+            Result::Ok(())
+        } else {
+            Result::Err(ContextBuildError::UntabulatedPrior {
+                type_id,
+                feat_dims,
+                out_dims
+            }.into())
+        }
+    }
+}
+
+fn lookup_tabulated(table : &HashMap<usize, f32>, dims : usize, description : &str) -> f32 {
+    match (table.get(&dims)) {
+        Option::Some(value) => *value,
+        Option::None => panic!("no {} was tabulated for {} dimensions", description, dims)
+    }
+}
+
+impl PriorSpecification for TabulatedPriorSpecification {
+    fn get_in_precision_multiplier(&self, feat_dims : usize) -> f32 {
+        lookup_tabulated(&self.in_precision_multipliers, feat_dims, "input precision multiplier")
+    }
+    fn get_out_covariance_multiplier(&self, out_dims : usize) -> f32 {
+        lookup_tabulated(&self.out_covariance_multipliers, out_dims, "output covariance multiplier")
+    }
+    fn get_out_pseudo_observations(&self, out_dims : usize) -> f32 {
+        lookup_tabulated(&self.out_pseudo_observations, out_dims, "output pseudo-observation count")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn tabulated_dimensions_survive_serialization() {
+        let tabulated = TabulatedPriorSpecification::tabulate(&TestPriorSpecification {}, 3, 2);
+        let restored : TabulatedPriorSpecification = serde_json::from_str(&serde_json::to_string(&tabulated).unwrap()).unwrap();
+        let test_prior_specification = TestPriorSpecification {};
+        assert_eq!(restored.get_in_precision_multiplier(3), test_prior_specification.get_in_precision_multiplier(3));
+        assert_eq!(restored.get_out_covariance_multiplier(2), test_prior_specification.get_out_covariance_multiplier(2));
+        assert_eq!(restored.get_out_pseudo_observations(2), test_prior_specification.get_out_pseudo_observations(2));
+    }
+
+    #[test]
+    fn untabulated_dimensions_are_reported() {
+        let tabulated = TabulatedPriorSpecification::tabulate(&TestPriorSpecification {}, 3, 2);
+        assert_eq!(tabulated.check_tabulated(TEST_VECTOR_FUNC_T, 3, 2), Result::Ok(()));
+        assert_eq!(tabulated.check_tabulated(TEST_VECTOR_FUNC_T, 3, 5).err(),
+                   Option::Some(FetishError::ContextBuild(ContextBuildError::UntabulatedPrior {
+                       type_id : TEST_VECTOR_FUNC_T,
+                       feat_dims : 3,
+                       out_dims : 5
+                   })));
+    }
+}
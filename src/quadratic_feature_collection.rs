@@ -12,6 +12,7 @@ use rustfft::FFT;
 use rustfft::num_complex::Complex;
 use rustfft::num_traits::Zero;
 use crate::params::*;
+use rand::prelude::*;
 
 use serde::{Serialize, Deserialize};
 
@@ -37,9 +38,10 @@ pub struct SerializableQuadraticFeatureCollection {
 }
 
 impl SerializableQuadraticFeatureCollection {
-    pub fn new(in_dimensions : usize, out_dimensions : usize, alpha : f32) -> SerializableQuadraticFeatureCollection {
-        let sketch_one = CountSketch::new(in_dimensions, out_dimensions);
-        let sketch_two = CountSketch::new(in_dimensions, out_dimensions);
+    pub fn new<R : Rng + ?Sized>(in_dimensions : usize, out_dimensions : usize, alpha : f32,
+                                 rng : &mut R) -> SerializableQuadraticFeatureCollection {
+        let sketch_one = CountSketch::new(in_dimensions, out_dimensions, rng);
+        let sketch_two = CountSketch::new(in_dimensions, out_dimensions, rng);
         SerializableQuadraticFeatureCollection {
             in_dimensions,
             alpha,
@@ -73,9 +75,10 @@ impl SerializableQuadraticFeatureCollection {
 impl QuadraticFeatureCollection {
     ///Constructs a new [`QuadraticFeatureCollection`] with the given number of input
     ///dimensions, the given scaling factor `alpha`], and the given number of quadratic
-    ///features `out_dimensions`.
-    pub fn new(in_dimensions : usize, out_dimensions : usize, alpha : f32) -> QuadraticFeatureCollection {
-        let serializable = SerializableQuadraticFeatureCollection::new(in_dimensions, out_dimensions, alpha);
+    ///features `out_dimensions`, drawing the underlying [`CountSketch`]es from `rng`.
+    pub fn new<R : Rng + ?Sized>(in_dimensions : usize, out_dimensions : usize, alpha : f32,
+                                 rng : &mut R) -> QuadraticFeatureCollection {
+        let serializable = SerializableQuadraticFeatureCollection::new(in_dimensions, out_dimensions, alpha, rng);
         serializable.deserialize()
     }
 
@@ -183,6 +186,10 @@ impl FeatureCollection for QuadraticFeatureCollection {
     fn get_dimension(&self) -> usize {
         self.sketch_one.get_out_dimensions()
     }
+
+    fn to_serialized(&self) -> SerializedFeatureCollection {
+        SerializedFeatureCollection::Quadratic(self.clone().serialize())
+    }
 }
 
 #[cfg(test)]
@@ -192,7 +199,7 @@ mod tests {
 
     #[test]
     fn empirical_jacobian_is_jacobian() {
-        let quadratic_feature_collection = QuadraticFeatureCollection::new(10, 15, 1.0f32);
+        let quadratic_feature_collection = QuadraticFeatureCollection::new(10, 15, 1.0f32, &mut rand::thread_rng());
         let in_vec = random_vector(10);
         let jacobian = quadratic_feature_collection.get_jacobian(in_vec.view());
         let empirical_jacobian = empirical_jacobian(|x| quadratic_feature_collection.get_features(x),
@@ -202,7 +209,7 @@ mod tests {
 
     #[test]
     fn unoptimized_get_features_is_get_features() {
-        let quadratic_feature_collection = QuadraticFeatureCollection::new(10, 15, 1.0f32);
+        let quadratic_feature_collection = QuadraticFeatureCollection::new(10, 15, 1.0f32, &mut rand::thread_rng());
         let in_vec = random_vector(10);
         let unoptimized = quadratic_feature_collection.unoptimized_get_features(in_vec.view());
         let optimized = quadratic_feature_collection.get_features(in_vec.view());
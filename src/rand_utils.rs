@@ -45,17 +45,22 @@ fn generate_inverse_gamma_random<R : Rng + ?Sized>(rng : &mut R, a : f32, b : f3
 }
 
 ///Generates an inverse-gamma random variable with the given `a` and `b` parameters.
-pub fn gen_inverse_gamma_random(rng : &mut ThreadRng, a : f32, b : f32) -> f32 {
+pub fn gen_inverse_gamma_random<R : Rng + ?Sized>(rng : &mut R, a : f32, b : f32) -> f32 {
     generate_inverse_gamma_random(rng, a, b)
 }
 
+///Generates a vector of `dims` independent standard normal random variables.
+pub fn gen_standard_normal_random<R : Rng + ?Sized>(rng : &mut R, dims : usize) -> Array1<f32> {
+    generate_standard_normal_random(rng, dims)
+}
+
 ///Generates a random vector on the unit `dims`-sphere.
-pub fn gen_nsphere_random(rng : &mut ThreadRng, dims : usize) -> Array1<f32> {
+pub fn gen_nsphere_random<R : Rng + ?Sized>(rng : &mut R, dims : usize) -> Array1<f32> {
     generate_nsphere_random(rng, dims)
 }
 
 ///Generates a random vector contained in the unit `dims`-ball.
-pub fn gen_nball_random(rng : &mut ThreadRng, dims : usize) -> Array1<f32> {
+pub fn gen_nball_random<R : Rng + ?Sized>(rng : &mut R, dims : usize) -> Array1<f32> {
     let mut result = gen_nsphere_random(rng, dims);
     let u : f32 = rng.gen();
     let exponent = 1.0f32 / (dims as f32);
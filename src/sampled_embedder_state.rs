@@ -45,6 +45,27 @@ impl<'a> SampledEmbedderState<'a> {
         result
     }
 
+    ///Gets the vector in the compressed space of its type which this [`SampledEmbedderState`]
+    ///assigns to the given [`TermReference`]. Tuples are mapped to the compression of the
+    ///concatenation of the compressed vectors for their components.
+    pub fn get_compressed_vec_from_ref(&self, term_ref : &TermReference) -> Array1<f32> {
+        match (term_ref) {
+            TermReference::VecRef(_, vec) => from_noisy(vec.view()),
+            TermReference::FuncRef(term_ptr) => {
+                let embedding_space = self.embedding_spaces.get(&term_ptr.type_id).unwrap();
+                embedding_space.get_embedding(term_ptr.index).sampled_compressed_vec.clone()
+            },
+            TermReference::ProductRef(type_id, components) => {
+                let component_vecs : Vec<Array1<f32>> = components.iter()
+                                                .map(|component| self.get_compressed_vec_from_ref(component))
+                                                .collect();
+                let component_views : Vec<ArrayView1<f32>> = component_vecs.iter().map(|vec| vec.view()).collect();
+                let base_vec = stack(Axis(0), &component_views).unwrap();
+                self.ctxt.get_feature_space_info(*type_id).sketch(base_vec.view())
+            }
+        }
+    }
+
     ///Given a [`TermApplication`], evaluates the result that the [`SampledModelEmbedding`]s
     ///in this [`SampledEmbedderState`] would yield for the expression. The result will be
     ///in the compressed space of the output type.
@@ -56,13 +77,7 @@ impl<'a> SampledEmbedderState<'a> {
         let func_embedding_space = self.embedding_spaces.get(&func_type_id).unwrap();
         let func_mat = &func_embedding_space.get_embedding(term_application.func_ptr.index).sampled_mat;
 
-        let arg_vec = match (&term_application.arg_ref) {
-            TermReference::VecRef(_, vec) => from_noisy(vec.view()),
-            TermReference::FuncRef(arg_ptr) => {
-                let arg_embedding_space = self.embedding_spaces.get(&arg_ptr.type_id).unwrap();
-                arg_embedding_space.get_embedding(arg_ptr.index).sampled_compressed_vec.clone()
-            }
-        };
+        let arg_vec = self.get_compressed_vec_from_ref(&term_application.arg_ref);
 
         let ret_vec = func_space_info.apply(func_mat.view(), arg_vec.view());
         TypedVector {
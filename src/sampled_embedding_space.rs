@@ -17,17 +17,19 @@ pub struct SampledEmbeddingSpace<'a> {
     ///Maps from the compressed space for the type to the base space for the type.
     pub elaborator : Array2<f32>,
     pub models : HashMap<ModelKey, SampledModelEmbedding>,
+    ///Maps merged [`TermIndex`]es to the [`TermIndex`] whose embedding they share.
+    pub aliases : HashMap<ModelKey, ModelKey>,
     pub ctxt : &'a Context
 }
 
 impl<'a> SampledEmbeddingSpace<'a> {
     ///Determines whether an embedding exists for the given [`TermIndex`].
     pub fn has_embedding(&self, model_key : ModelKey) -> bool {
-        self.models.contains_key(&model_key)
+        self.models.contains_key(self.aliases.get(&model_key).unwrap_or(&model_key))
     }
     ///Gets the [`SampledModelEmbedding`] corresponding to the given  [`TermIndex`].
     pub fn get_embedding(&self, model_key : ModelKey) -> &SampledModelEmbedding {
-        self.models.get(&model_key).unwrap()
+        self.models.get(self.aliases.get(&model_key).unwrap_or(&model_key)).unwrap()
     }
 
     ///Given a compressed vector, uses `self.elaborator` to expand it to a vector in the
@@ -57,6 +59,7 @@ impl<'a> SampledEmbeddingSpace<'a> {
             type_id,
             elaborator,
             models : HashMap::new(),
+            aliases : HashMap::new(),
             ctxt
         }
     }
@@ -32,7 +32,7 @@ pub struct SampledModelEmbedding {
 
 impl SampledModelEmbedding {
     ///Draws a [`SampledModelEmbedding`] for the given [`TermModel`].
-    pub fn new(term_model : &TermModel, rng : &mut ThreadRng) -> SampledModelEmbedding {
+    pub fn new<R : Rng + ?Sized>(term_model : &TermModel, rng : &mut R) -> SampledModelEmbedding {
         let model = &term_model.model;
         let sampled_mat = model.sample(rng);
         let func_schmear = model.get_schmear(); 
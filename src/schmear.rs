@@ -61,6 +61,25 @@ impl Schmear {
             covariance : covariance
         }
     }
+    ///Constructs the [`Schmear`] for the concatenation of independent random vectors
+    ///distributed according to the passed [`Schmear`]s, in order. The mean is the
+    ///concatenation of the means, and the covariance is block-diagonal.
+    pub fn concatenate(schmears : &[Schmear]) -> Schmear {
+        let n : usize = schmears.iter().map(|schmear| schmear.mean.len()).sum();
+        let mut mean = Array::zeros((n,));
+        let mut covariance = Array::zeros((n, n));
+        let mut offset = 0;
+        for schmear in schmears.iter() {
+            let end = offset + schmear.mean.len();
+            mean.slice_mut(s![offset..end]).assign(&schmear.mean);
+            covariance.slice_mut(s![offset..end, offset..end]).assign(&schmear.covariance);
+            offset = end;
+        }
+        Schmear {
+            mean,
+            covariance
+        }
+    }
     ///Constructs the [`InverseSchmear`] which corresponds to this
     ///[`Schmear`] by taking the [`pseudoinverse_h`] of the covariance.
     pub fn inverse(&self) -> InverseSchmear {
@@ -25,9 +25,12 @@ impl SchmearedHole {
     ///gets the term which is closest in mahalanobis distance to the center
     ///of the wrapped [`InverseSchmear`] within the given [`SampledEmbedderState`],
     ///and returns both a reference to the term and the square of the best mahalanobis distance.
-    ///If there are no terms of the proper type in `embedder_state`, yields `Option::None`.
+    ///If there are no terms of the proper type in `embedder_state`, or if the type is
+    ///a product type (whose terms aren't tracked by `embedder_state`), yields `Option::None`.
     pub fn get_closest_term(&self, embedder_state : &SampledEmbedderState) -> Option<(TermReference, f32)> {
-        if (embedder_state.ctxt.is_vector_type(self.type_id)) {
+        if (embedder_state.ctxt.type_info_directory.is_product_type(self.type_id)) {
+            Option::None
+        } else if (embedder_state.ctxt.is_vector_type(self.type_id)) {
             Option::Some((TermReference::VecRef(self.type_id, to_noisy(self.inv_schmear.mean.view())), 0.0f32))
         } else {
             let embedding_space = embedder_state.embedding_spaces.get(&self.type_id).unwrap();
@@ -42,16 +45,13 @@ impl SchmearedHole {
                     best_term_id = Option::Some(*term_id);
                 }
             }
+            let index = best_term_id?;
             let term_ptr = TermPointer {
                 type_id : self.type_id,
-                index : best_term_id.unwrap()
+                index
             };
             let term_ref = TermReference::FuncRef(term_ptr);
-            if best_term_id.is_none() {
-                Option::None
-            } else {
-                Option::Some((term_ref, best_dist))
-            }
+            Option::Some((term_ref, best_dist))
         }
     }
 }
@@ -8,6 +8,7 @@ use ndarray_rand::rand_distr::StandardNormal;
 
 use crate::feature_collection::*;
 use crate::params::*;
+use rand::prelude::*;
 
 use serde::{Serialize, Deserialize};
 
@@ -23,9 +24,10 @@ pub struct SketchedLinearFeatureCollection {
 impl SketchedLinearFeatureCollection {
     ///Constructs a new [`SketchedLinearFeatureCollection`] with the given number of input
     ///dimensions, the given number of sketched linear features, and the given post-scaling
-    ///factor `alpha`.
-    pub fn new(in_dimensions : usize, out_dimensions : usize, alpha : f32) -> SketchedLinearFeatureCollection {
-        let projection_mat = Array::random((out_dimensions, in_dimensions), StandardNormal);
+    ///factor `alpha`, drawing the projection matrix from `rng`.
+    pub fn new<R : Rng + ?Sized>(in_dimensions : usize, out_dimensions : usize, alpha : f32,
+                                 rng : &mut R) -> SketchedLinearFeatureCollection {
+        let projection_mat = Array::random_using((out_dimensions, in_dimensions), StandardNormal, rng);
 
         SketchedLinearFeatureCollection {
             in_dimensions,
@@ -60,4 +62,7 @@ impl FeatureCollection for SketchedLinearFeatureCollection {
         self.alpha * result
     }
 
+    fn to_serialized(&self) -> SerializedFeatureCollection {
+        SerializedFeatureCollection::SketchedLinear(self.clone())
+    }
 }
@@ -6,12 +6,39 @@ use crate::feature_space_info::*;
 use crate::function_space_info::*;
 use topological_sort::TopologicalSort;
 
+use serde::{Serialize, Deserialize};
+
 ///A directory of `FeatureSpaceInfo`s, indexed by [`TypeId`].
 pub struct SpaceInfoDirectory {
     pub feature_spaces : Vec<FeatureSpaceInfo>
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SerializedSpaceInfoDirectory {
+    pub feature_spaces : Vec<SerializedFeatureSpaceInfo>
+}
+
+impl SerializedSpaceInfoDirectory {
+    pub fn deserialize(mut self) -> SpaceInfoDirectory {
+        let feature_spaces = self.feature_spaces.drain(..)
+                                 .map(|feature_space| feature_space.deserialize())
+                                 .collect();
+        SpaceInfoDirectory {
+            feature_spaces
+        }
+    }
+}
+
 impl SpaceInfoDirectory {
+    pub fn serialize(&self) -> SerializedSpaceInfoDirectory {
+        let feature_spaces = self.feature_spaces.iter()
+                                 .map(|feature_space| feature_space.serialize())
+                                 .collect();
+        SerializedSpaceInfoDirectory {
+            feature_spaces
+        }
+    }
+
     ///Gets the [`FeatureSpaceInfo`] for the given [`TypeId`].
     pub fn get_feature_space_info(&self, type_id : TypeId) -> &FeatureSpaceInfo {
         &self.feature_spaces[type_id]
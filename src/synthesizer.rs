@@ -0,0 +1,348 @@
+use crate::type_id::*;
+use crate::context::*;
+use crate::multiple::*;
+use crate::term_pointer::*;
+use crate::term_reference::*;
+use crate::term_input_output::*;
+use crate::term_model::*;
+use crate::schmeared_hole::*;
+use crate::input_to_schmeared_output::*;
+use crate::schmear::*;
+use crate::embedder_state::*;
+use crate::sampled_embedder_state::*;
+use crate::interpreter_and_embedder_state::*;
+use crate::expression_tree::*;
+use crate::array_utils::*;
+use crate::fetish_error::*;
+use crate::func_impl::*;
+use rand::prelude::*;
+use std::collections::HashSet;
+
+///A program found by a [`Synthesizer`] which is consistent with its examples.
+#[derive(Clone)]
+pub struct SynthesizedProgram {
+    pub tree : ExpressionTree,
+    ///The function term which `tree` evaluates to.
+    pub func_ptr : TermPointer,
+    ///The number of existing function terms which were chained to obtain `tree`.
+    pub num_stages : usize,
+    ///The sum, over all examples, of the squared distance between the
+    ///actual and the expected output.
+    pub squared_error : f32
+}
+
+///Searches for function terms of a given type which map the inputs of the given
+///examples to their outputs, guided by the embeddings in an [`EmbedderState`].
+///
+///Every round, the examples are used to update a fresh [`TermModel`] of the target type,
+///whose [`SchmearedHole`] is then matched against the closest terms in a number of
+///[`SampledEmbedderState`]s. Each proposed term is verified by evaluation, and if it's not
+///consistent with the examples, its outputs are used to build a second hole for a function
+///from the return type to itself to compose it with (given a `compose` primitive of the right type).
+///Finally, everything which was evaluated is fed back via
+///[`InterpreterAndEmbedderState::bayesian_update_step`] to sharpen the next round's proposals.
+///Before the first round, the constant functions `const c` are tried for every constant `c`
+///of the return type in the [`crate::constant_directory::ConstantDirectory`] (given a `const`
+///or `K` primitive of the right type).
+pub struct Synthesizer {
+    pub func_type_id : TypeId,
+    pub examples : Vec<TermInputOutput>,
+    ///The number of rounds of proposals and embedding updates to perform.
+    pub num_rounds : usize,
+    ///The number of [`SampledEmbedderState`]s to draw proposals from in every round.
+    pub num_samples : usize,
+    ///The maximal [`SynthesizedProgram::squared_error`] of a program which
+    ///is considered to be consistent with the examples.
+    pub tolerance : f32
+}
+
+impl Synthesizer {
+    ///Constructs a [`Synthesizer`] for functions of the given type with the given examples,
+    ///after checking that the examples' inputs and outputs have the argument and return type
+    ///of the function type.
+    pub fn new(func_type_id : TypeId, examples : Vec<TermInputOutput>, ctxt : &Context)
+              -> Result<Synthesizer, FetishError> {
+        let (arg_type_id, ret_type_id) = ctxt.type_info_directory.try_get_arg_and_ret_type_ids(func_type_id)?;
+        for example in examples.iter() {
+            example.input.check_type(arg_type_id, ctxt)?;
+            example.output.check_type(ret_type_id, ctxt)?;
+        }
+        Result::Ok(Synthesizer {
+            func_type_id,
+            examples,
+            num_rounds : 4,
+            num_samples : 8,
+            tolerance : 0.0001f32
+        })
+    }
+
+    ///Builds the [`SchmearedHole`] for a function of the given type which maps
+    ///the inputs of the given examples to their outputs.
+    pub fn build_hole(embedder_state : &EmbedderState, func_type_id : TypeId,
+                      examples : &[TermInputOutput]) -> SchmearedHole {
+        let ctxt = embedder_state.ctxt;
+        let mut term_model = TermModel::new(func_type_id, ctxt.get_model_prior_specification(func_type_id), ctxt);
+        for example in examples.iter() {
+            let data_point = InputToSchmearedOutput {
+                in_vec : embedder_state.get_compressed_schmear_from_ref(&example.input).mean,
+                out_schmear : embedder_state.get_compressed_schmear_from_ref(&example.output)
+            };
+            term_model.update_data(example.clone(), Multiple {
+                elem : data_point,
+                count : 1
+            });
+        }
+        term_model.get_schmeared_hole()
+    }
+
+    ///Runs the search against the given [`InterpreterAndEmbedderState`], yielding every
+    ///[`SynthesizedProgram`] found to be consistent with the examples, ordered by
+    ///increasing squared error and then by increasing number of stages.
+    pub fn synthesize<R : Rng + ?Sized>(&self, state : &mut InterpreterAndEmbedderState, rng : &mut R)
+                                       -> Vec<SynthesizedProgram> {
+        let ret_type_id = state.get_context().get_ret_type_id(self.func_type_id);
+        let compose_ptr = self.find_compose(state.get_context());
+
+        let mut found = Vec::new();
+        let mut tried = HashSet::new();
+        if let Option::Some(const_ptr) = self.find_const(state.get_context()) {
+            for constant in state.get_context().constant_directory.get_constants_of_type(ret_type_id) {
+                let tree = ExpressionTree::apply(ExpressionTree::Term(const_ptr), ExpressionTree::Literal(constant));
+                if let Option::Some((func_ptr, outputs)) = self.verify(state, &tree, &mut tried) {
+                    let squared_error = self.get_squared_error(&outputs);
+                    if (squared_error <= self.tolerance) {
+                        found.push(SynthesizedProgram {
+                            tree,
+                            func_ptr,
+                            num_stages : 1,
+                            squared_error
+                        });
+                    }
+                }
+            }
+        }
+        for _ in 0..self.num_rounds {
+            let hole = Self::build_hole(&state.embedder_state, self.func_type_id, &self.examples);
+            let sampled_states : Vec<SampledEmbedderState> = (0..self.num_samples).map(|_| state.embedder_state.sample(rng))
+                                                                                    .collect();
+            let proposals = Self::get_closest_terms(&hole, &sampled_states);
+            for proposal in proposals {
+                let tree = ExpressionTree::Term(proposal);
+                let (func_ptr, outputs) = match (self.verify(state, &tree, &mut tried)) {
+                    Option::Some(verified) => verified,
+                    Option::None => continue
+                };
+                let squared_error = self.get_squared_error(&outputs);
+                if (squared_error <= self.tolerance) {
+                    found.push(SynthesizedProgram {
+                        tree,
+                        func_ptr,
+                        num_stages : 1,
+                        squared_error
+                    });
+                    continue;
+                }
+                if let Option::Some(compose_ptr) = compose_ptr {
+                    let remaining_examples : Vec<TermInputOutput> = outputs.into_iter().zip(self.examples.iter())
+                                                .map(|(output, example)| TermInputOutput {
+                                                    input : output,
+                                                    output : example.output.clone()
+                                                })
+                                                .collect();
+                    let remaining_type_id = state.get_context().get_func_type_id(ret_type_id, ret_type_id);
+                    let remaining_hole = Self::build_hole(&state.embedder_state, remaining_type_id, &remaining_examples);
+                    for second_stage in Self::get_closest_terms(&remaining_hole, &sampled_states) {
+                        let composed = ExpressionTree::apply(ExpressionTree::apply(ExpressionTree::Term(compose_ptr),
+                                                                                   ExpressionTree::Term(second_stage)),
+                                                             ExpressionTree::Term(proposal));
+                        if let Option::Some((func_ptr, outputs)) = self.verify(state, &composed, &mut tried) {
+                            let squared_error = self.get_squared_error(&outputs);
+                            if (squared_error <= self.tolerance) {
+                                found.push(SynthesizedProgram {
+                                    tree : composed,
+                                    func_ptr,
+                                    num_stages : 2,
+                                    squared_error
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+            state.bayesian_update_step();
+            state.clear_newly_received();
+        }
+        found.sort_by(|a, b| a.squared_error.total_cmp(&b.squared_error)
+                              .then(a.num_stages.cmp(&b.num_stages)));
+        found
+    }
+
+    ///Finds a `compose` or `B` primitive of type `(ret -> ret) -> (arg -> ret) -> (arg -> ret)`
+    ///for the argument and return types of the target function type, if there is one.
+    fn find_compose(&self, ctxt : &Context) -> Option<TermPointer> {
+        let ret_type_id = ctxt.get_ret_type_id(self.func_type_id);
+        if (!ctxt.has_func_type(ret_type_id, ret_type_id) || !ctxt.has_func_type(self.func_type_id, self.func_type_id)) {
+            return Option::None;
+        }
+        let second_stage_type_id = ctxt.get_func_type_id(ret_type_id, ret_type_id);
+        let composed_type_id = ctxt.get_func_type_id(self.func_type_id, self.func_type_id);
+        if (!ctxt.has_func_type(second_stage_type_id, composed_type_id)) {
+            return Option::None;
+        }
+        let compose_type_id = ctxt.get_func_type_id(second_stage_type_id, composed_type_id);
+        COMPOSE_NAMES.iter()
+            .flat_map(|name| ctxt.primitive_directory.find_primitives(name))
+            .find(|primitive_ptr| primitive_ptr.type_id == compose_type_id)
+            .map(TermPointer::from)
+    }
+
+    ///Finds a `const` or `K` primitive of type `ret -> (arg -> ret)` for the argument
+    ///and return types of the target function type, if there is one.
+    fn find_const(&self, ctxt : &Context) -> Option<TermPointer> {
+        let ret_type_id = ctxt.get_ret_type_id(self.func_type_id);
+        let const_type_id = ctxt.try_get_func_type_id(ret_type_id, self.func_type_id).ok()?;
+        CONST_NAMES.iter()
+            .flat_map(|name| ctxt.primitive_directory.find_primitives(name))
+            .find(|primitive_ptr| primitive_ptr.type_id == const_type_id)
+            .map(TermPointer::from)
+    }
+
+    fn get_closest_terms(hole : &SchmearedHole, sampled_states : &[SampledEmbedderState]) -> Vec<TermPointer> {
+        let mut result = Vec::new();
+        for sampled_state in sampled_states.iter() {
+            if let Option::Some((TermReference::FuncRef(func_ptr), _)) = hole.get_closest_term(sampled_state) {
+                if (!result.contains(&func_ptr)) {
+                    result.push(func_ptr);
+                }
+            }
+        }
+        result
+    }
+
+    ///Evaluates the given tree, and applies the resulting function to the inputs
+    ///of every example, unless it was tried before. Evaluation failures are treated as
+    ///inconsistency with the examples.
+    fn verify(&self, state : &mut InterpreterAndEmbedderState, tree : &ExpressionTree,
+              tried : &mut HashSet<TermPointer>) -> Option<(TermPointer, Vec<TermReference>)> {
+        let (result_ref, newly_evaluated_terms) = tree.evaluate(&mut state.interpreter_state).ok()?;
+        state.newly_evaluated_terms.merge(newly_evaluated_terms);
+        let func_ptr = match (result_ref) {
+            TermReference::FuncRef(func_ptr) => func_ptr,
+            _ => return Option::None
+        };
+        if (!tried.insert(func_ptr)) {
+            return Option::None;
+        }
+        let mut outputs = Vec::new();
+        for example in self.examples.iter() {
+            outputs.push(state.apply(func_ptr, example.input.clone()).ok()?);
+        }
+        Option::Some((func_ptr, outputs))
+    }
+
+    fn get_squared_error(&self, outputs : &[TermReference]) -> f32 {
+        let mut result = 0.0f32;
+        for (output, example) in outputs.iter().zip(self.examples.iter()) {
+            result += match (output, &example.output) {
+                (TermReference::VecRef(_, actual), TermReference::VecRef(_, expected)) => {
+                    let diff = from_noisy(actual.view()) - from_noisy(expected.view());
+                    diff.dot(&diff)
+                },
+                (actual, expected) => if (actual == expected) { 0.0f32 } else { f32::INFINITY }
+            };
+        }
+        //Infinite outputs make for NaN differences, which are as far off as it gets
+        if (result.is_nan()) { f32::INFINITY } else { result }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::*;
+    use crate::primitive_template::*;
+    use crate::displayable_with_state::*;
+    use crate::test_utils::*;
+
+    fn vector_example(input : Array1<f32>, output : Array1<f32>) -> TermInputOutput {
+        TermInputOutput {
+            input : TermReference::VecRef(TEST_VECTOR_T, to_noisy(input.view())),
+            output : TermReference::VecRef(TEST_VECTOR_T, to_noisy(output.view()))
+        }
+    }
+
+    #[test]
+    fn examples_must_match_the_function_type() {
+        let ctxt = get_test_compose_context();
+        let func_type_id = ctxt.parse_type("Vec2 -> Vec2").unwrap();
+        let example = TermInputOutput {
+            input : TermReference::VecRef(TEST_SCALAR_T, to_noisy(array![1.0f32].view())),
+            output : TermReference::VecRef(TEST_VECTOR_T, to_noisy(array![1.0f32, 2.0f32].view()))
+        };
+        assert!(Synthesizer::new(func_type_id, vec![example], &ctxt).is_err());
+    }
+
+    #[test]
+    fn function_types_without_terms_yield_nothing() {
+        let ctxt = get_test_template_context(&["Vec2 -> Vec2"], Vec::new());
+
+        let func_type_id = ctxt.parse_type("Vec2 -> Vec2").unwrap();
+        let mut state = InterpreterAndEmbedderState::new(&ctxt);
+        let mut rng = StdRng::seed_from_u64(TEST_SEED);
+        let synthesizer = Synthesizer::new(func_type_id, vec![vector_example(array![1.0, 2.0], array![2.0, 1.0])],
+                                           &ctxt).unwrap();
+        assert!(synthesizer.synthesize(&mut state, &mut rng).is_empty());
+    }
+
+    #[test]
+    fn single_primitives_are_found() {
+        let ctxt = get_test_compose_context();
+        let func_type_id = ctxt.parse_type("Vec2 -> Vec2").unwrap();
+        let mut state = InterpreterAndEmbedderState::new(&ctxt);
+        let mut rng = StdRng::seed_from_u64(TEST_SEED);
+        let synthesizer = Synthesizer::new(func_type_id, vec![vector_example(array![1.0, 2.0], array![2.0, 1.0]),
+                                                              vector_example(array![3.0, 5.0], array![5.0, 3.0])],
+                                           &ctxt).unwrap();
+        let programs = synthesizer.synthesize(&mut state, &mut rng);
+        assert_eq!(programs[0].num_stages, 1);
+        assert_eq!(programs[0].tree.display(&state.interpreter_state), "rotate()");
+    }
+
+    #[test]
+    fn constant_functions_are_found() {
+        let mut builder = get_test_template_context_builder(&["Vec2 -> Vec2 -> Vec2"],
+                                                            vec![Box::new(RotateTemplate {}), Box::new(ConstTemplate {})]);
+        builder.add_standard_constants(TEST_VECTOR_T).unwrap();
+        let ctxt = builder.build().unwrap();
+
+        let func_type_id = ctxt.parse_type("Vec2 -> Vec2").unwrap();
+        let mut state = InterpreterAndEmbedderState::new(&ctxt);
+        let mut rng = StdRng::seed_from_u64(TEST_SEED);
+        let synthesizer = Synthesizer::new(func_type_id, vec![vector_example(array![1.0, 2.0], array![0.0, 1.0]),
+                                                              vector_example(array![3.0, 5.0], array![0.0, 1.0])],
+                                           &ctxt).unwrap();
+        let programs = synthesizer.synthesize(&mut state, &mut rng);
+        assert_eq!(programs[0].num_stages, 1);
+        assert_eq!(programs[0].squared_error, 0.0f32);
+        assert!(matches!(&programs[0].tree, ExpressionTree::Application(_, arg)
+                         if matches!(arg.as_ref(), ExpressionTree::Literal(_))));
+    }
+
+    #[test]
+    fn compositions_are_found() {
+        let ctxt = get_test_compose_context();
+        let func_type_id = ctxt.parse_type("Vec2 -> Vec2").unwrap();
+        let mut state = InterpreterAndEmbedderState::new(&ctxt);
+        let mut rng = StdRng::seed_from_u64(TEST_SEED);
+        let synthesizer = Synthesizer::new(func_type_id, vec![vector_example(array![1.0, 2.0], array![1.0, 2.0]),
+                                                              vector_example(array![3.0, 5.0], array![3.0, 5.0])],
+                                           &ctxt).unwrap();
+        let programs = synthesizer.synthesize(&mut state, &mut rng);
+        assert!(!programs.is_empty());
+        assert_eq!(programs[0].num_stages, 2);
+        assert_eq!(programs[0].squared_error, 0.0f32);
+        let identity = programs[0].func_ptr;
+        assert!(state.interpreter_state.apply(identity, TermReference::VecRef(TEST_VECTOR_T, to_noisy(array![7.0f32, 9.0f32].view())))
+                     .unwrap().0 == TermReference::VecRef(TEST_VECTOR_T, to_noisy(array![7.0f32, 9.0f32].view())));
+    }
+}
@@ -3,10 +3,10 @@ use crate::context::*;
 use crate::term_reference::*;
 use crate::type_id::*;
 use std::cmp::*;
-use std::fmt::*;
 use std::hash::*;
 use crate::interpreter_state::*;
 use crate::displayable_with_state::*;
+use crate::fetish_error::*;
 
 use serde::{Serialize, Deserialize};
 
@@ -19,6 +19,19 @@ pub struct TermApplication {
 }
 
 impl TermApplication {
+    ///Constructs the [`TermApplication`] of the given function to the given argument,
+    ///after checking in the given [`Context`] that `func_ptr` has a function type
+    ///whose argument type matches the type of `arg_ref`, and that vector arguments
+    ///have the declared number of dimensions.
+    pub fn new(func_ptr : TermPointer, arg_ref : TermReference, ctxt : &Context)
+              -> Result<TermApplication, FetishError> {
+        let arg_type_id = ctxt.try_get_arg_type_id(func_ptr.type_id)?;
+        arg_ref.check_type(arg_type_id, ctxt)?;
+        Result::Ok(TermApplication {
+            func_ptr,
+            arg_ref
+        })
+    }
     ///Gets the argument [`TypeId`] of this [`TermApplication`] in the given [`Context`].
     pub fn get_arg_type(&self, ctxt : &Context) -> TypeId {
         let (arg_type, _) = self.get_func_type_pair(ctxt);
@@ -0,0 +1,293 @@
+use crate::type_id::*;
+use crate::term::*;
+use crate::term_pointer::*;
+use crate::primitive_term_pointer::*;
+use crate::nonprimitive_term_pointer::*;
+use crate::term_reference::*;
+use crate::term_application::*;
+use crate::interpreter_state::*;
+use crate::newly_evaluated_terms::*;
+use crate::expression_tree::*;
+use std::collections::HashSet;
+
+///A well-typed [`ExpressionTree`] found by a [`TermEnumerator`].
+#[derive(Clone)]
+pub struct EnumeratedTerm {
+    pub tree : ExpressionTree,
+    pub type_id : TypeId,
+    ///The number of leaves in `tree`.
+    pub size : usize,
+    ///The number of nested applications along the deepest path in `tree`.
+    pub depth : usize,
+    ///The term which `tree` stands for, if known. When the [`TermEnumerator`] doesn't
+    ///evaluate, this is only known for leaves, for applications which were already evaluated,
+    ///and for partial applications which are already stored in the [`InterpreterState`].
+    pub result_ref : Option<TermReference>
+}
+
+///Enumerates every well-typed application of primitives, existing terms, the constants
+///in the [`crate::constant_directory::ConstantDirectory`], and caller-supplied literals
+///up to the given size and depth bounds, bottom-up by size.
+///Any [`EnumeratedTerm`] standing for a term which was already enumerated is dropped,
+///where function terms are identified through the [`crate::type_space::TypeSpace`]s
+///of the [`InterpreterState`].
+pub struct TermEnumerator {
+    ///The maximal number of leaves of an enumerated [`ExpressionTree`].
+    pub max_size : usize,
+    ///The maximal number of nested applications in an enumerated [`ExpressionTree`], if any.
+    pub max_depth : Option<usize>,
+    ///Whether to evaluate every enumerated application against the [`InterpreterState`].
+    ///If set, applications whose evaluation fails are skipped, and results are known for
+    ///every [`EnumeratedTerm`], which makes deduplication much more effective.
+    pub evaluate : bool,
+    ///Literal terms, such as vectors, to use as leaves alongside primitives and existing terms.
+    pub literals : Vec<TermReference>
+}
+
+impl TermEnumerator {
+    ///Constructs a [`TermEnumerator`] for terms with at most `max_size` leaves, without
+    ///a depth bound, literals, or evaluation.
+    pub fn new(max_size : usize) -> TermEnumerator {
+        TermEnumerator {
+            max_size,
+            max_depth : Option::None,
+            evaluate : false,
+            literals : Vec::new()
+        }
+    }
+
+    ///Enumerates every [`EnumeratedTerm`] within bounds, in order of increasing size,
+    ///together with the [`NewlyEvaluatedTerms`] from any evaluations which were performed.
+    pub fn enumerate(&self, state : &mut InterpreterState) -> (Vec<EnumeratedTerm>, NewlyEvaluatedTerms) {
+        let mut seen = HashSet::new();
+        let mut newly_evaluated_terms = NewlyEvaluatedTerms::new();
+        let mut by_size : Vec<Vec<EnumeratedTerm>> = vec![Vec::new()];
+
+        let leaves = self.get_leaves(state);
+        by_size.push(Self::deduplicate(leaves, &mut seen));
+
+        for size in 2..=self.max_size {
+            let mut candidates = Vec::new();
+            for func_size in 1..size {
+                let arg_size = size - func_size;
+                for func in by_size[func_size].iter() {
+                    let arg_type_id = match (state.get_context().try_get_arg_type_id(func.type_id)) {
+                        Result::Ok(arg_type_id) => arg_type_id,
+                        Result::Err(_) => continue
+                    };
+                    for arg in by_size[arg_size].iter() {
+                        if (arg.type_id != arg_type_id) {
+                            continue;
+                        }
+                        let depth = func.depth.max(arg.depth) + 1;
+                        if (self.max_depth.is_some_and(|max_depth| depth > max_depth)) {
+                            continue;
+                        }
+                        if let Option::Some(candidate) = self.apply(state, func, arg, &mut newly_evaluated_terms) {
+                            candidates.push(candidate);
+                        }
+                    }
+                }
+            }
+            by_size.push(Self::deduplicate(candidates, &mut seen));
+        }
+        (by_size.into_iter().flatten().collect(), newly_evaluated_terms)
+    }
+
+    ///Like [`Self::enumerate`], but only yields the [`EnumeratedTerm`]s of the given [`TypeId`].
+    pub fn enumerate_type(&self, state : &mut InterpreterState, type_id : TypeId) -> (Vec<EnumeratedTerm>, NewlyEvaluatedTerms) {
+        let (enumerated, newly_evaluated_terms) = self.enumerate(state);
+        let of_type = enumerated.into_iter().filter(|term| term.type_id == type_id).collect();
+        (of_type, newly_evaluated_terms)
+    }
+
+    fn get_leaves(&self, state : &InterpreterState) -> Vec<EnumeratedTerm> {
+        let ctxt = state.get_context();
+        let mut leaves = Vec::new();
+        for i in 0..ctxt.get_total_num_types() {
+            let type_id = i as TypeId;
+            if let Option::Some(primitive_space) = ctxt.primitive_directory.primitive_type_spaces.get(&type_id) {
+                for index in 0..primitive_space.terms.len() {
+                    let primitive_ptr = PrimitiveTermPointer {
+                        type_id,
+                        index
+                    };
+                    leaves.push(Self::leaf(ExpressionTree::Primitive(primitive_ptr), type_id,
+                                           TermReference::FuncRef(TermPointer::from(primitive_ptr))));
+                }
+            }
+            if let Option::Some(type_space) = state.type_spaces.get(&type_id) {
+                for index in 0..type_space.get_num_terms() {
+                    let term_ptr = TermPointer::from(NonPrimitiveTermPointer {
+                        type_id,
+                        index
+                    });
+                    leaves.push(Self::leaf(ExpressionTree::Term(term_ptr), type_id, TermReference::FuncRef(term_ptr)));
+                }
+            }
+        }
+        for constant in ctxt.constant_directory.constants.iter() {
+            leaves.push(Self::leaf(ExpressionTree::Literal(constant.value.clone()), constant.value.get_type(),
+                                   constant.value.clone()));
+        }
+        for literal in self.literals.iter() {
+            leaves.push(Self::leaf(ExpressionTree::Literal(literal.clone()), literal.get_type(), literal.clone()));
+        }
+        leaves
+    }
+
+    fn leaf(tree : ExpressionTree, type_id : TypeId, result_ref : TermReference) -> EnumeratedTerm {
+        EnumeratedTerm {
+            tree,
+            type_id,
+            size : 1,
+            depth : 0,
+            result_ref : Option::Some(result_ref)
+        }
+    }
+
+    fn apply(&self, state : &mut InterpreterState, func : &EnumeratedTerm, arg : &EnumeratedTerm,
+             newly_evaluated_terms : &mut NewlyEvaluatedTerms) -> Option<EnumeratedTerm> {
+        let ret_type_id = state.get_context().get_ret_type_id(func.type_id);
+        let term_app = match (&func.result_ref, &arg.result_ref) {
+            (Option::Some(TermReference::FuncRef(func_ptr)), Option::Some(arg_ref)) => {
+                Option::Some(TermApplication {
+                    func_ptr : *func_ptr,
+                    arg_ref : arg_ref.clone()
+                })
+            },
+            _ => Option::None
+        };
+        let result_ref = match (term_app) {
+            Option::Some(term_app) => {
+                if (self.evaluate) {
+                    let (result_ref, more_evaluated_terms) = state.evaluate_memoized(&term_app).ok()?;
+                    newly_evaluated_terms.merge(more_evaluated_terms);
+                    Option::Some(result_ref)
+                } else {
+                    Self::find_result(state, &term_app, ret_type_id)
+                }
+            },
+            Option::None => Option::None
+        };
+        Option::Some(EnumeratedTerm {
+            tree : ExpressionTree::apply(func.tree.clone(), arg.tree.clone()),
+            type_id : ret_type_id,
+            size : func.size + arg.size,
+            depth : func.depth.max(arg.depth) + 1,
+            result_ref
+        })
+    }
+
+    ///Finds the result of the given [`TermApplication`] without evaluating anything.
+    fn find_result(state : &InterpreterState, term_app : &TermApplication, ret_type_id : TypeId) -> Option<TermReference> {
+        if let Option::Some(result_ref) = state.get_memoized_result(term_app) {
+            return Option::Some(result_ref);
+        }
+        let func_term = state.get(term_app.func_ptr);
+        let mut args = func_term.args;
+        args.push(term_app.arg_ref.clone());
+        if (state.get_context().get_primitive(func_term.func_ptr).ready_to_evaluate(&args)) {
+            return Option::None;
+        }
+        let partial_term = PartiallyAppliedTerm {
+            func_ptr : func_term.func_ptr,
+            args
+        };
+        let term_ptr = state.find_term(ret_type_id, &partial_term)?;
+        Option::Some(TermReference::FuncRef(TermPointer::from(term_ptr)))
+    }
+
+    fn deduplicate(candidates : Vec<EnumeratedTerm>, seen : &mut HashSet<TermReference>) -> Vec<EnumeratedTerm> {
+        candidates.into_iter()
+                  .filter(|candidate| match (&candidate.result_ref) {
+                      Option::Some(result_ref) => seen.insert(result_ref.clone()),
+                      Option::None => true
+                  })
+                  .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::*;
+    use crate::array_utils::*;
+    use crate::displayable_with_state::*;
+    use crate::test_utils::*;
+
+    fn vector_literal(elements : Vec<f32>) -> TermReference {
+        TermReference::VecRef(TEST_VECTOR_T, to_noisy(Array::from(elements).view()))
+    }
+
+    #[test]
+    fn leaves_include_primitives_and_literals() {
+        let ctxt = get_test_function_context();
+        let mut state = InterpreterState::new(&ctxt);
+        let mut enumerator = TermEnumerator::new(1);
+        enumerator.literals.push(vector_literal(vec![1.0, 2.0]));
+        let (enumerated, _) = enumerator.enumerate(&mut state);
+        assert_eq!(enumerated.len(), 3);
+        assert!(enumerated.iter().all(|term| term.size == 1 && term.result_ref.is_some()));
+    }
+
+    #[test]
+    fn leaves_include_named_constants() {
+        let mut builder = get_test_vector_only_context_builder();
+        builder.add_constant("half", TEST_SCALAR_T, &[0.5f32]).unwrap();
+        builder.add_standard_constants(TEST_VECTOR_T).unwrap();
+        let ctxt = builder.build().unwrap();
+        let mut state = InterpreterState::new(&ctxt);
+        let (enumerated, _) = TermEnumerator::new(1).enumerate(&mut state);
+        assert_eq!(enumerated.len(), 6);
+        assert_eq!(enumerated.iter().filter(|term| term.type_id == TEST_SCALAR_T).count(), 1);
+    }
+
+    #[test]
+    fn applications_are_well_typed_and_within_bounds() {
+        let ctxt = get_test_function_context();
+        let mut state = InterpreterState::new(&ctxt);
+        let mut enumerator = TermEnumerator::new(3);
+        enumerator.literals.push(vector_literal(vec![1.0, 2.0]));
+        let (enumerated, newly_evaluated_terms) = enumerator.enumerate(&mut state);
+
+        assert!(newly_evaluated_terms.term_app_results.is_empty());
+        for term in enumerated.iter() {
+            assert!(term.size <= 3);
+            assert_eq!(term.tree.get_type(&ctxt), Result::Ok(term.type_id));
+        }
+        let vectors : Vec<String> = enumerated.iter()
+                                              .filter(|term| term.type_id == TEST_VECTOR_T)
+                                              .map(|term| term.tree.display(&state))
+                                              .collect();
+        assert_eq!(vectors.len(), 4);
+
+        enumerator.max_depth = Option::Some(1);
+        let (shallow, _) = enumerator.enumerate(&mut state);
+        assert!(shallow.iter().all(|term| term.depth <= 1));
+        assert!(shallow.len() < enumerated.len());
+    }
+
+    #[test]
+    fn evaluated_duplicates_are_dropped() {
+        let ctxt = get_test_function_context();
+        let mut state = InterpreterState::new(&ctxt);
+        let mut enumerator = TermEnumerator::new(3);
+        enumerator.evaluate = true;
+        enumerator.literals.push(vector_literal(vec![1.0, 1.0]));
+        let (vectors, newly_evaluated_terms) = enumerator.enumerate_type(&mut state, TEST_VECTOR_T);
+
+        //rotate [1, 1] = [1, 1] is dropped, leaving + [1, 1] [1, 1] = [2, 2] as the only new vector.
+        assert_eq!(vectors.len(), 2);
+        assert!(vectors.iter().all(|term| term.result_ref.is_some()));
+        assert!(!newly_evaluated_terms.term_app_results.is_empty());
+
+        //With the partial application + [1, 1] now stored, enumeration without
+        //evaluation finds it through the type-space instead of listing it twice.
+        let mut enumerator = TermEnumerator::new(2);
+        enumerator.literals.push(vector_literal(vec![1.0, 1.0]));
+        let (func_terms, _) = enumerator.enumerate_type(&mut state, TEST_VECTOR_FUNC_T);
+        assert_eq!(func_terms.len(), 2);
+        assert!(func_terms.iter().all(|term| term.size == 1));
+    }
+}
@@ -0,0 +1,727 @@
+use std::fmt;
+use ndarray::*;
+use crate::type_id::*;
+use crate::type_parser::*;
+use crate::context::*;
+use crate::primitive_term_pointer::*;
+use crate::term_reference::*;
+use crate::expression_tree::*;
+use crate::interpreter_state::*;
+use crate::newly_evaluated_terms::*;
+use crate::array_utils::*;
+use crate::fetish_error::*;
+
+///Parsed, but not-yet-resolved, textual term expression, such as `compose rotate rotate [1.0, 2.0]`.
+///Application is written by juxtaposition and is left-associative, so `f x y` is the same
+///as `((f x) y)`. Primitives are referred to by name (see [`crate::func_impl::HasFuncSignature::get_name`]),
+///as are the constants in the [`crate::constant_directory::ConstantDirectory`].
+///Vectors are written as bracketed lists of numbers, and a bare number stands for a one-element
+///vector. Since primitives and constants of different types may share a name, the meaning of
+///each name is determined by the types around it, and a subexpression may be annotated with a type as
+///`(expr : type)`, using the syntax of [`TypeExpression`], to resolve any remaining ambiguity.
+///
+///Lambda abstractions are written `\x. body`, or `\(x : type). body` to fix the type of the
+///bound variable, and `\x y. body` abbreviates `\x. \y. body`. Bound variables shadow
+///primitives of the same name. Lambda abstractions are compiled away by bracket abstraction
+///into applications of the `S`, `K`, `I`, `B` and `C` combinators (see
+///[`crate::primitive_template::combinator_templates`]), so the [`Context`] must contain
+///instances of those combinators at the types which the abstraction needs.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TermExpression {
+    ///A primitive, a named constant, or a lambda-bound variable, with the given name.
+    Primitive(String),
+    ///A vector with the given elements.
+    Vector(Vec<f32>),
+    ///An application of the former expression to the latter.
+    Application(Box<TermExpression>, Box<TermExpression>),
+    ///An expression together with the type that it must have.
+    Annotated(Box<TermExpression>, TypeExpression),
+    ///A lambda abstraction binding the named variable, optionally with
+    ///the type that it must have, within the body.
+    Lambda(String, Option<TypeExpression>, Box<TermExpression>)
+}
+
+impl fmt::Display for TermExpression {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self) {
+            TermExpression::Primitive(name) => write!(f, "{}", name),
+            TermExpression::Vector(elements) => {
+                let displayed : Vec<String> = elements.iter().map(|element| element.to_string()).collect();
+                write!(f, "[{}]", displayed.join(", "))
+            },
+            TermExpression::Application(func, arg) => {
+                match (func.as_ref()) {
+                    TermExpression::Lambda(_, _, _) => write!(f, "({})", func)?,
+                    _ => write!(f, "{}", func)?
+                }
+                match (arg.as_ref()) {
+                    TermExpression::Application(_, _) | TermExpression::Lambda(_, _, _) => write!(f, " ({})", arg),
+                    _ => write!(f, " {}", arg)
+                }
+            },
+            TermExpression::Annotated(expr, type_expr) => write!(f, "({} : {})", expr, type_expr),
+            TermExpression::Lambda(name, type_expr, body) => {
+                match (type_expr) {
+                    Option::Some(type_expr) => write!(f, "\\({} : {}). {}", name, type_expr, body),
+                    Option::None => write!(f, "\\{}. {}", name, body)
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    OpenParen,
+    CloseParen,
+    OpenBracket,
+    CloseBracket,
+    Comma,
+    Colon,
+    Backslash,
+    Dot,
+    Number(f32),
+    Name(String)
+}
+
+fn parse_error(position : usize, message : &str) -> FetishError {
+    FetishError::Parse {
+        position,
+        message : String::from(message)
+    }
+}
+
+fn is_delimiter(c : char) -> bool {
+    c.is_whitespace() || "()[],:\\".contains(c)
+}
+
+fn tokenize(text : &str) -> Vec<(usize, Token)> {
+    let chars : Vec<char> = text.chars().collect();
+    let mut result = Vec::new();
+    let mut i = 0;
+    while (i < chars.len()) {
+        let c = chars[i];
+        let token = match (c) {
+            '(' => Option::Some(Token::OpenParen),
+            ')' => Option::Some(Token::CloseParen),
+            '[' => Option::Some(Token::OpenBracket),
+            ']' => Option::Some(Token::CloseBracket),
+            ',' => Option::Some(Token::Comma),
+            ':' => Option::Some(Token::Colon),
+            '\\' => Option::Some(Token::Backslash),
+            //A dot followed by a digit starts a number instead
+            '.' if !chars.get(i + 1).is_some_and(|next| next.is_ascii_digit()) => Option::Some(Token::Dot),
+            _ => Option::None
+        };
+        if let Option::Some(token) = token {
+            result.push((i, token));
+            i += 1;
+        } else if (c.is_whitespace()) {
+            i += 1;
+        } else {
+            //Only numbers may contain dots, so that `\x. x` parses as a lambda
+            let is_numeric = c.is_ascii_digit() || "+-.".contains(c);
+            let start = i;
+            while (i < chars.len() && !is_delimiter(chars[i]) && (is_numeric || chars[i] != '.')) {
+                i += 1;
+            }
+            let word : String = chars[start..i].iter().collect();
+            match (word.parse::<f32>()) {
+                Result::Ok(value) if value.is_finite() => result.push((start, Token::Number(value))),
+                _ => result.push((start, Token::Name(word)))
+            }
+        }
+    }
+    result
+}
+
+struct Parser {
+    chars : Vec<char>,
+    tokens : Vec<(usize, Token)>,
+    pos : usize
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, token)| token)
+    }
+
+    fn position(&self) -> usize {
+        match (self.tokens.get(self.pos)) {
+            Option::Some((position, _)) => *position,
+            Option::None => self.chars.len()
+        }
+    }
+
+    fn expect(&mut self, token : Token, message : &str) -> Result<(), FetishError> {
+        if (self.peek() != Option::Some(&token)) {
+            return Result::Err(parse_error(self.position(), message));
+        }
+        self.pos += 1;
+        Result::Ok(())
+    }
+
+    fn starts_atom(&self) -> bool {
+        matches!(self.peek(), Option::Some(Token::OpenParen) | Option::Some(Token::OpenBracket) |
+                              Option::Some(Token::Backslash) | Option::Some(Token::Number(_)) |
+                              Option::Some(Token::Name(_)))
+    }
+
+    fn parse_expression(&mut self) -> Result<TermExpression, FetishError> {
+        let mut result = self.parse_atom()?;
+        while (self.starts_atom()) {
+            let arg = self.parse_atom()?;
+            result = TermExpression::Application(Box::new(result), Box::new(arg));
+        }
+        Result::Ok(result)
+    }
+
+    fn parse_atom(&mut self) -> Result<TermExpression, FetishError> {
+        let position = self.position();
+        let token = self.peek().cloned();
+        self.pos += 1;
+        match (token) {
+            Option::Some(Token::Name(name)) => Result::Ok(TermExpression::Primitive(name)),
+            Option::Some(Token::Number(value)) => Result::Ok(TermExpression::Vector(vec![value])),
+            Option::Some(Token::OpenBracket) => {
+                let mut elements = Vec::new();
+                while (self.peek() != Option::Some(&Token::CloseBracket)) {
+                    if (!elements.is_empty()) {
+                        self.expect(Token::Comma, "expected \",\" or \"]\"")?;
+                    }
+                    match (self.peek().cloned()) {
+                        Option::Some(Token::Number(value)) => {
+                            elements.push(value);
+                            self.pos += 1;
+                        },
+                        _ => return Result::Err(parse_error(self.position(), "expected a number"))
+                    }
+                }
+                self.pos += 1;
+                Result::Ok(TermExpression::Vector(elements))
+            },
+            Option::Some(Token::OpenParen) => {
+                let expr = self.parse_expression()?;
+                if (self.peek() == Option::Some(&Token::Colon)) {
+                    let type_expr = self.parse_annotation()?;
+                    Result::Ok(TermExpression::Annotated(Box::new(expr), type_expr))
+                } else {
+                    self.expect(Token::CloseParen, "expected \")\"")?;
+                    Result::Ok(expr)
+                }
+            },
+            Option::Some(Token::Backslash) => self.parse_lambda(),
+            Option::Some(_) => Result::Err(parse_error(position, "expected a term")),
+            Option::None => Result::Err(parse_error(position, "unexpected end of input"))
+        }
+    }
+
+    //Parses the binders and body of a lambda abstraction, after the backslash
+    fn parse_lambda(&mut self) -> Result<TermExpression, FetishError> {
+        let mut binders = Vec::new();
+        while (binders.is_empty() || self.peek() != Option::Some(&Token::Dot)) {
+            let position = self.position();
+            let token = self.peek().cloned();
+            self.pos += 1;
+            match (token) {
+                Option::Some(Token::Name(name)) => binders.push((name, Option::None)),
+                Option::Some(Token::OpenParen) => {
+                    let name = match (self.peek().cloned()) {
+                        Option::Some(Token::Name(name)) => name,
+                        _ => return Result::Err(parse_error(self.position(), "expected a variable name"))
+                    };
+                    self.pos += 1;
+                    if (self.peek() != Option::Some(&Token::Colon)) {
+                        return Result::Err(parse_error(self.position(), "expected \":\""));
+                    }
+                    let type_expr = self.parse_annotation()?;
+                    binders.push((name, Option::Some(type_expr)));
+                },
+                _ => return Result::Err(parse_error(position, "expected a variable name"))
+            }
+        }
+        self.pos += 1;
+        let mut result = self.parse_expression()?;
+        for (name, type_expr) in binders.into_iter().rev() {
+            result = TermExpression::Lambda(name, type_expr, Box::new(result));
+        }
+        Result::Ok(result)
+    }
+
+    //Parses everything between a colon and its matching close parenthesis as a type
+    fn parse_annotation(&mut self) -> Result<TypeExpression, FetishError> {
+        let colon_position = self.position();
+        self.pos += 1;
+        let mut depth = 0;
+        while let Option::Some(token) = self.peek() {
+            match (token) {
+                Token::OpenParen => depth += 1,
+                Token::CloseParen if (depth == 0) => break,
+                Token::CloseParen => depth -= 1,
+                _ => {}
+            }
+            self.pos += 1;
+        }
+        let close_position = self.position();
+        self.expect(Token::CloseParen, "expected \")\"")?;
+
+        let type_start = colon_position + 1;
+        let type_text : String = self.chars[type_start..close_position].iter().collect();
+        TypeExpression::parse(&type_text).map_err(|err| match (err) {
+            FetishError::Parse { position, message } => FetishError::Parse {
+                position : position + type_start,
+                message
+            },
+            other => other
+        })
+    }
+}
+
+fn lookup_variable(env : &[(String, TypeId)], name : &str) -> Option<TypeId> {
+    env.iter().rev().find(|(bound_name, _)| bound_name == name).map(|(_, type_id)| *type_id)
+}
+
+fn bind_variable(env : &[(String, TypeId)], name : &str, type_id : TypeId) -> Vec<(String, TypeId)> {
+    let mut result = env.to_vec();
+    result.push((String::from(name), type_id));
+    result
+}
+
+//Builds the curried function type taking the given arguments to the given return type
+fn curried_func_type_id(ctxt : &Context, arg_type_ids : &[TypeId], ret_type_id : TypeId) -> Result<TypeId, FetishError> {
+    let mut result = ret_type_id;
+    for arg_type_id in arg_type_ids.iter().rev() {
+        result = ctxt.try_get_func_type_id(*arg_type_id, result)?;
+    }
+    Result::Ok(result)
+}
+
+//A resolved expression which may still mention lambda-bound variables,
+//with the type of every subexpression, ready for bracket abstraction
+enum TypedTerm {
+    Tree(ExpressionTree, TypeId),
+    Variable(String, TypeId),
+    Application(Box<TypedTerm>, Box<TypedTerm>, TypeId)
+}
+
+impl TypedTerm {
+    fn get_type(&self) -> TypeId {
+        match (self) {
+            TypedTerm::Tree(_, type_id) => *type_id,
+            TypedTerm::Variable(_, type_id) => *type_id,
+            TypedTerm::Application(_, _, type_id) => *type_id
+        }
+    }
+
+    fn mentions(&self, name : &str) -> bool {
+        match (self) {
+            TypedTerm::Tree(_, _) => false,
+            TypedTerm::Variable(bound_name, _) => bound_name == name,
+            TypedTerm::Application(func, arg, _) => func.mentions(name) || arg.mentions(name)
+        }
+    }
+
+    fn combinator(name : &str, type_id : TypeId, ctxt : &Context) -> Result<TypedTerm, FetishError> {
+        let primitive_ptr = ctxt.primitive_directory.find_primitives(name).into_iter()
+                                .find(|primitive_ptr| primitive_ptr.type_id == type_id)
+                                .ok_or_else(|| FetishError::MissingPrimitiveInstance {
+                                    name : String::from(name),
+                                    type_id
+                                })?;
+        Result::Ok(TypedTerm::Tree(ExpressionTree::Primitive(primitive_ptr), type_id))
+    }
+
+    //Applies the named combinator, whose final argument and return types are given, to the given arguments
+    fn apply_combinator(name : &str, args : Vec<TypedTerm>, in_type_id : TypeId, ret_type_id : TypeId,
+                        ctxt : &Context) -> Result<TypedTerm, FetishError> {
+        let mut arg_type_ids : Vec<TypeId> = args.iter().map(|arg| arg.get_type()).collect();
+        arg_type_ids.push(in_type_id);
+        let combinator_type_id = curried_func_type_id(ctxt, &arg_type_ids, ret_type_id)?;
+        let mut result = TypedTerm::combinator(name, combinator_type_id, ctxt)?;
+        for arg in args {
+            let result_type_id = ctxt.try_get_ret_type_id(result.get_type())?;
+            result = TypedTerm::Application(Box::new(result), Box::new(arg), result_type_id);
+        }
+        Result::Ok(result)
+    }
+
+    //Bracket abstraction: yields a term of type `arg -> self` without any mention of the named
+    //variable, which behaves like this term with its argument substituted for the variable
+    fn abstract_variable(self, name : &str, arg_type_id : TypeId, ctxt : &Context) -> Result<TypedTerm, FetishError> {
+        let ret_type_id = self.get_type();
+        match (self) {
+            TypedTerm::Variable(bound_name, _) if (bound_name == name) => {
+                TypedTerm::apply_combinator("I", Vec::new(), arg_type_id, ret_type_id, ctxt)
+            },
+            TypedTerm::Application(func, arg, _) if (func.mentions(name) || arg.mentions(name)) => {
+                if (!func.mentions(name)) {
+                    if let TypedTerm::Variable(_, _) = arg.as_ref() {
+                        //Eta-reduction, since the argument must be the bound variable
+                        return Result::Ok(*func);
+                    }
+                    let abstracted_arg = arg.abstract_variable(name, arg_type_id, ctxt)?;
+                    return TypedTerm::apply_combinator("B", vec![*func, abstracted_arg], arg_type_id, ret_type_id, ctxt);
+                }
+                let abstracted_func = func.abstract_variable(name, arg_type_id, ctxt)?;
+                if (!arg.mentions(name)) {
+                    return TypedTerm::apply_combinator("C", vec![abstracted_func, *arg], arg_type_id, ret_type_id, ctxt);
+                }
+                let abstracted_arg = arg.abstract_variable(name, arg_type_id, ctxt)?;
+                TypedTerm::apply_combinator("S", vec![abstracted_func, abstracted_arg], arg_type_id, ret_type_id, ctxt)
+            },
+            unmentioned => TypedTerm::apply_combinator("K", vec![unmentioned], arg_type_id, ret_type_id, ctxt)
+        }
+    }
+
+    fn into_expression_tree(self) -> Result<ExpressionTree, FetishError> {
+        match (self) {
+            TypedTerm::Tree(tree, _) => Result::Ok(tree),
+            TypedTerm::Variable(name, _) => Result::Err(FetishError::UnknownPrimitive(name)),
+            TypedTerm::Application(func, arg, _) => {
+                Result::Ok(ExpressionTree::apply(func.into_expression_tree()?, arg.into_expression_tree()?))
+            }
+        }
+    }
+}
+
+impl TermExpression {
+    ///Parses a [`TermExpression`] from the given text.
+    pub fn parse(text : &str) -> Result<TermExpression, FetishError> {
+        let mut parser = Parser {
+            chars : text.chars().collect(),
+            tokens : tokenize(text),
+            pos : 0
+        };
+        let result = parser.parse_expression()?;
+        if (parser.pos < parser.tokens.len()) {
+            return Result::Err(parse_error(parser.position(), "unexpected trailing input"));
+        }
+        Result::Ok(result)
+    }
+
+    ///Yields the [`TypeId`]s of every type which this [`TermExpression`] could have
+    ///in the given [`Context`], in increasing order. Yields an error if there are none.
+    pub fn get_possible_types(&self, ctxt : &Context) -> Result<Vec<TypeId>, FetishError> {
+        self.get_possible_types_in(ctxt, &[])
+    }
+
+    fn get_possible_types_in(&self, ctxt : &Context, env : &[(String, TypeId)]) -> Result<Vec<TypeId>, FetishError> {
+        let mut result = match (self) {
+            TermExpression::Primitive(name) => {
+                if let Option::Some(type_id) = lookup_variable(env, name) {
+                    return Result::Ok(vec![type_id]);
+                }
+                let mut type_ids : Vec<TypeId> = ctxt.primitive_directory.find_primitives(name).iter()
+                                                     .map(|primitive_ptr| primitive_ptr.type_id)
+                                                     .collect();
+                type_ids.extend(ctxt.constant_directory.find_constants(name).iter().map(|value| value.get_type()));
+                if (type_ids.is_empty()) {
+                    return Result::Err(FetishError::UnknownPrimitive(name.clone()));
+                }
+                type_ids
+            },
+            TermExpression::Vector(elements) => {
+                let matching : Vec<TypeId> = (0..ctxt.get_total_num_types())
+                    .filter(|type_id| ctxt.type_info_directory.try_get_dimension(*type_id) == Result::Ok(elements.len()))
+                    .collect();
+                if (matching.is_empty()) {
+                    return Result::Err(FetishError::MissingVecType(elements.len()));
+                }
+                matching
+            },
+            TermExpression::Application(func, arg) => {
+                let arg_types = arg.get_possible_types_in(ctxt, env)?;
+                func.get_possible_types_in(ctxt, env)?.iter()
+                    .filter_map(|func_type_id| ctxt.type_info_directory.try_get_arg_and_ret_type_ids(*func_type_id).ok())
+                    .filter(|(arg_type_id, _)| arg_types.contains(arg_type_id))
+                    .map(|(_, ret_type_id)| ret_type_id)
+                    .collect()
+            },
+            TermExpression::Annotated(expr, type_expr) => {
+                let type_id = type_expr.lookup(&ctxt.type_info_directory)?;
+                if (expr.get_possible_types_in(ctxt, env)?.contains(&type_id)) {
+                    vec![type_id]
+                } else {
+                    Vec::new()
+                }
+            },
+            TermExpression::Lambda(name, type_expr, body) => {
+                //Without an annotation, the variable may have the argument type of any function type
+                let arg_type_ids : Vec<TypeId> = match (type_expr) {
+                    Option::Some(type_expr) => vec![type_expr.lookup(&ctxt.type_info_directory)?],
+                    Option::None => {
+                        let mut arg_type_ids : Vec<TypeId> = (0..ctxt.get_total_num_types())
+                            .filter_map(|type_id| ctxt.try_get_arg_type_id(type_id).ok())
+                            .collect();
+                        arg_type_ids.sort_unstable();
+                        arg_type_ids.dedup();
+                        arg_type_ids
+                    }
+                };
+                let mut func_type_ids = Vec::new();
+                let mut body_error = Option::None;
+                for arg_type_id in arg_type_ids {
+                    let body_env = bind_variable(env, name, arg_type_id);
+                    match (body.get_possible_types_in(ctxt, &body_env)) {
+                        Result::Ok(ret_type_ids) => {
+                            for ret_type_id in ret_type_ids {
+                                if let Result::Ok(func_type_id) = ctxt.try_get_func_type_id(arg_type_id, ret_type_id) {
+                                    func_type_ids.push(func_type_id);
+                                }
+                            }
+                        },
+                        Result::Err(err) => body_error = Option::Some(err)
+                    }
+                }
+                if (func_type_ids.is_empty()) {
+                    if let Option::Some(err) = body_error {
+                        return Result::Err(err);
+                    }
+                }
+                func_type_ids
+            }
+        };
+        result.sort_unstable();
+        result.dedup();
+        if (result.is_empty()) {
+            return Result::Err(FetishError::IllTypedExpression(self.to_string()));
+        }
+        Result::Ok(result)
+    }
+
+    ///Type-checks this [`TermExpression`] against the given [`TypeId`], resolves every
+    ///primitive name in it and compiles away any lambda abstractions, yielding the
+    ///[`ExpressionTree`] that it denotes. Yields an error if the expression has no reading
+    ///at that type, or more than one.
+    pub fn resolve(&self, type_id : TypeId, ctxt : &Context) -> Result<ExpressionTree, FetishError> {
+        self.resolve_in(type_id, ctxt, &[])?.into_expression_tree()
+    }
+
+    fn resolve_in(&self, type_id : TypeId, ctxt : &Context, env : &[(String, TypeId)]) -> Result<TypedTerm, FetishError> {
+        match (self) {
+            TermExpression::Primitive(name) => {
+                if let Option::Some(variable_type_id) = lookup_variable(env, name) {
+                    if (variable_type_id != type_id) {
+                        return Result::Err(FetishError::IllTypedExpression(self.to_string()));
+                    }
+                    return Result::Ok(TypedTerm::Variable(name.clone(), type_id));
+                }
+                let mut trees : Vec<ExpressionTree> = ctxt.primitive_directory.find_primitives(name).into_iter()
+                                                          .filter(|primitive_ptr| primitive_ptr.type_id == type_id)
+                                                          .map(ExpressionTree::Primitive)
+                                                          .collect();
+                trees.extend(ctxt.constant_directory.find_constants(name).into_iter()
+                                 .filter(|value| value.get_type() == type_id)
+                                 .map(|value| ExpressionTree::Literal(value.clone())));
+                match (trees.len()) {
+                    0 => Result::Err(FetishError::IllTypedExpression(self.to_string())),
+                    1 => Result::Ok(TypedTerm::Tree(trees.pop().unwrap(), type_id)),
+                    _ => Result::Err(FetishError::AmbiguousExpression(self.to_string()))
+                }
+            },
+            TermExpression::Vector(elements) => {
+                let vec = Array::from(elements.clone());
+                let term_ref = TermReference::VecRef(type_id, to_noisy(vec.view()));
+                term_ref.check_type(type_id, ctxt)?;
+                Result::Ok(TypedTerm::Tree(ExpressionTree::Literal(term_ref), type_id))
+            },
+            TermExpression::Application(func, arg) => {
+                let arg_types = arg.get_possible_types_in(ctxt, env)?;
+                let func_types : Vec<(TypeId, TypeId)> = func.get_possible_types_in(ctxt, env)?.iter()
+                    .filter(|func_type_id| ctxt.type_info_directory.try_get_ret_type_id(**func_type_id) == Result::Ok(type_id))
+                    .map(|func_type_id| (*func_type_id, ctxt.get_arg_type_id(*func_type_id)))
+                    .filter(|(_, arg_type_id)| arg_types.contains(arg_type_id))
+                    .collect();
+                match (func_types.len()) {
+                    0 => Result::Err(FetishError::IllTypedExpression(self.to_string())),
+                    1 => {
+                        let (func_type_id, arg_type_id) = func_types[0];
+                        let resolved_func = func.resolve_in(func_type_id, ctxt, env)?;
+                        let resolved_arg = arg.resolve_in(arg_type_id, ctxt, env)?;
+                        Result::Ok(TypedTerm::Application(Box::new(resolved_func), Box::new(resolved_arg), type_id))
+                    },
+                    _ => Result::Err(FetishError::AmbiguousExpression(self.to_string()))
+                }
+            },
+            TermExpression::Annotated(expr, type_expr) => {
+                if (type_expr.lookup(&ctxt.type_info_directory)? != type_id) {
+                    return Result::Err(FetishError::IllTypedExpression(self.to_string()));
+                }
+                expr.resolve_in(type_id, ctxt, env)
+            },
+            TermExpression::Lambda(name, type_expr, body) => {
+                let (arg_type_id, ret_type_id) = ctxt.type_info_directory.try_get_arg_and_ret_type_ids(type_id)
+                                                     .map_err(|_| FetishError::IllTypedExpression(self.to_string()))?;
+                if let Option::Some(type_expr) = type_expr {
+                    if (type_expr.lookup(&ctxt.type_info_directory)? != arg_type_id) {
+                        return Result::Err(FetishError::IllTypedExpression(self.to_string()));
+                    }
+                }
+                let body_env = bind_variable(env, name, arg_type_id);
+                let resolved_body = body.resolve_in(ret_type_id, ctxt, &body_env)?;
+                resolved_body.abstract_variable(name, arg_type_id, ctxt)
+            }
+        }
+    }
+
+    ///Type-checks this [`TermExpression`] and evaluates it against the given [`InterpreterState`],
+    ///yielding a [`TermReference`] to the result and the [`NewlyEvaluatedTerms`] which arose from
+    ///evaluating it. Yields an error if the expression has no reading, or more than one.
+    pub fn evaluate(&self, state : &mut InterpreterState) -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+        let possible_types = self.get_possible_types(state.get_context())?;
+        if (possible_types.len() > 1) {
+            return Result::Err(FetishError::AmbiguousExpression(self.to_string()));
+        }
+        self.evaluate_as(state, possible_types[0])
+    }
+
+    ///Like [`Self::evaluate`], but requires the result to have the given [`TypeId`],
+    ///which may resolve ambiguities.
+    pub fn evaluate_as(&self, state : &mut InterpreterState, type_id : TypeId)
+                      -> Result<(TermReference, NewlyEvaluatedTerms), FetishError> {
+        let resolved = self.resolve(type_id, state.get_context())?;
+        resolved.evaluate(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context_builder::*;
+    use crate::primitive_template::*;
+    use crate::func_impl::*;
+    use crate::term_pointer::*;
+    use crate::test_utils::*;
+
+    fn evaluate_text(ctxt : &Context, text : &str) -> Result<TermReference, FetishError> {
+        let mut state = InterpreterState::new(ctxt);
+        let (result, _) = TermExpression::parse(text)?.evaluate(&mut state)?;
+        Result::Ok(result)
+    }
+
+    fn get_lambda_context() -> Context {
+        let templates = vec![Box::new(RotateTemplate {}) as Box<dyn PrimitiveTemplate>].into_iter()
+                            .chain(combinator_templates()).collect();
+        let mut builder = get_test_template_context_builder(&["(Vec2 -> Vec2) -> (Vec2 -> Vec2) -> Vec2 -> Vec2",
+                                                              "(Vec2 -> Vec2 -> Vec2) -> (Vec2 -> Vec2) -> Vec2 -> Vec2",
+                                                              "(Vec2 -> Vec2 -> Vec2) -> Vec2 -> Vec2 -> Vec2"],
+                                                            templates);
+        builder.add_binary_func(TEST_VECTOR_T, Box::new(AddOperator {}));
+        builder.add_binary_func(TEST_VECTOR_T, Box::new(SubOperator {}));
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn applications_are_left_associative() {
+        let expr = TermExpression::parse("compose rotate (rotate) [1.0, 2]").unwrap();
+        assert_eq!(expr.to_string(), "compose rotate rotate [1, 2]");
+        let nested = TermExpression::parse("rotate (rotate [1, 2])").unwrap();
+        assert_eq!(nested.to_string(), "rotate (rotate [1, 2])");
+    }
+
+    #[test]
+    fn expressions_evaluate() {
+        let ctxt = get_test_function_context();
+        assert_equal_vector_term(evaluate_text(&ctxt, "rotate [1.0, 2.0]").unwrap(), array![2.0f32, 1.0f32].view());
+        assert_equal_vector_term(evaluate_text(&ctxt, "+ [1, 2] (rotate [3, 4])").unwrap(),
+                                 array![5.0f32, 5.0f32].view());
+
+        let ctxt = get_test_compose_context();
+        assert_equal_vector_term(evaluate_text(&ctxt, "compose rotate rotate [1.0, 2.0]").unwrap(),
+                                 array![1.0f32, 2.0f32].view());
+        assert_equal_vector_term(evaluate_text(&ctxt, "(compose : (Vec2 -> Vec2) -> (Vec2 -> Vec2) -> Vec2 -> Vec2) rotate rotate [3, 4]").unwrap(),
+                                 array![3.0f32, 4.0f32].view());
+    }
+
+    #[test]
+    fn lambdas_parse() {
+        let expr = TermExpression::parse("\\x y. - y x").unwrap();
+        assert_eq!(expr.to_string(), "\\x. \\y. - y x");
+        let applied = TermExpression::parse("(\\(x : Vec2).rotate x) [1.5, .5]").unwrap();
+        assert_eq!(applied.to_string(), "(\\(x : Vec2). rotate x) [1.5, 0.5]");
+        assert_eq!(TermExpression::parse("\\. x").err(),
+                   Option::Some(FetishError::Parse { position : 1, message : String::from("expected a variable name") }));
+    }
+
+    #[test]
+    fn lambdas_compile_to_combinators() {
+        let ctxt = get_lambda_context();
+        assert_equal_vector_term(evaluate_text(&ctxt, "(\\x. rotate (rotate x)) [1, 2]").unwrap(),
+                                 array![1.0f32, 2.0f32].view());
+        assert_equal_vector_term(evaluate_text(&ctxt, "(\\x. + x x) [1, 2]").unwrap(),
+                                 array![2.0f32, 4.0f32].view());
+        assert_equal_vector_term(evaluate_text(&ctxt, "(\\x y. - y x) [1, 1] [3, 5]").unwrap(),
+                                 array![2.0f32, 4.0f32].view());
+        assert_equal_vector_term(evaluate_text(&ctxt, "(\\(x : Vec2). [1, 1]) [5, 5]").unwrap(),
+                                 array![1.0f32, 1.0f32].view());
+
+        let vector_func_t = ctxt.type_info_directory.get_func_type_id(TEST_VECTOR_T, TEST_VECTOR_T);
+        let rotate_ptr = ctxt.primitive_directory.find_primitives("rotate")[0];
+        let eta_reduced = TermExpression::parse("\\x. rotate x").unwrap().resolve(vector_func_t, &ctxt).unwrap();
+        assert!(eta_reduced == ExpressionTree::Primitive(rotate_ptr));
+    }
+
+    #[test]
+    fn compiled_lambdas_are_first_class_terms() {
+        let ctxt = get_lambda_context();
+        let mut state = InterpreterState::new(&ctxt);
+        let (result, newly_evaluated_terms) = TermExpression::parse("\\x. + x x").unwrap().evaluate(&mut state).unwrap();
+        let func_ptr = match (result) {
+            TermReference::FuncRef(func_ptr) => func_ptr,
+            _ => panic!()
+        };
+        let term = state.get(func_ptr);
+        assert_eq!(ctxt.get_primitive(term.func_ptr).get_name(), "S");
+        assert_eq!(term.args.len(), 2);
+        assert!(newly_evaluated_terms.terms.iter().any(|term_ptr| TermPointer::from(*term_ptr) == func_ptr));
+    }
+
+    #[test]
+    fn lambdas_need_combinator_instances() {
+        let ctxt = get_test_compose_context();
+        let b_type_id = ctxt.type_info_directory.get_func_type_id(
+                            ctxt.type_info_directory.get_func_type_id(TEST_VECTOR_T, TEST_VECTOR_T),
+                            ctxt.type_info_directory.get_func_type_id(
+                                ctxt.type_info_directory.get_func_type_id(TEST_VECTOR_T, TEST_VECTOR_T),
+                                ctxt.type_info_directory.get_func_type_id(TEST_VECTOR_T, TEST_VECTOR_T)));
+        assert_eq!(evaluate_text(&ctxt, "\\x. rotate (rotate x)").err(),
+                   Option::Some(FetishError::MissingPrimitiveInstance { name : String::from("B"), type_id : b_type_id }));
+    }
+
+    #[test]
+    fn named_constants_resolve_by_type() {
+        let mut builder = get_test_vector_only_context_builder();
+        let binary_t = builder.parse_type("Vec2 -> Vec2 -> Vec2").unwrap();
+        add_test_function_space(&mut builder, TEST_VECTOR_FUNC_T, 8);
+        add_test_function_space(&mut builder, binary_t, 8);
+        builder.add_binary_func(TEST_VECTOR_T, Box::new(AddOperator {}));
+        builder.add_standard_constants(TEST_SCALAR_T).unwrap();
+        builder.add_standard_constants(TEST_VECTOR_T).unwrap();
+        let ctxt = builder.build().unwrap();
+
+        assert!(evaluate_text(&ctxt, "+ one e0").unwrap() ==
+                TermReference::VecRef(TEST_VECTOR_T, to_noisy(array![2.0f32, 1.0f32].view())));
+        assert_eq!(evaluate_text(&ctxt, "zero").err(), Option::Some(FetishError::AmbiguousExpression(String::from("zero"))));
+        assert_eq!(evaluate_text(&ctxt, "(pi : Scalar)").unwrap().get_type(), TEST_SCALAR_T);
+    }
+
+    #[test]
+    fn partial_applications_yield_functions() {
+        let ctxt = get_test_function_context();
+        let result = evaluate_text(&ctxt, "+ [1, 2]").unwrap();
+        assert_eq!(result.get_type(), TEST_VECTOR_FUNC_T);
+    }
+
+    #[test]
+    fn malformed_expressions_are_reported() {
+        let ctxt = get_test_function_context();
+        assert_eq!(evaluate_text(&ctxt, "rotate [1.0, 2.0").err(),
+                   Option::Some(FetishError::Parse { position : 16, message : String::from("expected \",\" or \"]\"") }));
+        assert_eq!(evaluate_text(&ctxt, "negate [1.0, 2.0]").err(),
+                   Option::Some(FetishError::UnknownPrimitive(String::from("negate"))));
+        assert_eq!(evaluate_text(&ctxt, "rotate 1.0").err(),
+                   Option::Some(FetishError::IllTypedExpression(String::from("rotate [1]"))));
+        assert_eq!(evaluate_text(&ctxt, "(rotate : Vec2 -> Scalar) [1, 2]").err(),
+                   Option::Some(FetishError::MissingFuncType { arg_type_id : TEST_VECTOR_T, ret_type_id : TEST_SCALAR_T }));
+    }
+}
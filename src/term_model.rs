@@ -77,11 +77,11 @@ impl <'a> TermModel<'a> {
         self.model.get_total_dims()
     }
     ///See [`Model::sample`].
-    pub fn sample(&self, rng : &mut ThreadRng) -> Array2<f32> {
+    pub fn sample<R : Rng + ?Sized>(&self, rng : &mut R) -> Array2<f32> {
         self.model.sample(rng)
     }
     ///See [`Model::sample_as_vec`].
-    pub fn sample_as_vec(&self, rng : &mut ThreadRng) -> Array1::<f32> {
+    pub fn sample_as_vec<R : Rng + ?Sized>(&self, rng : &mut R) -> Array1::<f32> {
         self.model.sample_as_vec(rng)
     }
     ///See [`Model::get_mean_as_vec`].
@@ -167,6 +167,24 @@ impl <'a> TermModel<'a> {
         }
     }
 
+    ///Applies every prior and data update of the given [`TermModel`] which
+    ///hasn't already been applied to this [`TermModel`], so that this one
+    ///accounts for everything known about both.
+    pub fn absorb(&mut self, other : TermModel<'a>) {
+        for (update_key, distr) in other.prior_updates.into_iter() {
+            if (!self.prior_updates.contains_key(&update_key)) {
+                self.model.data += &distr;
+                self.prior_updates.insert(update_key, distr);
+            }
+        }
+        for (update_key, feat_update) in other.data_updates.into_iter() {
+            if (!self.data_updates.contains_key(&update_key)) {
+                self.model.data += &feat_update;
+                self.data_updates.insert(update_key, feat_update);
+            }
+        }
+    }
+
     ///Constructs a new [`TermModel`] for the given type with the given [`PriorSpecification`]
     ///within the given [`Context`].
     pub fn new(type_id : TypeId, prior_specification : &dyn PriorSpecification,
@@ -12,7 +12,7 @@ use serde::{Serialize, Deserialize};
 
 ///A pointer to an arbitrary primitive or non-primitive
 ///term within an [`InterpreterState`].
-#[derive(Copy, Clone, PartialEq, Hash, Eq, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Hash, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct TermPointer {
     pub type_id : TypeId,
     pub index : TermIndex
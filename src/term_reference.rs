@@ -8,13 +8,15 @@ use crate::type_id::*;
 use crate::term_pointer::*;
 use crate::displayable_with_state::*;
 use crate::interpreter_state::*;
+use crate::context::*;
+use crate::fetish_error::*;
 use noisy_float::prelude::*;
 
 use serde::{Serialize, Deserialize};
 
 ///A reference to an arbitrary term, which may belong to
-///a function [`Type`] or a vector [`Type`].
-///Vectors are stored inline here, whereas functions
+///a function [`Type`], a vector [`Type`] or a product [`Type`].
+///Vectors and tuples are stored inline here, whereas functions
 ///are stored as [`TermPointer`]s to the relevant
 ///[`crate::term::PartiallyAppliedTerm`]s in an [`InterpreterState`].
 #[derive(Clone, PartialEq, Hash, Eq, Serialize, Deserialize)]
@@ -22,7 +24,9 @@ pub enum TermReference {
     ///A [`TermPointer`] reference to a function
     FuncRef(TermPointer),
     ///A vector of the given [`TypeId`] with the given elements.
-    VecRef(TypeId, Array1<R32>)
+    VecRef(TypeId, Array1<R32>),
+    ///A tuple of the given product [`TypeId`] with the given components.
+    ProductRef(TypeId, Vec<TermReference>)
 }
 
 impl TermReference {
@@ -30,16 +34,82 @@ impl TermReference {
     pub fn get_type(&self) -> TypeId {
         match (&self) {
             TermReference::FuncRef(func_ptr) => func_ptr.type_id,
-            TermReference::VecRef(type_id, _) => *type_id
+            TermReference::VecRef(type_id, _) => *type_id,
+            TermReference::ProductRef(type_id, _) => *type_id
         }
     }
+
+    ///Returns true iff every vector element within this [`TermReference`],
+    ///including those nested within tuples, is finite.
+    pub fn is_finite(&self) -> bool {
+        match (self) {
+            TermReference::FuncRef(_) => true,
+            TermReference::VecRef(_, vec) => vec.iter().all(|val| val.raw().is_finite()),
+            TermReference::ProductRef(_, components) => components.iter().all(|component| component.is_finite())
+        }
+    }
+
+    ///Checks that this [`TermReference`] is a well-formed term of the given [`TypeId`]
+    ///in the given [`Context`]. That is, its [`TypeId`] must match, vectors must
+    ///have the number of elements declared by their `Type::VecType`, and tuples must
+    ///have well-formed components of the types declared by their `Type::ProductType`. Whether a `FuncRef`
+    ///points to a known term can only be checked against an [`InterpreterState`], see
+    ///[`InterpreterState::check_term_reference`].
+    pub fn check_type(&self, type_id : TypeId, ctxt : &Context) -> Result<(), FetishError> {
+        if (self.get_type() != type_id) {
+            return Result::Err(FetishError::TypeMismatch {
+                expected : type_id,
+                actual : self.get_type()
+            });
+        }
+        match (self) {
+            TermReference::FuncRef(_) => {
+                ctxt.try_get_arg_type_id(type_id)?;
+            },
+            TermReference::VecRef(_, vec) => {
+                let dim = ctxt.try_get_dimension(type_id)?;
+                if (vec.len() != dim) {
+                    return Result::Err(FetishError::DimensionMismatch {
+                        type_id,
+                        expected : dim,
+                        actual : vec.len()
+                    });
+                }
+            },
+            TermReference::ProductRef(_, components) => {
+                let component_type_ids = ctxt.type_info_directory.try_get_component_type_ids(type_id)?;
+                if (components.len() != component_type_ids.len()) {
+                    return Result::Err(FetishError::DimensionMismatch {
+                        type_id,
+                        expected : component_type_ids.len(),
+                        actual : components.len()
+                    });
+                }
+                for (component, component_type_id) in components.iter().zip(component_type_ids.iter()) {
+                    component.check_type(*component_type_id, ctxt)?;
+                }
+            }
+        }
+        Result::Ok(())
+    }
 }
 
 impl DisplayableWithState for TermReference {
     fn display(&self, state : &InterpreterState) -> String {
         match (self) {
             TermReference::FuncRef(ptr) => ptr.display(state),
-            TermReference::VecRef(_, vec) => vec.to_string()
+            TermReference::VecRef(type_id, vec) => {
+                match (state.get_context().type_info_directory.get_vector_metadata(*type_id)) {
+                    Option::Some(metadata) => format!("{} : {}", vec, metadata.name),
+                    Option::None => vec.to_string()
+                }
+            },
+            TermReference::ProductRef(_, components) => {
+                let displayed : Vec<String> = components.iter()
+                                                        .map(|component| component.display(state))
+                                                        .collect();
+                format!("({})", displayed.join(", "))
+            }
         }
     }
 }
@@ -30,68 +30,160 @@ use crate::fourier_feature_collection::*;
 use crate::sketched_linear_feature_collection::*;
 use crate::primitive_directory::*;
 use crate::rand_utils::*;
+use crate::context_builder::*;
+use crate::func_impl::*;
+use crate::primitive_template::*;
+use crate::interpreter_state::*;
+use crate::term_expression::*;
+use crate::term_pointer::*;
 
 ///A collection of crate-internal utilities for constructing tests.
 
 pub const TEST_VECTOR_T : TypeId = 1 as TypeId;
 pub const TEST_SCALAR_T : TypeId = 0 as TypeId;
 pub const TEST_VECTOR_SIZE : usize = 2;
+pub const TEST_VECTOR_FUNC_T : TypeId = 2 as TypeId;
+pub const TEST_BINARY_VECTOR_FUNC_T : TypeId = 3 as TypeId;
+pub const TEST_SEED : u64 = 1337;
 
-fn get_test_vector_only_type_info_directory() -> TypeInfoDirectory {
-    let mut result = TypeInfoDirectory::new();
-    result.add(Type::VecType(1));
-    result.add(Type::VecType(TEST_VECTOR_SIZE));
-    result
+pub fn add_test_vector_features(builder : &mut ContextBuilder, type_id : TypeId, base_dimensions : usize) {
+    builder.add_fourier_features(type_id, base_dimensions * 2, 1.0f32).unwrap();
+    builder.add_sketched_linear_features(type_id, base_dimensions * 2, 1.0f32).unwrap();
 }
 
-pub fn get_test_vector_only_feature_space_info(base_dimensions : usize) -> FeatureSpaceInfo {
-    let sketcher = Option::None; 
-    let mut feature_collections = Vec::<Box<dyn FeatureCollection>>::new();
-    let fourier_feature_collection = FourierFeatureCollection::new(base_dimensions, base_dimensions * 2, 1.0f32,
-                                                          gen_nsphere_random);
-    let sketched_linear_feature_collection = SketchedLinearFeatureCollection::new(base_dimensions, base_dimensions * 2, 1.0f32);
-    feature_collections.push(Box::new(fourier_feature_collection));
-    feature_collections.push(Box::new(sketched_linear_feature_collection));
-
-    let feature_dimensions = get_total_feat_dims(&feature_collections);
+pub fn get_test_vector_only_context_builder() -> ContextBuilder {
+    let mut builder = ContextBuilder::new(TEST_SEED);
+    builder.add_vector_type(1);
+    builder.add_vector_type(TEST_VECTOR_SIZE);
+    builder.add_type_alias("Scalar", TEST_SCALAR_T).unwrap();
+    builder.add_type_alias("Vec2", TEST_VECTOR_T).unwrap();
+    add_test_vector_features(&mut builder, TEST_SCALAR_T, 1);
+    add_test_vector_features(&mut builder, TEST_VECTOR_T, TEST_VECTOR_SIZE);
+    builder
+}
 
-    FeatureSpaceInfo {
-        base_dimensions,
-        feature_dimensions,
-        feature_collections,
-        sketcher
-    }
+pub fn get_test_vector_only_context() -> Context {
+    get_test_vector_only_context_builder().build().unwrap()
 }
 
-fn get_test_vector_only_space_info_directory() -> SpaceInfoDirectory {
-    let mut feature_spaces = Vec::new();
+pub fn add_test_function_space(builder : &mut ContextBuilder, type_id : TypeId, sketched_dimensions : usize) {
+    builder.add_fourier_features(type_id, sketched_dimensions, 1.0f32).unwrap();
+    builder.add_sketched_linear_features(type_id, sketched_dimensions, 1.0f32).unwrap();
+    builder.set_random_sketcher(type_id, sketched_dimensions, 1.0f32).unwrap();
+    builder.set_prior_specifications(type_id, Box::new(TestPriorSpecification {}),
+                                              Box::new(TestPriorSpecification {})).unwrap();
+}
 
-    feature_spaces.push(get_test_vector_only_feature_space_info(1));
-    feature_spaces.push(get_test_vector_only_feature_space_info(TEST_VECTOR_SIZE));
+///Adds a test function space to every function type registered with the given builder,
+///sketching `Scalar -> Scalar` down to 2 dimensions and every other function type to 8.
+pub fn add_test_function_spaces_for_all_func_types(builder : &mut ContextBuilder) {
+    let scalar_func_t = builder.get_type_info_directory().try_get_func_type_id(TEST_SCALAR_T, TEST_SCALAR_T).ok();
+    for type_id in 0..builder.get_type_info_directory().get_total_num_types() {
+        if (builder.get_type_info_directory().is_func_type(type_id)) {
+            let sketched_dimensions = if (Option::Some(type_id) == scalar_func_t) { 2 } else { 8 };
+            add_test_function_space(builder, type_id, sketched_dimensions);
+        }
+    }
+}
 
-    SpaceInfoDirectory {
-        feature_spaces
+///Constructs fresh copies of the primitives in [`get_test_function_context`].
+pub fn get_test_function_primitives() -> Vec<Box<dyn FuncImpl>> {
+    vec![Box::new(BinaryFuncImpl {
+             elem_type : TEST_VECTOR_T,
+             f : Box::new(AddOperator {})
+         }),
+         Box::new(RotateImpl {
+             vector_type : TEST_VECTOR_T
+         })]
+}
+
+///Builds a small [`Context`] with function types on top of the types of
+///[`get_test_vector_only_context`], namely the unary vector function type
+///[`TEST_VECTOR_FUNC_T`] and the binary vector function type [`TEST_BINARY_VECTOR_FUNC_T`],
+///with vector addition and rotation as primitives.
+pub fn get_test_function_context() -> Context {
+    let mut builder = get_test_vector_only_context_builder();
+    builder.add_func_type(TEST_VECTOR_T, TEST_VECTOR_T).unwrap();
+    builder.add_func_type(TEST_VECTOR_T, TEST_VECTOR_FUNC_T).unwrap();
+    add_test_function_space(&mut builder, TEST_VECTOR_FUNC_T, 8);
+    add_test_function_space(&mut builder, TEST_BINARY_VECTOR_FUNC_T, 16);
+
+    for primitive in get_test_function_primitives() {
+        builder.add_primitive(primitive);
     }
+    builder.build().unwrap()
+}
+
+///Builds a small [`Context`] with the product type `(Vec2, Scalar)` on top of the types of
+///[`get_test_vector_only_context`], with pair, fst and snd as primitives.
+///Yields the [`Context`] and the [`TypeId`] of the product type.
+pub fn get_test_product_context() -> (Context, TypeId) {
+    let mut builder = get_test_vector_only_context_builder();
+    let product_t = builder.add_product_type(&[TEST_VECTOR_T, TEST_SCALAR_T]).unwrap();
+    add_test_vector_features(&mut builder, product_t, TEST_VECTOR_SIZE + 1);
+    for type_text in ["Scalar -> (Vec2, Scalar)", "Vec2 -> Scalar -> (Vec2, Scalar)",
+                      "(Vec2, Scalar) -> Vec2", "(Vec2, Scalar) -> Scalar"].iter() {
+        let func_t = builder.parse_type(type_text).unwrap();
+        add_test_function_space(&mut builder, func_t, 8);
+    }
+    let type_info_directory = builder.get_type_info_directory();
+    let pair_impl = PairImpl::new(type_info_directory, TEST_VECTOR_T, TEST_SCALAR_T).unwrap();
+    let fst_impl = FstImpl::new(type_info_directory, product_t).unwrap();
+    let snd_impl = SndImpl::new(type_info_directory, product_t).unwrap();
+    builder.add_primitive(Box::new(pair_impl));
+    builder.add_primitive(Box::new(fst_impl));
+    builder.add_primitive(Box::new(snd_impl));
+    (builder.build().unwrap(), product_t)
+}
+
+///Builds a [`TypeInfoDirectory`] with just the types of [`get_test_vector_only_context`]
+///and their aliases, for tests which don't need feature spaces.
+pub fn get_test_type_info_directory() -> TypeInfoDirectory {
+    let mut result = TypeInfoDirectory::new();
+    let scalar_t = result.add(Type::VecType(1));
+    let vector_t = result.add(Type::VecType(TEST_VECTOR_SIZE));
+    result.add_type_alias("Scalar", scalar_t).unwrap();
+    result.add_type_alias("Vec2", vector_t).unwrap();
+    result
 }
 
-fn get_test_vector_only_prior_info_directory() -> PriorDirectory {
-    //Empty, because we have no function types
-    let priors = HashMap::new();
-    PriorDirectory {
-        priors
+///Starts a [`ContextBuilder`] with the types of [`get_test_vector_only_context`], the function
+///types written in the given type texts (and everything they mention), test function spaces
+///for all of those (see [`add_test_function_spaces_for_all_func_types`]), and the given
+///[`PrimitiveTemplate`]s.
+pub fn get_test_template_context_builder(type_texts : &[&str],
+                                         templates : Vec<Box<dyn PrimitiveTemplate>>) -> ContextBuilder {
+    let mut builder = get_test_vector_only_context_builder();
+    for type_text in type_texts.iter() {
+        builder.parse_type(type_text).unwrap();
     }
+    add_test_function_spaces_for_all_func_types(&mut builder);
+    for template in templates {
+        builder.add_primitive_template(template).unwrap();
+    }
+    builder
 }
 
-pub fn get_test_vector_only_context() -> Context {
-    let type_info_directory = get_test_vector_only_type_info_directory();
-    let space_info_directory = get_test_vector_only_space_info_directory();
-    let primitive_directory = PrimitiveDirectory::new(&type_info_directory);
-    let prior_directory = get_test_vector_only_prior_info_directory();
-    Context {
-        type_info_directory,
-        space_info_directory,
-        primitive_directory,
-        prior_directory
+///Builds the [`Context`] of [`get_test_template_context_builder`].
+pub fn get_test_template_context(type_texts : &[&str], templates : Vec<Box<dyn PrimitiveTemplate>>) -> Context {
+    get_test_template_context_builder(type_texts, templates).build().unwrap()
+}
+
+///Builds a [`Context`] with rotation and composition of `Vec2 -> Vec2` functions,
+///see [`get_test_template_context`].
+pub fn get_test_compose_context() -> Context {
+    get_test_template_context(&["(Vec2 -> Vec2) -> (Vec2 -> Vec2) -> Vec2 -> Vec2"],
+                              vec![Box::new(RotateTemplate {}), Box::new(ComposeTemplate {})])
+}
+
+///Evaluates the given term expression text as a function of the type given by the given
+///type text, yielding a pointer to the resulting function term.
+pub fn evaluate_test_func(state : &mut InterpreterState, type_text : &str, text : &str) -> TermPointer {
+    let type_id = state.get_context().parse_type(type_text).unwrap();
+    let (result_ref, _) = TermExpression::parse(text).unwrap().evaluate_as(state, type_id).unwrap();
+    match (result_ref) {
+        TermReference::FuncRef(func_ptr) => func_ptr,
+        _ => panic!()
     }
 }
 
@@ -1,30 +1,169 @@
 use std::collections::HashMap;
+use std::collections::HashSet;
 use crate::context::*;
 use crate::params::*;
 use crate::displayable_with_context::*;
+use crate::fetish_error::*;
+use crate::type_parser::*;
+use crate::vector_type_metadata::*;
 use std::fmt;
 use rand::prelude::*;
 
+use serde::{Serialize, Deserialize};
+
 ///A directory of [`Type`]s associating them to [`TypeId`]s, used as
 ///part of the specification of a [`Context`]. There can be multiple
 ///`Type::VecType`s with different [`TypeId`]s, but given argument
 ///and return [`TypeId`]s, this directory may only contain at most one
-///[`TypeId`] with the corresponding `Type::FuncType`.
+///[`TypeId`] with the corresponding `Type::FuncType`, and likewise for
+///`Type::ProductType`s with given component [`TypeId`]s. Types may additionally be
+///given human-readable aliases, which may be used in textual type expressions
+///(see [`TypeExpression`]), and vector types may carry [`VectorTypeMetadata`].
 pub struct TypeInfoDirectory {
     info_vec : Vec::<Type>,
     func_ind_map : HashMap<(TypeId, TypeId), TypeId>,
-    ret_map : HashMap::<TypeId, Vec::<(TypeId, TypeId)>>
+    product_ind_map : HashMap<Vec<TypeId>, TypeId>,
+    ret_map : HashMap::<TypeId, Vec::<(TypeId, TypeId)>>,
+    aliases : HashMap::<String, TypeId>,
+    vector_metadata : HashMap::<TypeId, VectorTypeMetadata>
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SerializedTypeInfoDirectory {
+    pub types : Vec<Type>,
+    #[serde(default)]
+    pub aliases : HashMap<String, TypeId>,
+    #[serde(default)]
+    pub vector_metadata : HashMap<TypeId, VectorTypeMetadata>
+}
+
+impl SerializedTypeInfoDirectory {
+    pub fn deserialize(mut self) -> TypeInfoDirectory {
+        let mut result = TypeInfoDirectory::new();
+        for info in self.types.drain(..) {
+            result.add(info);
+        }
+        result.aliases = self.aliases;
+        result.vector_metadata = self.vector_metadata;
+        result
+    }
 }
 
 impl TypeInfoDirectory {
+    pub fn serialize(&self) -> SerializedTypeInfoDirectory {
+        SerializedTypeInfoDirectory {
+            types : self.info_vec.clone(),
+            aliases : self.aliases.clone(),
+            vector_metadata : self.vector_metadata.clone()
+        }
+    }
+
     ///Creates an empty [`TypeInfoDirectory`].
     pub fn new() -> Self {
         TypeInfoDirectory {
             info_vec : Vec::new(),
             func_ind_map : HashMap::new(),
-            ret_map : HashMap::new()
+            product_ind_map : HashMap::new(),
+            ret_map : HashMap::new(),
+            aliases : HashMap::new(),
+            vector_metadata : HashMap::new()
+        }
+    }
+    ///Registers a new vector type with the given number of dimensions, described by the
+    ///given [`VectorTypeMetadata`], and returns its [`TypeId`]. The name in the metadata
+    ///becomes an alias for the type, and so it must not already be in use.
+    pub fn add_named_vector_type(&mut self, dimensions : usize, metadata : VectorTypeMetadata)
+                                -> Result<TypeId, FetishError> {
+        if (!is_valid_type_name(&metadata.name)) {
+            return Result::Err(FetishError::InvalidTypeName(metadata.name));
+        }
+        if (self.aliases.contains_key(&metadata.name)) {
+            return Result::Err(FetishError::DuplicateTypeName(metadata.name));
+        }
+        if let Option::Some(shape_dimensions) = metadata.get_shape_dimensions() {
+            if (shape_dimensions != dimensions) {
+                return Result::Err(FetishError::ShapeMismatch {
+                    name : metadata.name,
+                    expected : dimensions,
+                    actual : shape_dimensions
+                });
+            }
+        }
+        let type_id = self.add(Type::VecType(dimensions));
+        self.aliases.insert(metadata.name.clone(), type_id);
+        self.vector_metadata.insert(type_id, metadata);
+        Result::Ok(type_id)
+    }
+    ///Gets the [`VectorTypeMetadata`] for the given [`TypeId`], if it's a named vector type.
+    pub fn get_vector_metadata(&self, type_id : TypeId) -> Option<&VectorTypeMetadata> {
+        self.vector_metadata.get(&type_id)
+    }
+    ///Gets the [`TypeId`] of the type with the given name, which may be either the name
+    ///of a named vector type or an alias.
+    pub fn get_type_id_by_name(&self, name : &str) -> Result<TypeId, FetishError> {
+        self.get_type_alias(name).ok_or_else(|| FetishError::UnknownTypeName(String::from(name)))
+    }
+    ///Gets the name of the type with the given [`TypeId`], which is the name of a named
+    ///vector type, or otherwise an alias for the type. If there are several aliases,
+    ///the alphabetically-first one is used.
+    pub fn get_type_name(&self, type_id : TypeId) -> Option<&str> {
+        match (self.get_vector_metadata(type_id)) {
+            Option::Some(metadata) => Option::Some(&metadata.name),
+            Option::None => self.aliases.iter()
+                                .filter(|(_, alias_type_id)| **alias_type_id == type_id)
+                                .map(|(name, _)| name.as_str())
+                                .min()
+        }
+    }
+    ///Renders the type with the given [`TypeId`] in the syntax accepted by [`Self::parse_type`],
+    ///using the names of types (see [`Self::get_type_name`]) where available.
+    pub fn display_type(&self, type_id : TypeId) -> String {
+        if let Option::Some(name) = self.get_type_name(type_id) {
+            return String::from(name);
+        }
+        match (self.get_type(type_id)) {
+            Type::VecType(n) => format!("{}", n),
+            Type::FuncType(arg, ret) => format!("({} -> {})", self.display_type(arg), self.display_type(ret)),
+            Type::ProductType(components) => {
+                let displayed : Vec<String> = components.iter()
+                                                        .map(|component| self.display_type(*component))
+                                                        .collect();
+                format!("({})", displayed.join(", "))
+            }
+        }
+    }
+    ///Registers `name` as an alias for the given [`TypeId`], so that it may be used
+    ///in place of the type in [`Self::parse_type`]. Re-registering the same alias for
+    ///the same type is permitted, but an alias may not be re-bound to a different type.
+    pub fn add_type_alias(&mut self, name : &str, type_id : TypeId) -> Result<(), FetishError> {
+        self.try_get_type(type_id)?;
+        if (!is_valid_type_name(name)) {
+            return Result::Err(FetishError::InvalidTypeName(String::from(name)));
+        }
+        match (self.aliases.get(name)) {
+            Option::Some(existing_type_id) if (*existing_type_id != type_id) => {
+                Result::Err(FetishError::DuplicateTypeName(String::from(name)))
+            },
+            _ => {
+                self.aliases.insert(String::from(name), type_id);
+                Result::Ok(())
+            }
         }
     }
+    ///Gets the [`TypeId`] registered under the given alias, if any.
+    pub fn get_type_alias(&self, name : &str) -> Option<TypeId> {
+        self.aliases.get(name).copied()
+    }
+    ///Gets the first-registered [`TypeId`] for a vector type with the given
+    ///number of dimensions, if any.
+    pub fn get_vector_type_id(&self, dimensions : usize) -> Option<TypeId> {
+        self.info_vec.iter().position(|kind| *kind == Type::VecType(dimensions))
+    }
+    ///Parses a textual type expression such as `(2 -> (2 -> Scalar))` (see [`TypeExpression`]),
+    ///and yields its [`TypeId`], registering any function types which weren't already present.
+    pub fn parse_type(&mut self, text : &str) -> Result<TypeId, FetishError> {
+        TypeExpression::parse(text)?.register(self)
+    }
     ///Adds the given [`Type`] to this [`TypeInfoDirectory`], and
     ///returns the [`TypeId`] that it was assigned.
     pub fn add(&mut self, info : Type) -> TypeId {
@@ -43,10 +182,53 @@ impl TypeInfoDirectory {
             let ret_row = self.ret_map.get_mut(&ret_type).unwrap();
             ret_row.push((added_type_id, arg_type));
         }
+        if let Type::ProductType(components) = &info {
+            if let Option::Some(existing_type_id) = self.product_ind_map.get(components) {
+                return *existing_type_id;
+            }
+            self.product_ind_map.insert(components.clone(), added_type_id);
+        }
 
         self.info_vec.push(info); 
         added_type_id
     }
+    ///Registers every function type which can be built from the given base [`TypeId`]s
+    ///with at most `max_depth` levels of nested arrows, where base types have depth zero, and
+    ///`arg -> ret` has one more than the deeper of `arg` and `ret`. For instance, with a
+    ///`max_depth` of two and a single base type `1`, this registers `1 -> 1`,
+    ///`1 -> (1 -> 1)`, `(1 -> 1) -> 1` and `(1 -> 1) -> (1 -> 1)`.
+    ///Yields the [`TypeId`]s of the function types which weren't already registered,
+    ///in the order they were added, or an error if any of the base types are unknown.
+    ///Note that the number of generated types grows doubly-exponentially in `max_depth`.
+    pub fn add_func_type_closure(&mut self, base_type_ids : &[TypeId], max_depth : usize)
+                                -> Result<Vec<TypeId>, FetishError> {
+        let mut closure : Vec<TypeId> = Vec::new();
+        let mut in_closure : HashSet<TypeId> = HashSet::new();
+        for base_type_id in base_type_ids.iter() {
+            self.try_get_type(*base_type_id)?;
+            if (in_closure.insert(*base_type_id)) {
+                closure.push(*base_type_id);
+            }
+        }
+
+        let mut generated = Vec::new();
+        for _ in 0..max_depth {
+            let previous_closure = closure.clone();
+            for arg_type_id in previous_closure.iter() {
+                for ret_type_id in previous_closure.iter() {
+                    let is_new = !self.has_func_type(*arg_type_id, *ret_type_id);
+                    let func_type_id = self.add(Type::FuncType(*arg_type_id, *ret_type_id));
+                    if (is_new) {
+                        generated.push(func_type_id);
+                    }
+                    if (in_closure.insert(func_type_id)) {
+                        closure.push(func_type_id);
+                    }
+                }
+            }
+        }
+        Result::Ok(generated)
+    }
     ///Gets the total number of types stored in this [`TypeInfoDirectory`].
     ///Since [`TypeId`]s are allocated consecutively, it's correct to use
     ///this as an iteration bound if you want to iterate over all [`TypeId`]s stored here.
@@ -61,13 +243,30 @@ impl TypeInfoDirectory {
     }
     ///Assuming that there is a [`TypeId`] for a function from the given argument [`TypeId`]
     ///to the given return [`TypeId`], yields the [`TypeId`] of the function type.
+    ///See [`Self::try_get_func_type_id`] for a non-panicking version.
     pub fn get_func_type_id(&self, arg_type_id : TypeId, ret_type_id : TypeId) -> TypeId {
+        self.try_get_func_type_id(arg_type_id, ret_type_id).unwrap()
+    }
+    ///Yields the [`TypeId`] of the function type from the given argument [`TypeId`]
+    ///to the given return [`TypeId`], or [`FetishError::MissingFuncType`] if there is none.
+    pub fn try_get_func_type_id(&self, arg_type_id : TypeId, ret_type_id : TypeId) -> Result<TypeId, FetishError> {
         let pair = (arg_type_id, ret_type_id);
-        *self.func_ind_map.get(&pair).unwrap()
+        match (self.func_ind_map.get(&pair)) {
+            Option::Some(func_type_id) => Result::Ok(*func_type_id),
+            Option::None => Result::Err(FetishError::MissingFuncType { arg_type_id, ret_type_id })
+        }
     }
     ///Gets the [`Type`] information stored for the given [`TypeId`].
     pub fn get_type(&self, id : TypeId) -> Type {
-        self.info_vec[id]
+        self.info_vec[id].clone()
+    }
+    ///Gets the [`Type`] information stored for the given [`TypeId`], or
+    ///[`FetishError::UnknownType`] if it was never registered.
+    pub fn try_get_type(&self, id : TypeId) -> Result<Type, FetishError> {
+        match (self.info_vec.get(id)) {
+            Option::Some(kind) => Result::Ok(kind.clone()),
+            Option::None => Result::Err(FetishError::UnknownType(id))
+        }
     }
     ///Gets all `(func_type_id, arg_type_id)` pairs which may be applied to yield the
     ///given [`TypeId`].
@@ -75,39 +274,79 @@ impl TypeInfoDirectory {
         self.ret_map.get(&id).unwrap().clone()
     }
     ///Given the [`TypeId`] of a function type, yields the [`TypeId`] of the return type.
+    ///See [`Self::try_get_ret_type_id`] for a non-panicking version.
     pub fn get_ret_type_id(&self, func_type_id : TypeId) -> TypeId {
-        let func_type = self.get_type(func_type_id);
-        if let Type::FuncType(_, ret_type_id) = func_type {
-            ret_type_id
-        } else {
-            panic!();
-        }
+        self.try_get_ret_type_id(func_type_id).unwrap()
+    }
+    ///Given the [`TypeId`] of a function type, yields the [`TypeId`] of the return type,
+    ///or an error if the given [`TypeId`] isn't a registered function type.
+    pub fn try_get_ret_type_id(&self, func_type_id : TypeId) -> Result<TypeId, FetishError> {
+        let (_, ret_type_id) = self.try_get_arg_and_ret_type_ids(func_type_id)?;
+        Result::Ok(ret_type_id)
     }
     ///Returns true iff the given [`TypeId`] points to a `Type::VecType`.
     pub fn is_vector_type(&self, id : TypeId) -> bool {
-        let kind = self.get_type(id);
-        match (kind) {
-            Type::FuncType(_, _) => false,
-            Type::VecType(_) => true
+        matches!(self.info_vec[id], Type::VecType(_))
+    }
+    ///Returns true iff the given [`TypeId`] points to a `Type::FuncType`.
+    pub fn is_func_type(&self, id : TypeId) -> bool {
+        matches!(self.info_vec[id], Type::FuncType(_, _))
+    }
+    ///Returns true iff the given [`TypeId`] points to a `Type::ProductType`.
+    pub fn is_product_type(&self, id : TypeId) -> bool {
+        matches!(self.info_vec[id], Type::ProductType(_))
+    }
+    ///Returns true iff this [`TypeInfoDirectory`] has a registered [`TypeId`] for
+    ///`Type::ProductType(components)`.
+    pub fn has_product_type(&self, components : &[TypeId]) -> bool {
+        self.product_ind_map.contains_key(components)
+    }
+    ///Yields the [`TypeId`] of the product type with the given components,
+    ///or [`FetishError::MissingProductType`] if there is none.
+    pub fn try_get_product_type_id(&self, components : &[TypeId]) -> Result<TypeId, FetishError> {
+        match (self.product_ind_map.get(components)) {
+            Option::Some(product_type_id) => Result::Ok(*product_type_id),
+            Option::None => Result::Err(FetishError::MissingProductType(components.to_vec()))
+        }
+    }
+    ///Given the [`TypeId`] of a product type, yields the [`TypeId`]s of its components,
+    ///or an error if the given [`TypeId`] isn't a registered product type.
+    pub fn try_get_component_type_ids(&self, product_type_id : TypeId) -> Result<Vec<TypeId>, FetishError> {
+        match (self.try_get_type(product_type_id)?) {
+            Type::ProductType(components) => Result::Ok(components),
+            _ => Result::Err(FetishError::NotAProductType(product_type_id))
         }
     }
     ///Given the [`TypeId`] of a function type, yields the [`TypeId`] of the argument type.
+    ///See [`Self::try_get_arg_type_id`] for a non-panicking version.
     pub fn get_arg_type_id(&self, func_type_id : TypeId) -> TypeId {
-        let func_type = self.get_type(func_type_id);
-        if let Type::FuncType(arg_type_id, _) = func_type {
-            arg_type_id
-        } else {
-            panic!();
+        self.try_get_arg_type_id(func_type_id).unwrap()
+    }
+    ///Given the [`TypeId`] of a function type, yields the [`TypeId`] of the argument type,
+    ///or an error if the given [`TypeId`] isn't a registered function type.
+    pub fn try_get_arg_type_id(&self, func_type_id : TypeId) -> Result<TypeId, FetishError> {
+        let (arg_type_id, _) = self.try_get_arg_and_ret_type_ids(func_type_id)?;
+        Result::Ok(arg_type_id)
+    }
+    ///Given the [`TypeId`] of a function type, yields the [`TypeId`]s of the argument
+    ///and return types, or an error if the given [`TypeId`] isn't a registered function type.
+    pub fn try_get_arg_and_ret_type_ids(&self, func_type_id : TypeId) -> Result<(TypeId, TypeId), FetishError> {
+        match (self.try_get_type(func_type_id)?) {
+            Type::FuncType(arg_type_id, ret_type_id) => Result::Ok((arg_type_id, ret_type_id)),
+            _ => Result::Err(FetishError::NotAFunctionType(func_type_id))
         }
     }
     ///Assuming that the given [`TypeId`] points to a `Type::VecType`, yields the
     ///declared number of dimensions for that type's base space.
     pub fn get_dimension(&self, vec_type_id : TypeId) -> usize {
-        let vec_type = self.get_type(vec_type_id);
-        if let Type::VecType(dim) = vec_type {
-            dim
-        } else {
-            panic!();
+        self.try_get_dimension(vec_type_id).unwrap()
+    }
+    ///Yields the declared number of dimensions for the given vector type's base space,
+    ///or an error if the given [`TypeId`] isn't a registered vector type.
+    pub fn try_get_dimension(&self, vec_type_id : TypeId) -> Result<usize, FetishError> {
+        match (self.try_get_type(vec_type_id)?) {
+            Type::VecType(dim) => Result::Ok(dim),
+            _ => Result::Err(FetishError::NotAVectorType(vec_type_id))
         }
     }
 }
@@ -122,22 +361,35 @@ pub type TypeId = usize;
 
 ///Fundamental information about a type, generally indexed by a [`TypeId`] and
 ///stored in a [`TypeInfoDirectory`].
-#[derive(Copy, Eq, PartialEq, Hash, Debug, Clone)]
+#[derive(Eq, PartialEq, Hash, Debug, Clone, Serialize, Deserialize)]
 pub enum Type {
     ///A type for vectors with the given declared number of dimensions for their base space
     VecType(usize),
     ///A type for functions which map elements of the former [`TypeId`] to the latter [`TypeId`],
     ///which is consequently only truly meaningful in the context of a [`TypeInfoDirectory`].
-    FuncType(TypeId, TypeId)
+    FuncType(TypeId, TypeId),
+    ///A type for tuples whose components are of the given [`TypeId`]s, in order.
+    ///The base space of a product type is the concatenation of the compressed
+    ///spaces of its components.
+    ProductType(Vec<TypeId>)
 }
 
 impl DisplayableWithContext for Type {
+    ///Renders this [`Type`], naming its component types as [`TypeInfoDirectory::display_type`] does.
+    ///Vector types are rendered by their dimension, since several named vector types may share it,
+    ///so a type whose [`TypeId`] is known should be rendered via [`TypeInfoDirectory::display_type`].
     fn display(&self, ctxt : &Context) -> String {
         match (self) {
             Type::VecType(n) => format!("{}", n),
             Type::FuncType(arg, ret) => format!("({} -> {})", 
-                                        ctxt.get_type(*arg).display(ctxt), 
-                                        ctxt.get_type(*ret).display(ctxt))
+                                        ctxt.display_type(*arg), 
+                                        ctxt.display_type(*ret)),
+            Type::ProductType(components) => {
+                let displayed : Vec<String> = components.iter()
+                                                        .map(|component| ctxt.display_type(*component))
+                                                        .collect();
+                format!("({})", displayed.join(", "))
+            }
         }
     }
 }
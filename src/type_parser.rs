@@ -0,0 +1,310 @@
+use std::fmt;
+use crate::type_id::*;
+use crate::fetish_error::*;
+
+///Parsed, but not-yet-resolved, textual type expression. The syntax is the one
+///used when displaying [`Type`]s, namely a vector type is written as its number of
+///dimensions (or as an alias registered with [`TypeInfoDirectory::add_type_alias`]),
+///a function type is written as `(arg -> ret)`, and a product type is written as
+///`(fst, snd)`. The arrow is right-associative, and so `2 -> 2 -> 1` is the same
+///as `(2 -> (2 -> 1))`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TypeExpression {
+    ///A vector type with the given number of dimensions.
+    Vector(usize),
+    ///A type referred to by an alias.
+    Named(String),
+    ///A function type from the former to the latter type.
+    Func(Box<TypeExpression>, Box<TypeExpression>),
+    ///A product type with the given components.
+    Product(Vec<TypeExpression>)
+}
+
+impl fmt::Display for TypeExpression {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self) {
+            TypeExpression::Vector(dimensions) => write!(f, "{}", dimensions),
+            TypeExpression::Named(name) => write!(f, "{}", name),
+            TypeExpression::Func(arg, ret) => write!(f, "({} -> {})", arg, ret),
+            TypeExpression::Product(components) => {
+                let displayed : Vec<String> = components.iter()
+                                                        .map(|component| component.to_string())
+                                                        .collect();
+                write!(f, "({})", displayed.join(", "))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    OpenParen,
+    CloseParen,
+    Arrow,
+    Comma,
+    Number(usize),
+    Ident(String)
+}
+
+///Returns true iff the given string could be used as the name of a type in
+///a [`TypeExpression`]. Names start with a letter or underscore, and continue
+///with letters, digits or underscores.
+pub fn is_valid_type_name(name : &str) -> bool {
+    let mut chars = name.chars();
+    match (chars.next()) {
+        Option::Some(c) if (c.is_ascii_alphabetic() || c == '_') => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        },
+        _ => false
+    }
+}
+
+///Returns true iff the given type name stands for a type variable in the signature
+///of a [`crate::primitive_template::PrimitiveTemplate`], namely if it's a valid type
+///name which starts with a lowercase letter.
+pub fn is_type_variable_name(name : &str) -> bool {
+    is_valid_type_name(name) && name.starts_with(|c : char| c.is_ascii_lowercase())
+}
+
+fn parse_error(position : usize, message : &str) -> FetishError {
+    FetishError::Parse {
+        position,
+        message : String::from(message)
+    }
+}
+
+fn tokenize(text : &str) -> Result<Vec<(usize, Token)>, FetishError> {
+    let chars : Vec<char> = text.chars().collect();
+    let mut result = Vec::new();
+    let mut i = 0;
+    while (i < chars.len()) {
+        let c = chars[i];
+        if (c.is_whitespace()) {
+            i += 1;
+        } else if (c == '(') {
+            result.push((i, Token::OpenParen));
+            i += 1;
+        } else if (c == ')') {
+            result.push((i, Token::CloseParen));
+            i += 1;
+        } else if (c == ',') {
+            result.push((i, Token::Comma));
+            i += 1;
+        } else if (c == '-') {
+            if (chars.get(i + 1) != Option::Some(&'>')) {
+                return Result::Err(parse_error(i, "expected \"->\""));
+            }
+            result.push((i, Token::Arrow));
+            i += 2;
+        } else if (c.is_ascii_digit()) {
+            let start = i;
+            while (i < chars.len() && chars[i].is_ascii_digit()) {
+                i += 1;
+            }
+            let digits : String = chars[start..i].iter().collect();
+            let dimensions = digits.parse::<usize>()
+                                   .map_err(|_| parse_error(start, "dimension is too large"))?;
+            result.push((start, Token::Number(dimensions)));
+        } else if (c.is_ascii_alphabetic() || c == '_') {
+            let start = i;
+            while (i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_')) {
+                i += 1;
+            }
+            result.push((start, Token::Ident(chars[start..i].iter().collect())));
+        } else {
+            return Result::Err(parse_error(i, "unexpected character"));
+        }
+    }
+    Result::Ok(result)
+}
+
+struct Parser {
+    tokens : Vec<(usize, Token)>,
+    pos : usize,
+    end : usize
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, token)| token)
+    }
+
+    fn position(&self) -> usize {
+        match (self.tokens.get(self.pos)) {
+            Option::Some((position, _)) => *position,
+            Option::None => self.end
+        }
+    }
+
+    fn parse_type(&mut self) -> Result<TypeExpression, FetishError> {
+        let arg = self.parse_atom()?;
+        if (self.peek() == Option::Some(&Token::Arrow)) {
+            self.pos += 1;
+            let ret = self.parse_type()?;
+            Result::Ok(TypeExpression::Func(Box::new(arg), Box::new(ret)))
+        } else {
+            Result::Ok(arg)
+        }
+    }
+
+    fn parse_atom(&mut self) -> Result<TypeExpression, FetishError> {
+        let position = self.position();
+        let token = self.peek().cloned();
+        self.pos += 1;
+        match (token) {
+            Option::Some(Token::Number(dimensions)) => Result::Ok(TypeExpression::Vector(dimensions)),
+            Option::Some(Token::Ident(name)) => Result::Ok(TypeExpression::Named(name)),
+            Option::Some(Token::OpenParen) => {
+                let mut components = vec![self.parse_type()?];
+                while (self.peek() == Option::Some(&Token::Comma)) {
+                    self.pos += 1;
+                    components.push(self.parse_type()?);
+                }
+                if (self.peek() != Option::Some(&Token::CloseParen)) {
+                    return Result::Err(parse_error(self.position(), "expected \")\""));
+                }
+                self.pos += 1;
+                if (components.len() == 1) {
+                    Result::Ok(components.pop().unwrap())
+                } else {
+                    Result::Ok(TypeExpression::Product(components))
+                }
+            },
+            Option::Some(_) => Result::Err(parse_error(position, "expected a type")),
+            Option::None => Result::Err(parse_error(position, "unexpected end of input"))
+        }
+    }
+}
+
+impl TypeExpression {
+    ///Parses a [`TypeExpression`] from the given text, such as `(2 -> (2 -> Scalar))`.
+    pub fn parse(text : &str) -> Result<TypeExpression, FetishError> {
+        let mut parser = Parser {
+            tokens : tokenize(text)?,
+            pos : 0,
+            end : text.chars().count()
+        };
+        let result = parser.parse_type()?;
+        if (parser.pos < parser.tokens.len()) {
+            return Result::Err(parse_error(parser.position(), "unexpected trailing input"));
+        }
+        Result::Ok(result)
+    }
+
+    ///Resolves this [`TypeExpression`] to a [`TypeId`] in the given [`TypeInfoDirectory`],
+    ///registering any function or product types along the way which weren't already present.
+    ///Vector types and aliases must already be registered.
+    pub fn register(&self, type_info_directory : &mut TypeInfoDirectory) -> Result<TypeId, FetishError> {
+        match (self) {
+            TypeExpression::Func(arg, ret) => {
+                let arg_type_id = arg.register(type_info_directory)?;
+                let ret_type_id = ret.register(type_info_directory)?;
+                Result::Ok(type_info_directory.add(Type::FuncType(arg_type_id, ret_type_id)))
+            },
+            TypeExpression::Product(components) => {
+                let mut component_type_ids = Vec::new();
+                for component in components.iter() {
+                    component_type_ids.push(component.register(type_info_directory)?);
+                }
+                Result::Ok(type_info_directory.add(Type::ProductType(component_type_ids)))
+            },
+            _ => self.lookup(type_info_directory)
+        }
+    }
+
+    ///Resolves this [`TypeExpression`] to a [`TypeId`] in the given [`TypeInfoDirectory`],
+    ///without registering anything. Yields an error if any of the mentioned types are missing.
+    ///A dimension resolves to the first-registered vector type with that many dimensions.
+    pub fn lookup(&self, type_info_directory : &TypeInfoDirectory) -> Result<TypeId, FetishError> {
+        match (self) {
+            TypeExpression::Vector(dimensions) => {
+                type_info_directory.get_vector_type_id(*dimensions)
+                                   .ok_or(FetishError::MissingVecType(*dimensions))
+            },
+            TypeExpression::Named(name) => {
+                type_info_directory.get_type_id_by_name(name)
+            },
+            TypeExpression::Func(arg, ret) => {
+                let arg_type_id = arg.lookup(type_info_directory)?;
+                let ret_type_id = ret.lookup(type_info_directory)?;
+                type_info_directory.try_get_func_type_id(arg_type_id, ret_type_id)
+            },
+            TypeExpression::Product(components) => {
+                let mut component_type_ids = Vec::new();
+                for component in components.iter() {
+                    component_type_ids.push(component.lookup(type_info_directory)?);
+                }
+                type_info_directory.try_get_product_type_id(&component_type_ids)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::displayable_with_context::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn arrows_are_right_associative() {
+        let nested = TypeExpression::parse("(2 -> (2 -> 1))").unwrap();
+        let flat = TypeExpression::parse("2 -> 2 -> 1").unwrap();
+        assert_eq!(nested, flat);
+    }
+
+    #[test]
+    fn missing_function_types_are_registered() {
+        let mut type_info_directory = get_test_type_info_directory();
+        let type_id = type_info_directory.parse_type("Vec2 -> (Vec2 -> Scalar)").unwrap();
+        assert_eq!(type_info_directory.get_total_num_types(), 4);
+
+        let inner_type_id = type_info_directory.get_func_type_id(1, 0);
+        assert_eq!(type_info_directory.get_type(type_id), Type::FuncType(1, inner_type_id));
+        assert_eq!(type_info_directory.parse_type("(2 -> (2 -> 1))").unwrap(), type_id);
+    }
+
+    #[test]
+    fn lookup_does_not_register() {
+        let type_info_directory = get_test_type_info_directory();
+        let expr = TypeExpression::parse("(1 -> 1)").unwrap();
+        assert_eq!(expr.lookup(&type_info_directory).err(),
+                   Option::Some(FetishError::MissingFuncType { arg_type_id : 0, ret_type_id : 0 }));
+    }
+
+    #[test]
+    fn malformed_types_are_reported() {
+        let mut type_info_directory = get_test_type_info_directory();
+        assert_eq!(type_info_directory.parse_type("(2 -> 1").err(),
+                   Option::Some(FetishError::Parse { position : 7, message : String::from("expected \")\"") }));
+        assert_eq!(type_info_directory.parse_type("Matrix").err(),
+                   Option::Some(FetishError::UnknownTypeName(String::from("Matrix"))));
+        assert_eq!(type_info_directory.parse_type("3").err(),
+                   Option::Some(FetishError::MissingVecType(3)));
+    }
+
+    #[test]
+    fn product_types_are_registered() {
+        let mut type_info_directory = get_test_type_info_directory();
+        let type_id = type_info_directory.parse_type("(Vec2, Scalar) -> (Scalar, Vec2)").unwrap();
+        let arg_type_id = type_info_directory.try_get_product_type_id(&[1, 0]).unwrap();
+        let ret_type_id = type_info_directory.try_get_product_type_id(&[0, 1]).unwrap();
+        assert_eq!(type_info_directory.get_type(type_id), Type::FuncType(arg_type_id, ret_type_id));
+        assert_eq!(type_info_directory.display_type(type_id), "((Vec2, Scalar) -> (Scalar, Vec2))");
+        assert_eq!(type_info_directory.parse_type("((2, 1) -> (1, 2))").unwrap(), type_id);
+        assert_eq!(TypeExpression::parse("(1, 1)").unwrap().lookup(&type_info_directory).err(),
+                   Option::Some(FetishError::MissingProductType(vec![0, 0])));
+    }
+
+    #[test]
+    fn displayed_types_parse_back() {
+        let ctxt = get_test_function_context();
+        for type_id in 0..ctxt.get_total_num_types() {
+            let text = ctxt.get_type(type_id).display(&ctxt);
+            assert_eq!(ctxt.parse_type(&text).unwrap(), type_id);
+        }
+        assert_eq!(ctxt.parse_type("Vec2 -> Vec2").unwrap(), TEST_VECTOR_FUNC_T);
+        assert_eq!(ctxt.get_type(TEST_VECTOR_FUNC_T).display(&ctxt), "(Vec2 -> Vec2)");
+        assert_eq!(ctxt.display_type(TEST_SCALAR_T), "Scalar");
+    }
+}
@@ -39,6 +39,11 @@ impl TypeSpace {
         &self.terms[term_index]
     }
 
+    ///If the given term was previously added to this type-space, yields its index.
+    pub fn get_index(&self, term : &PartiallyAppliedTerm) -> Option<usize> {
+        self.term_to_index_map.get(term).copied()
+    }
+
     ///Adds a given term to this type-space if it doesn't
     ///already exist in that space, otherwise returns a reference
     ///to the previously-added term
@@ -59,4 +64,12 @@ impl TypeSpace {
             }
         }
     }
+
+    ///Removes the most-recently added term from this type-space, if any, and yields it.
+    ///Used to undo the effects of a failed evaluation.
+    pub fn remove_last(&mut self) -> Option<PartiallyAppliedTerm> {
+        let term = self.terms.pop()?;
+        self.term_to_index_map.remove(&term);
+        Option::Some(term)
+    }
 }
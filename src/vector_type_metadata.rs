@@ -0,0 +1,113 @@
+use serde::{Serialize, Deserialize};
+
+///Descriptive information about a vector type, attached to its [`crate::type_id::TypeId`]
+///in a [`crate::type_id::TypeInfoDirectory`] via
+///[`crate::type_id::TypeInfoDirectory::add_named_vector_type`]. This allows distinguishing
+///vector types of the same dimension which stand for different things, such as
+///8x8 images and 64-sample signals. The name doubles as an alias for the type
+///in textual type expressions, and is used when displaying types and vectors.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct VectorTypeMetadata {
+    ///The name of the vector type, which must be a valid type name
+    ///(see [`crate::type_parser::is_valid_type_name`]).
+    pub name : String,
+    ///The shape that vectors of this type are flattened from, if any, such as `[8, 8]`
+    ///for 8x8 images. The product of the shape must be the dimension of the type.
+    pub shape : Option<Vec<usize>>,
+    ///The inclusive `(min, max)` range of values that elements of vectors of this type
+    ///are expected to lie within, if any.
+    pub value_range : Option<(f32, f32)>,
+    ///The unit of measurement for the elements of vectors of this type, if any.
+    pub unit : Option<String>
+}
+
+impl VectorTypeMetadata {
+    ///Constructs [`VectorTypeMetadata`] with the given name, and no other information.
+    pub fn new(name : &str) -> VectorTypeMetadata {
+        VectorTypeMetadata {
+            name : String::from(name),
+            shape : Option::None,
+            value_range : Option::None,
+            unit : Option::None
+        }
+    }
+
+    ///Gets the number of dimensions implied by the shape of this [`VectorTypeMetadata`], if any.
+    pub fn get_shape_dimensions(&self) -> Option<usize> {
+        self.shape.as_ref().map(|shape| shape.iter().product())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::type_id::*;
+    use crate::fetish_error::*;
+    use crate::context::*;
+    use crate::context_builder::*;
+    use crate::interpreter_state::*;
+    use crate::displayable_with_state::*;
+    use crate::displayable_with_context::*;
+    use crate::term_reference::*;
+    use crate::array_utils::*;
+    use crate::test_utils::*;
+    use ndarray::*;
+
+    fn image_and_signal_context() -> (Context, TypeId, TypeId) {
+        let mut builder = ContextBuilder::new(TEST_SEED);
+        let image_metadata = VectorTypeMetadata {
+            shape : Option::Some(vec![2, 2]),
+            value_range : Option::Some((0.0f32, 1.0f32)),
+            ..VectorTypeMetadata::new("Image")
+        };
+        let signal_metadata = VectorTypeMetadata {
+            unit : Option::Some(String::from("volts")),
+            ..VectorTypeMetadata::new("Signal")
+        };
+        let image_t = builder.add_named_vector_type(4, image_metadata).unwrap();
+        let signal_t = builder.add_named_vector_type(4, signal_metadata).unwrap();
+        let func_t = builder.parse_type("Image -> Signal").unwrap();
+        add_test_vector_features(&mut builder, image_t, 4);
+        add_test_vector_features(&mut builder, signal_t, 4);
+        add_test_function_space(&mut builder, func_t, 8);
+        (builder.build().unwrap(), image_t, signal_t)
+    }
+
+    #[test]
+    fn same_dimension_types_are_distinguished_by_name() {
+        let (ctxt, image_t, signal_t) = image_and_signal_context();
+        assert_ne!(image_t, signal_t);
+        assert_eq!(ctxt.type_info_directory.get_type_id_by_name("Signal").unwrap(), signal_t);
+        assert_eq!(ctxt.type_info_directory.get_vector_metadata(signal_t).unwrap().unit,
+                   Option::Some(String::from("volts")));
+
+        let func_t = ctxt.get_func_type_id(image_t, signal_t);
+        assert_eq!(ctxt.display_type(signal_t), "Signal");
+        assert_eq!(ctxt.display_type(func_t), "(Image -> Signal)");
+        assert_eq!(ctxt.get_type(func_t).display(&ctxt), "(Image -> Signal)");
+        assert_eq!(ctxt.parse_type(&ctxt.display_type(func_t)).unwrap(), func_t);
+    }
+
+    #[test]
+    fn vectors_display_with_type_names() {
+        let (ctxt, _, signal_t) = image_and_signal_context();
+        let state = InterpreterState::new(&ctxt);
+        let vec_ref = TermReference::VecRef(signal_t, to_noisy(array![1.0f32, 2.0f32, 3.0f32, 4.0f32].view()));
+        assert_eq!(vec_ref.display(&state), "[1, 2, 3, 4] : Signal");
+    }
+
+    #[test]
+    fn mismatched_shapes_are_rejected() {
+        let mut type_info_directory = TypeInfoDirectory::new();
+        let metadata = VectorTypeMetadata {
+            shape : Option::Some(vec![3, 3]),
+            ..VectorTypeMetadata::new("Image")
+        };
+        assert_eq!(type_info_directory.add_named_vector_type(8, metadata).err(),
+                   Option::Some(FetishError::ShapeMismatch {
+                       name : String::from("Image"),
+                       expected : 8,
+                       actual : 9
+                   }));
+    }
+}
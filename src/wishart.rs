@@ -32,28 +32,28 @@ impl Wishart {
         }
     }
     ///Draws the pseudoinverse of a sample from this Wishart distribution
-    pub fn sample_inv(&self, rng : &mut ThreadRng) -> Array2<f32> {
+    pub fn sample_inv<R : Rng + ?Sized>(&self, rng : &mut R) -> Array2<f32> {
         let sample = self.sample(rng);
         let result = pseudoinverse_h(&sample);
         result
     }
 
     ///Draws a sample from this Wishart distribution
-    pub fn sample(&self, rng : &mut ThreadRng) -> Array2<f32> {
+    pub fn sample<R : Rng + ?Sized>(&self, rng : &mut R) -> Array2<f32> {
         let L = self.sample_cholesky_factor(rng);
 	let result = L.dot(&L.t());
         result
     }
 
     ///Draws a matrix `L` such that `(L * L_^T)^(-1)` has this Wishart distribution
-    pub fn sample_inv_cholesky_factor(&self, rng : &mut ThreadRng) -> Array2<f32> {
+    pub fn sample_inv_cholesky_factor<R : Rng + ?Sized>(&self, rng : &mut R) -> Array2<f32> {
         let sample_inv = self.sample_inv(rng);
         let cholesky_factor = sqrtm(&sample_inv);
         cholesky_factor
     }
 
     ///Draws a matrix `L` such that `L * L^T` has this Wishart distribution.
-    pub fn sample_cholesky_factor(&self, rng : &mut ThreadRng) -> Array2<f32> {
+    pub fn sample_cholesky_factor<R : Rng + ?Sized>(&self, rng : &mut R) -> Array2<f32> {
         //Following https://github.com/scipy/scipy/blob/v1.5.1/scipy/stats/_multivariate.py
         //and https://www.math.wustl.edu/~sawyer/hmhandouts/Wishart.pdf,
         //first sample a lower-diagonal matrix whose off diagonal elements